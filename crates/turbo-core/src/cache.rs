@@ -0,0 +1,654 @@
+//! Local cache (`cacheDir`) inspection and garbage collection
+
+use std::{
+    cmp::Reverse,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::TurboConfig,
+    error::{Error, Result},
+    runs::RunSummary,
+};
+
+/// Default cache directories (turbo 2.x first, then turbo 1.x)
+pub const DEFAULT_CACHE_DIRS: &[&str] = &[".turbo/cache", "node_modules/.cache/turbo"];
+
+/// Suffix of the metadata file written next to each artifact
+const META_SUFFIX: &str = "-meta.json";
+
+/// Length of a turbo task hash (64-bit xxhash in hex)
+const HASH_LEN: usize = 16;
+
+/// A local cache directory
+#[derive(Debug, Clone)]
+pub struct LocalCache {
+    dir: PathBuf,
+}
+
+/// A cached artifact
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    /// Task hash the artifact was stored under
+    pub hash: String,
+    /// Archive file (or directory for legacy caches)
+    pub path: PathBuf,
+    /// Archive format
+    pub format: ArchiveFormat,
+    /// Size on disk in bytes, including metadata
+    pub size_bytes: u64,
+    /// Creation time in milliseconds since the Unix epoch
+    pub created_at: i64,
+    /// Task duration recorded in the metadata, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// `package#task` that produced the artifact, when run summaries know the hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+}
+
+/// Artifact archive format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArchiveFormat {
+    /// `<hash>.tar.zst` (turbo >= 1.10)
+    TarZst,
+    /// `<hash>.tar` (uncompressed)
+    Tar,
+    /// `<hash>/` directory (turbo 1.x Go cache)
+    Directory,
+}
+
+/// A file stored inside an artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchiveFile {
+    /// Path relative to the repo root
+    pub path: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+}
+
+/// Summary of a cache directory
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheInventory {
+    /// Cache directory
+    pub dir: PathBuf,
+    /// Number of artifacts
+    pub entry_count: usize,
+    /// Total size on disk in bytes
+    pub total_bytes: u64,
+    /// Artifacts, newest first
+    pub entries: Vec<CacheEntry>,
+}
+
+/// Eviction policy for [`LocalCache::gc`]
+///
+/// Unset limits are not enforced, so the default policy evicts nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcPolicy {
+    /// Maximum total size of the cache in bytes
+    pub max_total_bytes: Option<u64>,
+    /// Maximum artifact age in milliseconds
+    pub max_age_ms: Option<u64>,
+    /// Keep only artifacts referenced by the last N runs
+    pub keep_runs: Option<usize>,
+}
+
+impl GcPolicy {
+    /// Whether no limit is set
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.max_total_bytes.is_none() && self.max_age_ms.is_none() && self.keep_runs.is_none()
+    }
+}
+
+/// Why an artifact was evicted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EvictionReason {
+    /// Older than `max_age_ms`
+    Expired,
+    /// Not referenced by the last `keep_runs` runs
+    Unreferenced,
+    /// Oldest artifact while the cache exceeded `max_total_bytes`
+    OverSize,
+    /// Removed by [`LocalCache::clear`]
+    Cleared,
+}
+
+/// An artifact selected for eviction
+#[derive(Debug, Clone, Serialize)]
+pub struct Eviction {
+    /// The artifact
+    #[serde(flatten)]
+    pub entry: CacheEntry,
+    /// Policy that selected it
+    pub reason: EvictionReason,
+}
+
+/// Result of a garbage collection (or its dry run)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    /// Cache directory
+    pub dir: PathBuf,
+    /// Nothing was deleted; the report lists what would be freed
+    pub dry_run: bool,
+    /// Evicted artifacts, newest first
+    pub evicted: Vec<Eviction>,
+    /// Bytes freed (or that would be freed)
+    pub freed_bytes: u64,
+    /// Number of artifacts kept
+    pub kept_count: usize,
+    /// Bytes kept
+    pub kept_bytes: u64,
+}
+
+/// Contents of `<hash>-meta.json`
+#[derive(Debug, Deserialize)]
+struct CacheMeta {
+    #[serde(default)]
+    duration: Option<u64>,
+}
+
+impl LocalCache {
+    /// Cache rooted at `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Existing cache directories for a repo
+    ///
+    /// An explicit `cacheDir` wins; otherwise every default location that exists is returned.
+    #[must_use]
+    pub fn discover(repo_root: &Path, config: Option<&TurboConfig>) -> Vec<Self> {
+        if let Some(dir) = config.and_then(|config| config.cache_dir.as_deref()) {
+            return vec![Self::new(repo_root.join(dir))];
+        }
+        DEFAULT_CACHE_DIRS
+            .iter()
+            .map(|dir| repo_root.join(dir))
+            .filter(|dir| dir.is_dir())
+            .map(Self::new)
+            .collect()
+    }
+
+    /// Cache directory
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// List artifacts, newest first
+    ///
+    /// # Errors
+    /// Returns error if the cache directory cannot be read
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let read_dir = fs::read_dir(&self.dir).map_err(|e| Error::ReadFile {
+            path: self.dir.clone(),
+            source: e,
+        })?;
+
+        let mut entries = Vec::new();
+        for item in read_dir.flatten() {
+            let path = item.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Ok(metadata) = item.metadata() else {
+                continue;
+            };
+
+            let (hash, format) = if let Some(hash) = name.strip_suffix(".tar.zst") {
+                (hash, ArchiveFormat::TarZst)
+            } else if let Some(hash) = name.strip_suffix(".tar") {
+                (hash, ArchiveFormat::Tar)
+            } else if metadata.is_dir() {
+                (name, ArchiveFormat::Directory)
+            } else {
+                continue;
+            };
+            if !is_artifact(&self.dir, hash) {
+                continue;
+            }
+
+            let meta_path = self.dir.join(format!("{hash}{META_SUFFIX}"));
+            let meta_size = fs::metadata(&meta_path).map_or(0, |m| m.len());
+            let size = if metadata.is_dir() {
+                dir_size(&path)
+            } else {
+                metadata.len()
+            };
+
+            entries.push(CacheEntry {
+                hash: hash.to_string(),
+                format,
+                size_bytes: size + meta_size,
+                created_at: epoch_millis(metadata.created().or_else(|_| metadata.modified()).ok()),
+                duration_ms: read_meta(&meta_path).and_then(|meta| meta.duration),
+                task_id: None,
+                path,
+            });
+        }
+
+        entries.sort_by_key(|entry| Reverse(entry.created_at));
+        Ok(entries)
+    }
+
+    /// List artifacts and attribute them to tasks using run summaries
+    ///
+    /// # Errors
+    /// Returns error if the cache directory cannot be read
+    pub fn inspect(&self, runs: &[RunSummary]) -> Result<CacheInventory> {
+        let producers: IndexMap<&str, &str> = runs
+            .iter()
+            .flat_map(|run| &run.tasks)
+            .map(|task| (task.hash.as_str(), task.task_id.as_str()))
+            .collect();
+
+        let mut entries = self.entries()?;
+        for entry in &mut entries {
+            entry.task_id = producers
+                .get(entry.hash.as_str())
+                .map(|id| (*id).to_string());
+        }
+
+        Ok(CacheInventory {
+            dir: self.dir.clone(),
+            entry_count: entries.len(),
+            total_bytes: entries.iter().map(|entry| entry.size_bytes).sum(),
+            entries,
+        })
+    }
+
+    /// List the files stored in an artifact
+    ///
+    /// # Errors
+    /// Returns error if the artifact does not exist or cannot be decoded
+    pub fn archive_files(&self, hash: &str) -> Result<Vec<ArchiveFile>> {
+        let entry = self
+            .entries()?
+            .into_iter()
+            .find(|entry| entry.hash == hash)
+            .ok_or_else(|| Error::CacheEntryNotFound(hash.to_string()))?;
+
+        match entry.format {
+            ArchiveFormat::TarZst => {
+                let file = open(&entry.path)?;
+                let decoder = zstd::Decoder::new(file).map_err(|e| Error::ReadFile {
+                    path: entry.path.clone(),
+                    source: e,
+                })?;
+                tar_files(decoder, &entry.path)
+            }
+            ArchiveFormat::Tar => tar_files(open(&entry.path)?, &entry.path),
+            ArchiveFormat::Directory => Ok(directory_files(&entry.path)),
+        }
+    }
+
+    /// Evict artifacts according to `policy`
+    ///
+    /// Expired artifacts go first, then artifacts not referenced by the last
+    /// `keep_runs` runs (skipped when there is no run history), then the oldest
+    /// remaining artifacts until the cache fits in `max_total_bytes`.
+    /// With `dry_run` nothing is deleted.
+    ///
+    /// # Errors
+    /// Returns error if the cache directory cannot be read or an artifact cannot be removed
+    pub fn gc(&self, policy: &GcPolicy, runs: &[RunSummary], dry_run: bool) -> Result<GcReport> {
+        let now = epoch_millis(Some(SystemTime::now()));
+        let (evicted, kept) = plan_evictions(self.inspect(runs)?.entries, policy, runs, now);
+        self.evict(evicted, &kept, dry_run)
+    }
+
+    /// Evict every artifact
+    ///
+    /// # Errors
+    /// Returns error if the cache directory cannot be read or an artifact cannot be removed
+    pub fn clear(&self, runs: &[RunSummary], dry_run: bool) -> Result<GcReport> {
+        let evicted = self
+            .inspect(runs)?
+            .entries
+            .into_iter()
+            .map(|entry| Eviction {
+                entry,
+                reason: EvictionReason::Cleared,
+            })
+            .collect();
+        self.evict(evicted, &[], dry_run)
+    }
+
+    fn evict(
+        &self,
+        evicted: Vec<Eviction>,
+        kept: &[CacheEntry],
+        dry_run: bool,
+    ) -> Result<GcReport> {
+        if !dry_run {
+            for eviction in &evicted {
+                remove_artifact(&self.dir, &eviction.entry)?;
+            }
+        }
+        Ok(GcReport {
+            dir: self.dir.clone(),
+            dry_run,
+            freed_bytes: evicted.iter().map(|e| e.entry.size_bytes).sum(),
+            evicted,
+            kept_count: kept.len(),
+            kept_bytes: kept.iter().map(|entry| entry.size_bytes).sum(),
+        })
+    }
+}
+
+/// Split newest-first `entries` into (evicted, kept)
+fn plan_evictions(
+    entries: Vec<CacheEntry>,
+    policy: &GcPolicy,
+    runs: &[RunSummary],
+    now_ms: i64,
+) -> (Vec<Eviction>, Vec<CacheEntry>) {
+    // without history every artifact would look unreferenced
+    let referenced: Option<IndexSet<&str>> =
+        policy.keep_runs.filter(|_| !runs.is_empty()).map(|n| {
+            runs[runs.len().saturating_sub(n)..]
+                .iter()
+                .flat_map(|run| &run.tasks)
+                .map(|task| task.hash.as_str())
+                .collect()
+        });
+
+    let mut evicted = Vec::new();
+    let mut kept = Vec::new();
+    let mut kept_bytes = 0_u64;
+    for entry in entries {
+        let age = u64::try_from(now_ms.saturating_sub(entry.created_at)).unwrap_or_default();
+        let reason = if policy.max_age_ms.is_some_and(|max| age > max) {
+            Some(EvictionReason::Expired)
+        } else if referenced
+            .as_ref()
+            .is_some_and(|hashes| !hashes.contains(entry.hash.as_str()))
+        {
+            Some(EvictionReason::Unreferenced)
+        } else {
+            // entries are newest first, so once the limit is hit every older artifact goes
+            kept_bytes = kept_bytes.saturating_add(entry.size_bytes);
+            policy
+                .max_total_bytes
+                .filter(|max| kept_bytes > *max)
+                .map(|_| EvictionReason::OverSize)
+        };
+        match reason {
+            Some(reason) => evicted.push(Eviction { entry, reason }),
+            None => kept.push(entry),
+        }
+    }
+    (evicted, kept)
+}
+
+fn remove_artifact(dir: &Path, entry: &CacheEntry) -> Result<()> {
+    let to_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |e| Error::RemoveFile { path, source: e }
+    };
+    if !is_artifact(dir, &entry.hash) {
+        return Err(Error::InvalidPath(entry.path.clone()));
+    }
+    match entry.format {
+        ArchiveFormat::Directory => fs::remove_dir_all(&entry.path),
+        ArchiveFormat::TarZst | ArchiveFormat::Tar => fs::remove_file(&entry.path),
+    }
+    .map_err(to_error(&entry.path))?;
+
+    let meta_path = dir.join(format!("{}{META_SUFFIX}", entry.hash));
+    match fs::remove_file(&meta_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(to_error(&meta_path)(e)),
+        _ => Ok(()),
+    }
+}
+
+/// Whether an archive or legacy directory in the cache is an artifact rather than
+/// something else living there: its hash is a task hash or it has a metadata file
+fn is_artifact(dir: &Path, hash: &str) -> bool {
+    let hash_shaped = hash.len() == HASH_LEN && hash.bytes().all(|b| b.is_ascii_hexdigit());
+    hash_shaped || dir.join(format!("{hash}{META_SUFFIX}")).is_file()
+}
+
+fn open(path: &Path) -> Result<fs::File> {
+    fs::File::open(path).map_err(|e| Error::ReadFile {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+fn tar_files(reader: impl Read, path: &Path) -> Result<Vec<ArchiveFile>> {
+    let to_error = |e: std::io::Error| Error::ReadFile {
+        path: path.to_path_buf(),
+        source: e,
+    };
+
+    let mut archive = tar::Archive::new(reader);
+    let mut files = Vec::new();
+    for entry in archive.entries().map_err(to_error)? {
+        let entry = entry.map_err(to_error)?;
+        if entry.header().entry_type().is_dir() {
+            continue;
+        }
+        files.push(ArchiveFile {
+            path: entry.path().map_err(to_error)?.display().to_string(),
+            size: entry.size(),
+        });
+    }
+    Ok(files)
+}
+
+fn directory_files(dir: &Path) -> Vec<ArchiveFile> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(read_dir) = fs::read_dir(&current) else {
+            continue;
+        };
+        for item in read_dir.flatten() {
+            let path = item.path();
+            match item.metadata() {
+                Ok(metadata) if metadata.is_dir() => stack.push(path),
+                Ok(metadata) => files.push(ArchiveFile {
+                    path: path
+                        .strip_prefix(dir)
+                        .unwrap_or(&path)
+                        .display()
+                        .to_string(),
+                    size: metadata.len(),
+                }),
+                Err(_) => {}
+            }
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    directory_files(dir).iter().map(|file| file.size).sum()
+}
+
+fn read_meta(path: &Path) -> Option<CacheMeta> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn epoch_millis(time: Option<SystemTime>) -> i64 {
+    time.and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .and_then(|duration| i64::try_from(duration.as_millis()).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn write_artifact(dir: &Path, hash: &str, files: &[(&str, &[u8])]) {
+        let file = fs::File::create(dir.join(format!("{hash}.tar.zst"))).unwrap();
+        let encoder = zstd::Encoder::new(file, 0).unwrap().auto_finish();
+        let mut builder = tar::Builder::new(encoder);
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.finish().unwrap();
+        fs::write(
+            dir.join(format!("{hash}{META_SUFFIX}")),
+            format!(r#"{{"hash":"{hash}","duration":1200}}"#),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_inspect_attributes_artifacts() {
+        let dir = TempDir::new("cache-inspect");
+        write_artifact(
+            &dir,
+            "abc123",
+            &[("apps/web/dist/index.js", b"console.log(1)")],
+        );
+        let run = RunSummary::parse(
+            r#"{"tasks": [{"taskId": "web#build", "task": "build", "hash": "abc123"}]}"#,
+            None,
+        )
+        .unwrap();
+
+        let inventory = LocalCache::new(dir.path()).inspect(&[run]).unwrap();
+
+        assert_eq!(inventory.entry_count, 1);
+        assert_eq!(inventory.entries[0].task_id.as_deref(), Some("web#build"));
+        assert_eq!(inventory.entries[0].duration_ms, Some(1200));
+        assert!(inventory.total_bytes > 0);
+    }
+
+    fn entry(hash: &str, size_bytes: u64, created_at: i64) -> CacheEntry {
+        CacheEntry {
+            hash: hash.to_string(),
+            path: PathBuf::from(format!("{hash}.tar.zst")),
+            format: ArchiveFormat::TarZst,
+            size_bytes,
+            created_at,
+            duration_ms: None,
+            task_id: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_evictions() {
+        let entries = vec![
+            entry("new", 40, 9_000),
+            entry("mid", 40, 8_000),
+            entry("unused", 10, 7_500),
+            entry("old", 40, 7_000),
+            entry("ancient", 40, 1_000),
+        ];
+        let run = RunSummary::parse(
+            r#"{"tasks": [
+                {"taskId": "a#build", "hash": "new"},
+                {"taskId": "b#build", "hash": "mid"},
+                {"taskId": "c#build", "hash": "old"},
+                {"taskId": "d#build", "hash": "ancient"}
+            ]}"#,
+            None,
+        )
+        .unwrap();
+        let policy = GcPolicy {
+            max_total_bytes: Some(100),
+            max_age_ms: Some(5_000),
+            keep_runs: Some(1),
+        };
+
+        let (evicted, kept) = plan_evictions(entries, &policy, &[run], 10_000);
+
+        let reasons: Vec<_> = evicted
+            .iter()
+            .map(|e| (e.entry.hash.as_str(), e.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("unused", EvictionReason::Unreferenced),
+                ("old", EvictionReason::OverSize),
+                ("ancient", EvictionReason::Expired),
+            ]
+        );
+        assert_eq!(kept.len(), 2);
+    }
+
+    #[test]
+    fn test_gc_dry_run_and_delete() {
+        let dir = TempDir::new("cache-gc");
+        write_artifact(&dir, "aaa111", &[("dist/a.js", b"a")]);
+        let cache = LocalCache::new(dir.path());
+
+        let report = cache.clear(&[], true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.evicted.len(), 1);
+        assert_eq!(cache.entries().unwrap().len(), 1);
+
+        let report = cache.clear(&[], false).unwrap();
+        assert!(report.freed_bytes > 0);
+        assert!(cache.entries().unwrap().is_empty());
+        assert!(!dir.join(format!("aaa111{META_SUFFIX}")).exists());
+    }
+
+    #[test]
+    fn test_clear_keeps_unrelated_files_and_directories() {
+        let dir = TempDir::new("cache-unrelated");
+        dir.write("0123456789abcdef/dist/a.js", "a");
+        dir.write("legacy/dist/b.js", "b");
+        dir.write(&format!("legacy{META_SUFFIX}"), "{}");
+        dir.write("notes/todo.md", "keep me");
+        dir.write("fedcba9876543210.tar", "");
+        dir.write("release.tar.zst", "keep me");
+        let cache = LocalCache::new(dir.path());
+
+        let hashes: Vec<_> = cache
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.hash)
+            .collect();
+        assert_eq!(hashes.len(), 3);
+        assert!(!hashes.contains(&"notes".to_string()));
+        assert!(!hashes.contains(&"release".to_string()));
+
+        cache.clear(&[], false).unwrap();
+        assert!(!dir.join("0123456789abcdef").exists());
+        assert!(!dir.join("legacy").exists());
+        assert!(!dir.join("fedcba9876543210.tar").exists());
+        assert!(dir.join("notes/todo.md").exists());
+        assert!(dir.join("release.tar.zst").exists());
+    }
+
+    #[test]
+    fn test_archive_files() {
+        let dir = TempDir::new("cache-archive");
+        write_artifact(&dir, "def456", &[("apps/web/dist/index.js", b"1234")]);
+
+        let files = LocalCache::new(dir.path()).archive_files("def456").unwrap();
+
+        assert_eq!(
+            files,
+            vec![ArchiveFile {
+                path: "apps/web/dist/index.js".to_string(),
+                size: 4,
+            }]
+        );
+    }
+}
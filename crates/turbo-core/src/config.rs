@@ -7,6 +7,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
 
+/// turbo.json file names, in lookup order
+pub const CONFIG_FILES: &[&str] = &["turbo.json", "turbo.jsonc"];
+
 /// Parsed turbo.json configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -139,7 +142,7 @@ impl TurboConfig {
     pub fn find_config_path(start_dir: &Path) -> Result<PathBuf> {
         let mut current = start_dir.to_path_buf();
         loop {
-            for name in ["turbo.json", "turbo.jsonc"] {
+            for name in CONFIG_FILES {
                 let path = current.join(name);
                 if path.exists() {
                     return Ok(path);
@@ -152,6 +155,33 @@ impl TurboConfig {
         Err(Error::ConfigNotFound(start_dir.to_path_buf()))
     }
 
+    /// Find the monorepo root for a path
+    ///
+    /// Package turbo.json files (`apps/web/turbo.json`) sit below the root, so
+    /// the nearest directory with a turbo.json that is also a workspace root
+    /// (pnpm-workspace.yaml, or a package.json with `workspaces`) wins. Without
+    /// one, the outermost directory with a turbo.json is used.
+    #[must_use]
+    pub fn find_repo_root(start: &Path) -> Option<PathBuf> {
+        let has_config = |dir: &Path| CONFIG_FILES.iter().any(|name| dir.join(name).is_file());
+        let is_workspace_root = |dir: &Path| {
+            dir.join("pnpm-workspace.yaml").is_file()
+                || std::fs::read_to_string(dir.join("package.json"))
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                    .is_some_and(|package| package.get("workspaces").is_some())
+        };
+
+        let mut outermost = None;
+        for dir in start.ancestors().filter(|dir| has_config(dir)) {
+            if is_workspace_root(dir) {
+                return Some(dir.to_path_buf());
+            }
+            outermost = Some(dir.to_path_buf());
+        }
+        outermost
+    }
+
     /// Load and parse turbo.json from a specific path
     ///
     /// # Errors
@@ -282,6 +312,21 @@ pub fn strip_json_comments(input: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_find_repo_root_skips_package_configs() {
+        let root = TempDir::new("repo-root");
+        root.write("turbo.json", "{}");
+        root.write("package.json", r#"{"workspaces": ["apps/*"]}"#);
+        root.write("apps/web/turbo.json", r#"{"extends": ["//"]}"#);
+        std::fs::create_dir_all(root.join("apps/web/src")).unwrap();
+
+        assert_eq!(
+            TurboConfig::find_repo_root(&root.join("apps/web/src")),
+            Some(root.to_path_buf())
+        );
+    }
 
     #[test]
     fn test_strip_line_comments() {
@@ -0,0 +1,445 @@
+//! Critical path and parallelism analysis of a task graph
+
+use std::cmp::Reverse;
+
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+
+use crate::{
+    error::{Error, Result},
+    runs::{RunSummary, TaskStats},
+};
+
+/// Default `--concurrency` used by turbo
+pub const DEFAULT_CONCURRENCY: usize = 10;
+
+/// Task graph with a duration per task
+#[derive(Debug, Clone, Default)]
+pub struct TaskGraph {
+    nodes: IndexMap<String, TaskNode>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TaskNode {
+    task: String,
+    duration_ms: u64,
+    dependencies: Vec<String>,
+}
+
+/// Result of [`TaskGraph::analyze`]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CriticalPathAnalysis {
+    /// Tasks on the longest dependency chain, in execution order
+    pub critical_path: Vec<PathStep>,
+    /// Length of the critical path in milliseconds
+    pub critical_path_ms: u64,
+    /// Sum of every task's duration in milliseconds
+    pub total_work_ms: u64,
+    /// Concurrency the wall-time figures assume
+    pub concurrency: usize,
+    /// Theoretical minimum wall time: `max(critical path, total work / concurrency)`
+    pub lower_bound_ms: u64,
+    /// Wall time of a critical-path-first schedule at `concurrency`
+    pub estimated_wall_time_ms: u64,
+    /// Critical tasks ranked by how much detaching them from the chain saves
+    pub bottlenecks: Vec<Saving>,
+    /// Critical `dependsOn` edges ranked by how much removing them saves
+    pub edges: Vec<EdgeSaving>,
+}
+
+/// A task on the critical path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathStep {
+    /// Task id
+    pub task_id: String,
+    /// Task duration in milliseconds
+    pub duration_ms: u64,
+    /// Earliest start time in milliseconds with unlimited concurrency
+    pub start_ms: u64,
+}
+
+/// Critical path reduction from detaching a task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Saving {
+    /// Task id
+    pub task_id: String,
+    /// Milliseconds the critical path would shrink by
+    pub savings_ms: u64,
+}
+
+/// Critical path reduction from removing a single dependency edge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeSaving {
+    /// Dependent task id
+    pub task_id: String,
+    /// Dependency task id
+    pub depends_on: String,
+    /// Milliseconds the critical path would shrink by
+    pub savings_ms: u64,
+}
+
+/// Edge or node excluded while recomputing the longest path
+#[derive(Clone, Copy)]
+enum Exclude<'a> {
+    None,
+    Edge(&'a str, &'a str),
+    Node(&'a str),
+}
+
+impl TaskGraph {
+    /// Build a graph from a run summary (or `turbo run --dry=json` output)
+    ///
+    /// Durations prefer the historical average from `stats` and fall back to the
+    /// duration recorded in `summary`.
+    #[must_use]
+    pub fn from_summary(summary: &RunSummary, stats: &IndexMap<String, TaskStats>) -> Self {
+        let nodes = summary
+            .tasks
+            .iter()
+            .map(|task| {
+                let duration_ms = stats
+                    .get(&task.task_id)
+                    .map(|s| s.average_ms)
+                    .filter(|ms| *ms > 0)
+                    .or_else(|| task.duration_ms())
+                    .unwrap_or_default();
+                (
+                    task.task_id.clone(),
+                    TaskNode {
+                        task: task.task.clone(),
+                        duration_ms,
+                        dependencies: task.dependencies.clone(),
+                    },
+                )
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Add a task with its duration and dependencies
+    pub fn add_task(
+        &mut self,
+        task_id: impl Into<String>,
+        duration_ms: u64,
+        dependencies: impl IntoIterator<Item = impl Into<String>>,
+    ) {
+        let task_id = task_id.into();
+        let task = task_id
+            .rsplit_once('#')
+            .map_or(task_id.as_str(), |(_, task)| task)
+            .to_string();
+        self.nodes.insert(
+            task_id,
+            TaskNode {
+                task,
+                duration_ms,
+                dependencies: dependencies.into_iter().map(Into::into).collect(),
+            },
+        );
+    }
+
+    /// Whether the graph has no tasks
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Subgraph needed to run `task` (by task name or id): matching tasks and their dependencies
+    #[must_use]
+    pub fn for_task(&self, task: &str) -> Self {
+        let mut keep = IndexSet::new();
+        let mut stack: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|(id, node)| node.task == task || id.as_str() == task)
+            .map(|(id, _)| id.as_str())
+            .collect();
+
+        while let Some(id) = stack.pop() {
+            if !keep.insert(id) {
+                continue;
+            }
+            if let Some(node) = self.nodes.get(id) {
+                stack.extend(node.dependencies.iter().map(String::as_str));
+            }
+        }
+
+        Self {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|(id, _)| keep.contains(id.as_str()))
+                .map(|(id, node)| (id.clone(), node.clone()))
+                .collect(),
+        }
+    }
+
+    /// Compute the critical path, wall-time estimates and the most valuable cuts
+    ///
+    /// # Errors
+    /// Returns `Error::CyclicTaskGraph` if the dependencies contain a cycle
+    pub fn analyze(&self, concurrency: usize) -> Result<CriticalPathAnalysis> {
+        let order = self.topological_order()?;
+        let concurrency = concurrency.max(1);
+        let (finish, predecessor) = self.longest_paths(&order, Exclude::None);
+
+        let mut critical_path = Vec::new();
+        let mut cursor = finish
+            .iter()
+            .max_by_key(|(_, finish)| **finish)
+            .map(|(id, _)| *id);
+        while let Some(id) = cursor {
+            let duration_ms = self.nodes[id].duration_ms;
+            critical_path.push(PathStep {
+                task_id: id.to_string(),
+                duration_ms,
+                start_ms: finish[id] - duration_ms,
+            });
+            cursor = predecessor.get(id).copied().flatten();
+        }
+        critical_path.reverse();
+
+        let critical_path_ms = finish.values().copied().max().unwrap_or_default();
+        let total_work_ms: u64 = self.nodes.values().map(|node| node.duration_ms).sum();
+        let lower_bound_ms = critical_path_ms.max(total_work_ms.div_ceil(concurrency as u64));
+
+        let mut bottlenecks: Vec<Saving> = critical_path
+            .iter()
+            .map(|step| Saving {
+                task_id: step.task_id.clone(),
+                savings_ms: critical_path_ms
+                    - self.critical_length(&order, Exclude::Node(&step.task_id)),
+            })
+            .filter(|saving| saving.savings_ms > 0)
+            .collect();
+        bottlenecks.sort_by_key(|saving| Reverse(saving.savings_ms));
+
+        let mut edges: Vec<EdgeSaving> = critical_path
+            .windows(2)
+            .map(|pair| {
+                let (dependency, dependent) = (&pair[0].task_id, &pair[1].task_id);
+                EdgeSaving {
+                    task_id: dependent.clone(),
+                    depends_on: dependency.clone(),
+                    savings_ms: critical_path_ms
+                        - self.critical_length(&order, Exclude::Edge(dependent, dependency)),
+                }
+            })
+            .filter(|saving| saving.savings_ms > 0)
+            .collect();
+        edges.sort_by_key(|saving| Reverse(saving.savings_ms));
+
+        Ok(CriticalPathAnalysis {
+            critical_path,
+            critical_path_ms,
+            total_work_ms,
+            concurrency,
+            lower_bound_ms,
+            estimated_wall_time_ms: self.simulate(&order, concurrency),
+            bottlenecks,
+            edges,
+        })
+    }
+
+    /// Kahn's algorithm; dependencies outside the graph are ignored
+    fn topological_order(&self) -> Result<Vec<&str>> {
+        let mut remaining: IndexMap<&str, usize> = self
+            .nodes
+            .iter()
+            .map(|(id, node)| (id.as_str(), self.known_dependencies(node).count()))
+            .collect();
+        let mut dependents: IndexMap<&str, Vec<&str>> = IndexMap::new();
+        for (id, node) in &self.nodes {
+            for dependency in self.known_dependencies(node) {
+                dependents.entry(dependency).or_default().push(id);
+            }
+        }
+
+        let mut ready: Vec<&str> = remaining
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(id) = ready.pop() {
+            order.push(id);
+            for dependent in dependents.get(id).into_iter().flatten() {
+                let count = remaining.entry(dependent).or_default();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+
+        if order.len() == self.nodes.len() {
+            Ok(order)
+        } else {
+            let stuck = remaining
+                .iter()
+                .find(|(_, count)| **count > 0)
+                .map(|(id, _)| (*id).to_string())
+                .unwrap_or_default();
+            Err(Error::CyclicTaskGraph(stuck))
+        }
+    }
+
+    fn known_dependencies<'a>(&'a self, node: &'a TaskNode) -> impl Iterator<Item = &'a str> {
+        node.dependencies
+            .iter()
+            .map(String::as_str)
+            .filter(|dependency| self.nodes.contains_key(*dependency))
+    }
+
+    /// Earliest finish time per task and the predecessor that determined it
+    fn longest_paths<'a>(
+        &'a self,
+        order: &[&'a str],
+        exclude: Exclude<'_>,
+    ) -> (IndexMap<&'a str, u64>, IndexMap<&'a str, Option<&'a str>>) {
+        let mut finish: IndexMap<&str, u64> = IndexMap::with_capacity(order.len());
+        let mut predecessor = IndexMap::with_capacity(order.len());
+
+        for &id in order {
+            let node = &self.nodes[id];
+            let detached = matches!(exclude, Exclude::Node(excluded) if excluded == id);
+            let mut best: Option<(&str, u64)> = None;
+            for dependency in self.known_dependencies(node) {
+                let skipped = detached
+                    || matches!(exclude, Exclude::Node(excluded) if excluded == dependency)
+                    || matches!(exclude, Exclude::Edge(from, to) if from == id && to == dependency);
+                if skipped {
+                    continue;
+                }
+                let candidate = finish[dependency];
+                if best.is_none_or(|(_, current)| candidate > current) {
+                    best = Some((dependency, candidate));
+                }
+            }
+            finish.insert(id, best.map_or(0, |(_, t)| t) + node.duration_ms);
+            predecessor.insert(id, best.map(|(dependency, _)| dependency));
+        }
+
+        (finish, predecessor)
+    }
+
+    fn critical_length(&self, order: &[&str], exclude: Exclude<'_>) -> u64 {
+        let (finish, _) = self.longest_paths(order, exclude);
+        finish.values().copied().max().unwrap_or_default()
+    }
+
+    /// List scheduling that always starts the task with the longest remaining chain first
+    fn simulate(&self, order: &[&str], concurrency: usize) -> u64 {
+        let mut dependents: IndexMap<&str, Vec<&str>> = IndexMap::new();
+        for &id in order {
+            for dependency in self.known_dependencies(&self.nodes[id]) {
+                dependents.entry(dependency).or_default().push(id);
+            }
+        }
+
+        // Remaining chain length: task duration plus its longest dependent chain
+        let mut tail: IndexMap<&str, u64> = IndexMap::with_capacity(order.len());
+        for &id in order.iter().rev() {
+            let longest_dependent = dependents
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter_map(|dependent| tail.get(dependent))
+                .max()
+                .copied()
+                .unwrap_or_default();
+            tail.insert(id, self.nodes[id].duration_ms + longest_dependent);
+        }
+
+        let mut done: IndexMap<&str, u64> = IndexMap::new();
+        let mut running: Vec<(&str, u64)> = Vec::new();
+        let mut now = 0;
+
+        while done.len() < order.len() {
+            let mut ready: Vec<&str> = order
+                .iter()
+                .copied()
+                .filter(|id| !done.contains_key(id) && !running.iter().any(|(r, _)| r == id))
+                .filter(|id| {
+                    self.known_dependencies(&self.nodes[*id])
+                        .all(|dependency| done.contains_key(dependency))
+                })
+                .collect();
+            ready.sort_by_key(|id| Reverse(tail[id]));
+
+            for id in ready.into_iter().take(concurrency - running.len()) {
+                running.push((id, now + self.nodes[id].duration_ms));
+            }
+
+            let Some(next) = running.iter().map(|(_, end)| *end).min() else {
+                break;
+            };
+            now = next;
+            running.retain(|(id, end)| {
+                if *end <= now {
+                    done.insert(id, *end);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> TaskGraph {
+        let mut graph = TaskGraph::default();
+        graph.add_task("ui#build", 4_000, Vec::<String>::new());
+        graph.add_task("utils#build", 1_000, Vec::<String>::new());
+        graph.add_task("web#codegen", 2_000, Vec::<String>::new());
+        graph.add_task(
+            "web#build",
+            5_000,
+            ["ui#build", "utils#build", "web#codegen"],
+        );
+        graph.add_task("docs#build", 3_000, ["ui#build"]);
+        graph
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let analysis = graph().analyze(DEFAULT_CONCURRENCY).unwrap();
+        let path: Vec<_> = analysis
+            .critical_path
+            .iter()
+            .map(|step| step.task_id.as_str())
+            .collect();
+
+        assert_eq!(path, ["ui#build", "web#build"]);
+        assert_eq!(analysis.critical_path_ms, 9_000);
+        assert_eq!(analysis.total_work_ms, 15_000);
+        assert_eq!(analysis.estimated_wall_time_ms, 9_000);
+        // Without `web#build -> ui#build` the chain becomes codegen -> build
+        assert_eq!(analysis.edges[0].savings_ms, 2_000);
+    }
+
+    #[test]
+    fn test_limited_concurrency() {
+        let analysis = graph().analyze(1).unwrap();
+        assert_eq!(analysis.lower_bound_ms, 15_000);
+        assert_eq!(analysis.estimated_wall_time_ms, 15_000);
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let mut graph = TaskGraph::default();
+        graph.add_task("a#build", 1, ["b#build"]);
+        graph.add_task("b#build", 1, ["a#build"]);
+        assert!(graph.analyze(1).is_err());
+    }
+}
@@ -3,8 +3,11 @@
 use std::{
     path::{Path, PathBuf},
     process::Stdio,
+    sync::Mutex,
 };
 
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{WalkBuilder, WalkState};
 use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
 
@@ -14,8 +17,12 @@ use crate::{
 };
 
 /// Discovered package information
+///
+/// Marked `#[non_exhaustive]` so fields can be added without breaking
+/// downstream crates; build one with [`Package::new`] and set the rest.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::struct_field_names)]
+#[non_exhaustive]
 pub struct Package {
     /// Package name from package.json
     pub name: String,
@@ -26,6 +33,23 @@ pub struct Package {
     /// Scripts defined in package.json
     #[serde(default)]
     pub scripts: IndexMap<String, String>,
+    /// Names from every dependency section of package.json
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl Package {
+    /// Package at `path` with no scripts or dependencies
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            name: name.into(),
+            package_json_path: path.join("package.json"),
+            path,
+            scripts: IndexMap::new(),
+            dependencies: Vec::new(),
+        }
+    }
 }
 
 /// Task information combining turbo.json and package.json data
@@ -77,20 +101,99 @@ impl PackageDiscovery {
 
     /// Discover all packages in the workspace
     ///
+    /// Expands the workspace globs from pnpm-workspace.yaml or package.json
+    /// with a parallel directory walk that respects `.gitignore` and skips
+    /// `node_modules`. Use [`Self::cross_check`] to compare with `turbo ls`.
+    ///
     /// # Errors
     /// Returns error if package discovery fails
     pub async fn discover_packages(&self) -> Result<Vec<Package>> {
-        // Try `turbo ls --output json` first
-        if let Ok(packages) = self.discover_via_turbo().await {
-            return Ok(packages);
+        let root_pkg_path = self.root.join("package.json");
+        let content = tokio::fs::read_to_string(&root_pkg_path)
+            .await
+            .map_err(|e| Error::ReadFile {
+                path: root_pkg_path.clone(),
+                source: e,
+            })?;
+
+        let root_pkg: serde_json::Value =
+            serde_json::from_str(&content).map_err(|e| Error::ParseJson {
+                path: root_pkg_path,
+                message: e.to_string(),
+            })?;
+
+        let workspace_globs = self.workspace_globs(&root_pkg).await;
+
+        // Not a monorepo: the root is the only package
+        if workspace_globs.is_empty() {
+            let scripts = Self::read_package_scripts(&self.root.join("package.json"))
+                .await
+                .unwrap_or_default();
+            return Ok(vec![Package {
+                name: root_pkg
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("root")
+                    .to_string(),
+                path: self.root.clone(),
+                package_json_path: self.root.join("package.json"),
+                scripts,
+                dependencies: dependency_names(&root_pkg),
+            }]);
+        }
+
+        let root = self.root.clone();
+        tokio::task::spawn_blocking(move || walk_workspace(&root, &workspace_globs))
+            .await
+            .map_err(|e| Error::WalkFailed {
+                path: self.root.clone(),
+                message: e.to_string(),
+            })?
+    }
+
+    /// Compare packages from native discovery with `turbo ls`
+    ///
+    /// # Errors
+    /// Returns error if `turbo ls` fails
+    pub async fn cross_check(&self, native: &[Package]) -> Result<DiscoveryDiff> {
+        let turbo = self.discover_via_turbo().await?;
+        Ok(DiscoveryDiff::between(native, &turbo))
+    }
+
+    /// Compiled workspace globs, `None` when the root package.json cannot be read or
+    /// the repo is not a monorepo
+    pub(crate) async fn workspace_matcher(&self) -> Option<WorkspaceMatcher> {
+        let content = tokio::fs::read_to_string(self.root.join("package.json"))
+            .await
+            .ok()?;
+        let root_pkg = serde_json::from_str(&content).ok()?;
+        let globs = self.workspace_globs(&root_pkg).await;
+        if globs.is_empty() {
+            return None;
         }
+        WorkspaceMatcher::new(&globs).ok()
+    }
+
+    /// pnpm-workspace.yaml `packages`, otherwise package.json `workspaces`
+    async fn workspace_globs(&self, root_pkg: &serde_json::Value) -> Vec<String> {
+        self.pnpm_workspace_globs()
+            .await
+            .unwrap_or_else(|| package_json_workspace_globs(root_pkg))
+    }
 
-        // Fallback to package.json workspaces
-        self.discover_via_package_json().await
+    /// `packages` globs from pnpm-workspace.yaml, if the file exists and lists any
+    async fn pnpm_workspace_globs(&self) -> Option<Vec<String>> {
+        let content = tokio::fs::read_to_string(self.root.join("pnpm-workspace.yaml"))
+            .await
+            .ok()?;
+        parse_pnpm_workspace_globs(&content)
     }
 
     /// Discover packages using `turbo ls`
-    async fn discover_via_turbo(&self) -> Result<Vec<Package>> {
+    ///
+    /// # Errors
+    /// Returns error if `turbo` cannot be spawned or its output cannot be parsed
+    pub async fn discover_via_turbo(&self) -> Result<Vec<Package>> {
         let output = tokio::process::Command::new("turbo")
             .args(["ls", "--output", "json"])
             .current_dir(&self.root)
@@ -141,92 +244,15 @@ impl PackageDiscovery {
                 path: pkg_path,
                 package_json_path,
                 scripts,
+                dependencies: Vec::new(),
             });
         }
 
         Ok(result)
     }
 
-    /// Discover packages via root package.json workspaces field
-    async fn discover_via_package_json(&self) -> Result<Vec<Package>> {
-        let root_pkg_path = self.root.join("package.json");
-        let content = tokio::fs::read_to_string(&root_pkg_path)
-            .await
-            .map_err(|e| Error::ReadFile {
-                path: root_pkg_path.clone(),
-                source: e,
-            })?;
-
-        let root_pkg: serde_json::Value =
-            serde_json::from_str(&content).map_err(|e| Error::ParseJson {
-                path: root_pkg_path,
-                message: e.to_string(),
-            })?;
-
-        let workspace_globs = match root_pkg.get("workspaces") {
-            Some(serde_json::Value::Array(arr)) => arr
-                .iter()
-                .filter_map(|v| v.as_str())
-                .map(String::from)
-                .collect::<Vec<_>>(),
-            Some(serde_json::Value::Object(obj)) => {
-                // Yarn workspaces format: {"packages": [...]}
-                obj.get("packages")
-                    .and_then(|v| v.as_array())
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_str())
-                            .map(String::from)
-                            .collect()
-                    })
-                    .unwrap_or_default()
-            }
-            _ => Vec::new(),
-        };
-
-        // For now, just return root package if no workspaces
-        // TODO: implement glob expansion
-        if workspace_globs.is_empty() {
-            let scripts = Self::read_package_scripts(&self.root.join("package.json"))
-                .await
-                .unwrap_or_default();
-            return Ok(vec![Package {
-                name: root_pkg
-                    .get("name")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("root")
-                    .to_string(),
-                path: self.root.clone(),
-                package_json_path: self.root.join("package.json"),
-                scripts,
-            }]);
-        }
-
-        // Simple glob handling - look for package.json in common locations
-        let mut packages = Vec::new();
-        for glob in &workspace_globs {
-            let base = glob.trim_end_matches("/*").trim_end_matches("/**");
-            let base_path = self.root.join(base);
-
-            if base_path.is_dir() {
-                if let Ok(mut entries) = tokio::fs::read_dir(&base_path).await {
-                    while let Ok(Some(entry)) = entries.next_entry().await {
-                        let pkg_json = entry.path().join("package.json");
-                        if pkg_json.exists() {
-                            if let Ok(pkg) = Self::load_package(&entry.path()).await {
-                                packages.push(pkg);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(packages)
-    }
-
     /// Load a single package from its directory
-    async fn load_package(path: &Path) -> Result<Package> {
+    pub(crate) async fn load_package(path: &Path) -> Result<Package> {
         let package_json_path = path.join("package.json");
         let content = tokio::fs::read_to_string(&package_json_path)
             .await
@@ -234,35 +260,7 @@ impl PackageDiscovery {
                 path: package_json_path.clone(),
                 source: e,
             })?;
-
-        let pkg: serde_json::Value =
-            serde_json::from_str(&content).map_err(|e| Error::ParseJson {
-                path: package_json_path.clone(),
-                message: e.to_string(),
-            })?;
-
-        let name = pkg
-            .get("name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let scripts = pkg
-            .get("scripts")
-            .and_then(|v| v.as_object())
-            .map(|obj| {
-                obj.iter()
-                    .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        Ok(Package {
-            name,
-            path: path.to_path_buf(),
-            package_json_path,
-            scripts,
-        })
+        parse_package(path, &content)
     }
 
     /// Read scripts from a package.json
@@ -339,6 +337,263 @@ impl PackageDiscovery {
     }
 }
 
+/// Packages found by only one of native discovery and `turbo ls`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryDiff {
+    /// Package names only native discovery found
+    pub only_native: Vec<String>,
+    /// Package names only `turbo ls` found
+    pub only_turbo: Vec<String>,
+}
+
+impl DiscoveryDiff {
+    fn between(native: &[Package], turbo: &[Package]) -> Self {
+        let names = |packages: &[Package]| -> IndexSet<String> {
+            packages.iter().map(|p| p.name.clone()).collect()
+        };
+        let (native, turbo) = (names(native), names(turbo));
+        Self {
+            only_native: native.difference(&turbo).cloned().collect(),
+            only_turbo: turbo.difference(&native).cloned().collect(),
+        }
+    }
+
+    /// Whether both paths found the same packages
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.only_native.is_empty() && self.only_turbo.is_empty()
+    }
+}
+
+/// Workspace globs compiled for matching package directories relative to the root
+#[derive(Debug, Clone)]
+pub(crate) struct WorkspaceMatcher {
+    include: GlobSet,
+    exclude: GlobSet,
+    /// Deepest package.json the globs can match, `None` for `**`
+    max_depth: Option<usize>,
+}
+
+impl WorkspaceMatcher {
+    fn new(globs: &[String]) -> Result<Self> {
+        let mut include = GlobSetBuilder::new();
+        let mut exclude = GlobSetBuilder::new();
+        let mut max_depth = Some(0);
+
+        for raw in globs {
+            let (negated, glob) = raw
+                .strip_prefix('!')
+                .map_or((false, raw.as_str()), |glob| (true, glob));
+            let glob = glob.trim_start_matches("./").trim_end_matches('/');
+            let compiled = GlobBuilder::new(glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| Error::InvalidGlob {
+                    glob: raw.clone(),
+                    message: e.kind().to_string(),
+                })?;
+
+            if negated {
+                exclude.add(compiled);
+                continue;
+            }
+            include.add(compiled);
+            max_depth = if glob.contains("**") {
+                None
+            } else {
+                // package.json sits one level below the matched directory
+                max_depth.map(|depth: usize| depth.max(glob.split('/').count() + 1))
+            };
+        }
+
+        let build = |builder: GlobSetBuilder| {
+            builder.build().map_err(|e| Error::InvalidGlob {
+                glob: globs.join(", "),
+                message: e.to_string(),
+            })
+        };
+        Ok(Self {
+            include: build(include)?,
+            exclude: build(exclude)?,
+            max_depth,
+        })
+    }
+
+    pub(crate) fn is_match(&self, dir: &Path) -> bool {
+        self.include.is_match(dir) && !self.exclude.is_match(dir)
+    }
+}
+
+/// `workspaces` from package.json: an array, or yarn's `{"packages": [...]}`
+fn package_json_workspace_globs(root_pkg: &serde_json::Value) -> Vec<String> {
+    let globs = match root_pkg.get("workspaces") {
+        Some(serde_json::Value::Object(obj)) => obj.get("packages"),
+        other => other,
+    };
+    globs
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `packages` list of a pnpm-workspace.yaml, in block or flow (`[...]`) style
+///
+/// `None` when the file lists no packages, so package.json `workspaces` apply.
+fn parse_pnpm_workspace_globs(content: &str) -> Option<Vec<String>> {
+    let mut globs = Vec::new();
+    let mut in_packages = false;
+    // flow sequence spanning several lines, collected up to its `]`
+    let mut flow: Option<String> = None;
+    for line in content.lines() {
+        let trimmed = line.split(" #").next().unwrap_or_default().trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(items) = &mut flow {
+            items.push_str(trimmed);
+            if trimmed.contains(']') {
+                globs.extend(flow_items(items));
+                flow = None;
+            }
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            let (key, value) = trimmed.split_once(':').unwrap_or((trimmed, ""));
+            in_packages = key == "packages";
+            let value = value.trim();
+            if in_packages && value.starts_with('[') {
+                if value.contains(']') {
+                    globs.extend(flow_items(value));
+                } else {
+                    flow = Some(value.to_string());
+                }
+            }
+            continue;
+        }
+        if in_packages && let Some(item) = trimmed.strip_prefix('-') {
+            globs.push(unquote(item));
+        }
+    }
+    (!globs.is_empty()).then_some(globs)
+}
+
+/// Entries of a YAML flow sequence such as `["apps/*", 'packages/*']`
+fn flow_items(sequence: &str) -> Vec<String> {
+    let inner = sequence.trim_start_matches('[');
+    inner
+        .split(']')
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(unquote)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn unquote(item: &str) -> String {
+    item.trim().trim_matches(['"', '\'']).to_string()
+}
+
+/// Walk `root` in parallel and load every package.json matched by the workspace globs
+fn walk_workspace(root: &Path, globs: &[String]) -> Result<Vec<Package>> {
+    let matcher = WorkspaceMatcher::new(globs)?;
+    let found = Mutex::new(Vec::new());
+
+    let mut walker = WalkBuilder::new(root);
+    walker
+        .require_git(false)
+        .max_depth(matcher.max_depth)
+        .filter_entry(|entry| entry.file_name() != "node_modules");
+    walker.build_parallel().run(|| {
+        Box::new(|entry| {
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if entry.file_name() == "package.json"
+                && entry.file_type().is_some_and(|kind| kind.is_file())
+                && let Some(dir) = entry.path().parent()
+                && dir
+                    .strip_prefix(root)
+                    .is_ok_and(|relative| matcher.is_match(relative))
+            {
+                let package = std::fs::read_to_string(entry.path())
+                    .map_err(|e| Error::ReadFile {
+                        path: entry.path().to_path_buf(),
+                        source: e,
+                    })
+                    .and_then(|content| parse_package(dir, &content));
+                match package {
+                    Ok(package) => {
+                        if let Ok(mut found) = found.lock() {
+                            found.push(package);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Skipping package: {e}"),
+                }
+            }
+            WalkState::Continue
+        })
+    });
+
+    let mut packages = found.into_inner().unwrap_or_default();
+    packages.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(packages)
+}
+
+/// Build a [`Package`] from the contents of `<dir>/package.json`
+fn parse_package(dir: &Path, content: &str) -> Result<Package> {
+    let package_json_path = dir.join("package.json");
+    let pkg: serde_json::Value = serde_json::from_str(content).map_err(|e| Error::ParseJson {
+        path: package_json_path.clone(),
+        message: e.to_string(),
+    })?;
+
+    let name = pkg
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let scripts = pkg
+        .get("scripts")
+        .and_then(|v| v.as_object())
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Package {
+        name,
+        path: dir.to_path_buf(),
+        package_json_path,
+        scripts,
+        dependencies: dependency_names(&pkg),
+    })
+}
+
+/// Package names in `dependencies`, `devDependencies`, `peerDependencies` and `optionalDependencies`
+fn dependency_names(pkg: &serde_json::Value) -> Vec<String> {
+    let names: IndexSet<&String> = [
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ]
+    .iter()
+    .filter_map(|section| pkg.get(section)?.as_object())
+    .flat_map(serde_json::Map::keys)
+    .collect();
+    names.into_iter().cloned().collect()
+}
+
 /// Internal: turbo ls JSON output format
 #[derive(Deserialize)]
 struct TurboLsOutput {
@@ -355,10 +610,157 @@ struct TurboLsPackage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::TempDir;
 
     #[tokio::test]
     async fn test_discovery_new() {
         let discovery = PackageDiscovery::new("/tmp");
         assert_eq!(discovery.root, PathBuf::from("/tmp"));
     }
+
+    fn write_package(root: &TempDir, dir: &str, name: &str) {
+        root.write(
+            &format!("{dir}/package.json"),
+            format!(r#"{{"name": "{name}", "scripts": {{"build": "tsc"}}}}"#),
+        );
+    }
+
+    #[test]
+    fn test_parse_pnpm_workspace_globs() {
+        let yaml = "packages:\n  - 'apps/*'\n  - \"packages/**\" # all\n  - '!**/test/**'\ncatalog:\n  - react\n";
+        assert_eq!(
+            parse_pnpm_workspace_globs(yaml).unwrap(),
+            vec!["apps/*", "packages/**", "!**/test/**"]
+        );
+
+        let flow = "packages: [\"apps/*\", 'packages/*']\n";
+        assert_eq!(
+            parse_pnpm_workspace_globs(flow).unwrap(),
+            vec!["apps/*", "packages/*"]
+        );
+        let multiline = "packages: [\n  \"apps/*\",\n  \"tools/*\"\n]\ncatalog:\n  react: ^19\n";
+        assert_eq!(
+            parse_pnpm_workspace_globs(multiline).unwrap(),
+            vec!["apps/*", "tools/*"]
+        );
+        assert_eq!(parse_pnpm_workspace_globs("catalog:\n  react: ^19\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_empty_pnpm_workspace_falls_back_to_package_json() {
+        let root = TempDir::new("discovery-pnpm-fallback");
+        root.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        root.write(
+            "pnpm-workspace.yaml",
+            "onlyBuiltDependencies:\n  - esbuild\n",
+        );
+        write_package(&root, "apps/web", "web");
+
+        let packages = PackageDiscovery::new(root.path())
+            .discover_packages()
+            .await
+            .unwrap();
+
+        let names: Vec<_> = packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["web"]);
+    }
+
+    #[tokio::test]
+    async fn test_native_discovery_respects_globs_and_ignores() {
+        let root = TempDir::new("discovery-native");
+        root.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/**", "packages/*", "!packages/excluded"]}"#,
+        );
+        root.write(".gitignore", "packages/ignored\n");
+        write_package(&root, "apps/web", "web");
+        write_package(&root, "apps/web/node_modules/dep", "dep");
+        write_package(&root, "packages/ui", "ui");
+        write_package(&root, "packages/ui/nested", "nested");
+        write_package(&root, "packages/excluded", "excluded");
+        write_package(&root, "packages/ignored", "ignored");
+
+        let packages = PackageDiscovery::new(root.path())
+            .discover_packages()
+            .await
+            .unwrap();
+
+        let names: Vec<_> = packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["web", "ui"]);
+        assert!(packages[0].scripts.contains_key("build"));
+    }
+
+    /// 1000 packages laid out like a large pnpm monorepo
+    fn synthetic_monorepo(name: &str) -> TempDir {
+        let root = TempDir::new(name);
+        root.write(
+            "package.json",
+            r#"{"name": "synthetic", "packageManager": "pnpm@9.0.0"}"#,
+        );
+        root.write(
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'apps/*'\n  - 'packages/*'\n",
+        );
+        root.write("turbo.json", r#"{"tasks": {"build": {}}}"#);
+        for i in 0..1000 {
+            let group = if i % 10 == 0 { "apps" } else { "packages" };
+            write_package(&root, &format!("{group}/pkg-{i}"), &format!("pkg-{i}"));
+        }
+        root
+    }
+
+    #[tokio::test]
+    async fn test_native_discovery_synthetic_monorepo() {
+        let root = synthetic_monorepo("discovery-1000");
+
+        let packages = PackageDiscovery::new(root.path())
+            .discover_packages()
+            .await
+            .unwrap();
+
+        assert_eq!(packages.len(), 1000);
+    }
+
+    #[tokio::test]
+    #[ignore = "spawns turbo; run with --ignored to compare timings"]
+    async fn test_native_discovery_beats_turbo_ls() {
+        let root = synthetic_monorepo("discovery-bench");
+        let discovery = PackageDiscovery::new(root.path());
+
+        let start = std::time::Instant::now();
+        let native = discovery.discover_packages().await.unwrap();
+        let native_elapsed = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let turbo = discovery.discover_via_turbo().await.unwrap();
+        let turbo_elapsed = start.elapsed();
+
+        assert!(DiscoveryDiff::between(&native, &turbo).is_empty());
+        assert!(
+            native_elapsed < turbo_elapsed,
+            "native {native_elapsed:?}, turbo ls {turbo_elapsed:?}"
+        );
+    }
+
+    #[test]
+    fn test_discovery_diff() {
+        let package = |name: &str| Package {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/repo/{name}")),
+            package_json_path: PathBuf::from(format!("/repo/{name}/package.json")),
+            scripts: IndexMap::new(),
+            dependencies: Vec::new(),
+        };
+        let diff = DiscoveryDiff::between(
+            &[package("web"), package("ui"), package("docs")],
+            &[package("ui"), package("web"), package("api")],
+        );
+        assert_eq!(diff.only_native, vec!["docs"]);
+        assert_eq!(diff.only_turbo, vec!["api"]);
+        assert!(!diff.is_empty());
+        assert!(DiscoveryDiff::between(&[package("web")], &[package("web")]).is_empty());
+    }
 }
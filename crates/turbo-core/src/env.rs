@@ -0,0 +1,216 @@
+//! Environment variable names a package uses, for `env` / `globalEnv` suggestions
+//!
+//! Only names are collected. `.env` files are read whole, but their values are
+//! dropped while parsing and never kept or returned.
+
+use std::path::Path;
+
+use ignore::WalkBuilder;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::discovery::Package;
+
+/// Prefixes frameworks expose to client code, keyed by the dependency that enables them
+pub const FRAMEWORK_PREFIXES: &[(&str, &str)] = &[
+    ("next", "NEXT_PUBLIC_"),
+    ("vite", "VITE_"),
+    ("astro", "PUBLIC_"),
+    ("@sveltejs/kit", "PUBLIC_"),
+    ("nuxt", "NUXT_PUBLIC_"),
+    ("react-scripts", "REACT_APP_"),
+    ("expo", "EXPO_PUBLIC_"),
+    ("gatsby", "GATSBY_"),
+];
+
+/// Source file extensions scanned for `process.env` / `import.meta.env` usages
+pub const SOURCE_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "vue", "svelte", "astro",
+];
+
+/// Upper bound on source files read per package
+const MAX_SOURCE_FILES: usize = 5000;
+
+/// Files larger than this are skipped (bundles, generated code)
+const MAX_SOURCE_BYTES: u64 = 1024 * 1024;
+
+/// Where a variable name was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EnvSource {
+    /// A `.env*` file such as `.env.local` or `.env.example`
+    DotEnv,
+    /// `process.env.X` or `import.meta.env.X` in source code
+    SourceCode,
+    /// Wildcard for a framework's public prefix, e.g. `NEXT_PUBLIC_*`
+    Framework,
+}
+
+/// Variable names from a package's `.env*` files, optionally its sources, and its frameworks
+///
+/// Reads the file system synchronously; call from a blocking context.
+#[must_use]
+pub fn collect(package: &Package, include_sources: bool) -> IndexMap<String, EnvSource> {
+    let mut names = IndexMap::new();
+    for name in dotenv_names(&package.path) {
+        names.entry(name).or_insert(EnvSource::DotEnv);
+    }
+    if include_sources {
+        for name in source_names(&package.path) {
+            names.entry(name).or_insert(EnvSource::SourceCode);
+        }
+    }
+    for (dependency, prefix) in FRAMEWORK_PREFIXES {
+        if package.dependencies.iter().any(|name| name == dependency) {
+            names
+                .entry(format!("{prefix}*"))
+                .or_insert(EnvSource::Framework);
+        }
+    }
+    names
+}
+
+/// Names declared in `.env*` files directly inside `dir`
+fn dotenv_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name == ".env" || name.starts_with(".env."))
+        })
+        .collect();
+    files.sort();
+
+    files
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|content| parse_dotenv_names(&content))
+        .collect()
+}
+
+/// Variable names of `KEY=value` / `export KEY=value` lines
+#[must_use]
+pub fn parse_dotenv_names(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, _) = line.split_once('=')?;
+            let name = name.trim();
+            is_env_name(name).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Names used in source files under `dir`, honoring `.gitignore` and skipping `node_modules`
+fn source_names(dir: &Path) -> Vec<String> {
+    let walker = WalkBuilder::new(dir)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != "node_modules")
+        .build();
+
+    walker
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_some_and(|kind| kind.is_file())
+                && entry
+                    .path()
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| SOURCE_EXTENSIONS.contains(&extension))
+                && entry
+                    .metadata()
+                    .is_ok_and(|metadata| metadata.len() <= MAX_SOURCE_BYTES)
+        })
+        .take(MAX_SOURCE_FILES)
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .flat_map(|content| parse_source_names(&content))
+        .collect()
+}
+
+/// Names read through `process.env.X`, `process.env["X"]` or `import.meta.env.X`
+#[must_use]
+pub fn parse_source_names(content: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for marker in ["process.env", "import.meta.env"] {
+        for (start, _) in content.match_indices(marker) {
+            let rest = &content[start + marker.len()..];
+            let rest = rest
+                .strip_prefix('.')
+                .or_else(|| rest.strip_prefix("[\""))
+                .or_else(|| rest.strip_prefix("['"));
+            let Some(rest) = rest else {
+                continue;
+            };
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            if is_env_name(name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+fn is_env_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_parse_names_only() {
+        assert_eq!(
+            parse_dotenv_names("# comment\nAPI_URL=https://x\nexport SECRET = hunter2\n=oops\n"),
+            vec!["API_URL", "SECRET"]
+        );
+        assert_eq!(
+            parse_source_names(
+                "fetch(process.env.API_URL); import.meta.env.VITE_KEY; process.env['PORT']; process.env[name]"
+            ),
+            vec!["API_URL", "PORT", "VITE_KEY"]
+        );
+    }
+
+    #[test]
+    fn test_collect_package_env() {
+        let dir = TempDir::new("env");
+        dir.write(".env.example", "DATABASE_URL=\n");
+        dir.write("src/index.ts", "process.env.API_URL");
+        dir.write("node_modules/dep/index.js", "process.env.HIDDEN");
+
+        let package = Package {
+            name: "web".to_string(),
+            path: dir.to_path_buf(),
+            package_json_path: dir.join("package.json"),
+            scripts: IndexMap::new(),
+            dependencies: vec!["next".to_string()],
+        };
+
+        let names = collect(&package, true);
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("DATABASE_URL".to_string(), EnvSource::DotEnv),
+                ("API_URL".to_string(), EnvSource::SourceCode),
+                ("NEXT_PUBLIC_*".to_string(), EnvSource::Framework),
+            ]
+        );
+        assert!(collect(&package, false).contains_key("DATABASE_URL"));
+    }
+}
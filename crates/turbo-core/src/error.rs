@@ -20,6 +20,14 @@ pub enum Error {
         source: std::io::Error,
     },
 
+    /// Failed to remove a file or directory
+    #[error("Failed to remove {path}: {source}")]
+    RemoveFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     /// Failed to parse JSON
     #[error("Failed to parse JSON in {path}: {message}")]
     ParseJson { path: PathBuf, message: String },
@@ -32,6 +40,18 @@ pub enum Error {
     #[error("Invalid path: {0}")]
     InvalidPath(PathBuf),
 
+    /// Invalid workspace glob
+    #[error("Invalid glob `{glob}`: {message}")]
+    InvalidGlob { glob: String, message: String },
+
+    /// Directory walk failed
+    #[error("Failed to walk {path}: {message}")]
+    WalkFailed { path: PathBuf, message: String },
+
+    /// File watcher could not be started
+    #[error("Failed to watch {path}: {message}")]
+    WatchFailed { path: PathBuf, message: String },
+
     /// Command execution failed
     #[error("Command `{command}` failed: {message}")]
     CommandFailed { command: String, message: String },
@@ -43,4 +63,16 @@ pub enum Error {
     /// Task not found
     #[error("Task not found: {0}")]
     TaskNotFound(String),
+
+    /// Task graph contains a dependency cycle
+    #[error("Task graph contains a cycle involving {0}")]
+    CyclicTaskGraph(String),
+
+    /// Cache artifact not found
+    #[error("Cache entry not found: {0}")]
+    CacheEntryNotFound(String),
+
+    /// Run summary not found
+    #[error("Run summary not found: {0}")]
+    RunNotFound(String),
 }
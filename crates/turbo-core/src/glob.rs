@@ -0,0 +1,164 @@
+//! Evaluating `inputs`, `outputs` and `globalDependencies` globs against the file system
+
+use std::path::{Path, PathBuf};
+
+use globset::GlobBuilder;
+use ignore::WalkBuilder;
+
+use crate::error::{Error, Result};
+
+/// `inputs` token for turbo's default inputs (every tracked file of the package)
+pub const TURBO_DEFAULT: &str = "$TURBO_DEFAULT$";
+
+/// Prefix making a glob relative to the repo root instead of the package
+pub const TURBO_ROOT: &str = "$TURBO_ROOT$";
+
+/// A glob split into the directory it is evaluated in and the pattern below it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedGlob {
+    pub dir: PathBuf,
+    pub pattern: String,
+    /// `!`-prefixed globs exclude files instead of adding them
+    pub negated: bool,
+}
+
+/// Resolve a turbo glob for a package
+///
+/// Leading `..` segments move the directory up, so `../shared/**` is evaluated in
+/// the package's parent. Returns `None` for `$TURBO_DEFAULT$`, which is not a
+/// path, and for `..` after the first segment.
+#[must_use]
+pub fn resolve(glob: &str, package_dir: &Path, repo_root: &Path) -> Option<ResolvedGlob> {
+    let (negated, glob) = glob
+        .strip_prefix('!')
+        .map_or((false, glob), |glob| (true, glob));
+    if glob == TURBO_DEFAULT {
+        return None;
+    }
+    let (dir, pattern) = glob.strip_prefix(TURBO_ROOT).map_or_else(
+        || (package_dir, glob),
+        |rest| (repo_root, rest.trim_start_matches('/')),
+    );
+    let mut dir = dir.to_path_buf();
+    let mut pattern = pattern.trim_start_matches("./");
+    while let Some(rest) = pattern.strip_prefix("../") {
+        if !dir.pop() {
+            return None;
+        }
+        pattern = rest.trim_start_matches("./");
+    }
+    if pattern.split('/').any(|segment| segment == "..") {
+        return None;
+    }
+    Some(ResolvedGlob {
+        dir,
+        pattern: pattern.to_string(),
+        negated,
+    })
+}
+
+impl ResolvedGlob {
+    /// Number of files and directories matching, counting at most `limit`
+    ///
+    /// `.gitignore` is deliberately not honored: outputs such as `dist/**` are
+    /// usually ignored. `node_modules` and `.git` are skipped.
+    ///
+    /// # Errors
+    /// Returns error if the pattern is not a valid glob
+    pub fn count_matches(&self, limit: usize) -> Result<usize> {
+        let matcher = GlobBuilder::new(&self.pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| Error::InvalidGlob {
+                glob: self.pattern.clone(),
+                message: e.to_string(),
+            })?
+            .compile_matcher();
+
+        // walk only below the literal part of the pattern, e.g. `dist` for `dist/**/*.js`
+        let literal: PathBuf = self
+            .pattern
+            .split('/')
+            .take_while(|segment| !segment.contains(['*', '?', '[', '{']))
+            .collect();
+        let start = self.dir.join(&literal);
+        if !start.exists() {
+            return Ok(0);
+        }
+
+        let count = WalkBuilder::new(&start)
+            .standard_filters(false)
+            .filter_entry(|entry| {
+                entry.file_name() != "node_modules" && entry.file_name() != ".git"
+            })
+            .build()
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| {
+                entry
+                    .path()
+                    .strip_prefix(&self.dir)
+                    .is_ok_and(|relative| matcher.is_match(relative))
+            })
+            .take(limit)
+            .count();
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_resolve_microsyntax() {
+        let package = Path::new("/repo/apps/web");
+        let root = Path::new("/repo");
+
+        assert_eq!(resolve("$TURBO_DEFAULT$", package, root), None);
+        assert_eq!(
+            resolve("!$TURBO_ROOT$/tsconfig.json", package, root),
+            Some(ResolvedGlob {
+                dir: root.to_path_buf(),
+                pattern: "tsconfig.json".to_string(),
+                negated: true,
+            })
+        );
+        assert_eq!(
+            resolve("./src/**", package, root).map(|glob| glob.pattern),
+            Some("src/**".to_string())
+        );
+        assert_eq!(
+            resolve("../../shared/**", package, root),
+            Some(ResolvedGlob {
+                dir: root.to_path_buf(),
+                pattern: "shared/**".to_string(),
+                negated: false,
+            })
+        );
+        assert_eq!(resolve("src/../lib/**", package, root), None);
+    }
+
+    #[test]
+    fn test_count_matches_ignores_gitignore() {
+        let dir = TempDir::new("glob");
+        dir.write(".gitignore", "dist\n");
+        dir.write("dist/index.js", "");
+        dir.write("dist/chunks/a.js", "");
+
+        let count = |pattern: &str| {
+            resolve(pattern, &dir, &dir)
+                .unwrap()
+                .count_matches(100)
+                .unwrap()
+        };
+        assert_eq!(count("dist/**/*.js"), 2);
+        assert_eq!(count("build/**"), 0);
+        assert!(
+            resolve("src/[", &dir, &dir)
+                .unwrap()
+                .count_matches(1)
+                .is_err()
+        );
+    }
+}
@@ -3,14 +3,39 @@
 //! Shared core functionality for Turborepo tooling (LSP, MCP, etc.)
 //!
 //! ## Modules
+//! - [`cache`] - local cache inspection and garbage collection
 //! - [`config`] - turbo.json parsing and validation
+//! - [`critical_path`] - critical path and parallelism analysis
 //! - [`discovery`] - package and task discovery
+//! - [`env`] - environment variable names used by packages
 //! - [`error`] - common error types
+//! - [`glob`] - `inputs` / `outputs` glob evaluation
+//! - [`runs`] - run summary parsing, cache-miss diffing and task statistics
+//! - [`version`] - turbo version detection
+//! - [`watcher`] - debounced workspace file watching
+//! - [`workspace`] - cached workspace snapshot with per-file invalidation
 
+pub mod cache;
 pub mod config;
+pub mod critical_path;
 pub mod discovery;
+pub mod env;
 pub mod error;
+pub mod glob;
+pub mod runs;
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+pub mod test_util;
+pub mod version;
+pub mod watcher;
+pub mod workspace;
 
+pub use cache::{CacheEntry, CacheInventory, GcPolicy, GcReport, LocalCache};
 pub use config::{TurboConfig, TurboTask};
+pub use critical_path::{CriticalPathAnalysis, TaskGraph};
 pub use discovery::{Package, PackageDiscovery, TaskInfo};
 pub use error::{Error, Result};
+pub use runs::{CacheMissExplanation, RunSummary, TaskStats, TaskSummary};
+pub use version::{TurboVersion, VersionSource};
+pub use watcher::{WorkspaceEvent, WorkspaceWatcher};
+pub use workspace::WorkspaceSnapshot;
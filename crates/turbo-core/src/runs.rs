@@ -0,0 +1,748 @@
+//! Run summary parsing (`turbo run --summarize`), cache-miss diffing and task statistics
+
+use std::path::{Path, PathBuf};
+
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Directory (relative to the repo root) where turbo writes run summaries
+pub const RUNS_DIR: &str = ".turbo/runs";
+
+/// Parsed `.turbo/runs/<id>.json` summary
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    /// Run identifier (file stem of the summary)
+    #[serde(default)]
+    pub id: String,
+
+    /// Summary format version
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// Version of turbo that produced the summary
+    #[serde(default)]
+    pub turbo_version: Option<String>,
+
+    /// Inputs shared by every task in the run
+    #[serde(default)]
+    pub global_cache_inputs: GlobalCacheInputs,
+
+    /// Environment mode (`strict` / `loose`)
+    #[serde(default)]
+    pub env_mode: Option<String>,
+
+    /// Run-level execution summary
+    #[serde(default)]
+    pub execution: Option<ExecutionSummary>,
+
+    /// Packages in scope for the run
+    #[serde(default)]
+    pub packages: Vec<String>,
+
+    /// Per-task summaries
+    #[serde(default)]
+    pub tasks: Vec<TaskSummary>,
+
+    /// Source control state when the run started
+    #[serde(default)]
+    pub scm: Option<ScmState>,
+
+    /// Path to the summary file (not serialized)
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+/// Global hash inputs of a run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalCacheInputs {
+    /// Hash of the global cache key
+    #[serde(default)]
+    pub root_key: Option<String>,
+
+    /// Global dependency files and their hashes
+    #[serde(default)]
+    pub files: IndexMap<String, String>,
+
+    /// Hash of root external dependencies (lockfile)
+    #[serde(default)]
+    pub hash_of_external_dependencies: Option<String>,
+
+    /// Global environment variables
+    #[serde(default)]
+    pub environment_variables: EnvironmentVariables,
+}
+
+/// Environment variables that contributed to a hash
+///
+/// Entries are `NAME=hash` pairs; values are never recorded by turbo.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentVariables {
+    /// Variables listed explicitly in `env` / `globalEnv`
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub configured: Vec<String>,
+
+    /// Variables inferred from framework prefixes
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub inferred: Vec<String>,
+
+    /// Pass-through variables
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub passthrough: Vec<String>,
+}
+
+/// Run- or task-level execution timings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionSummary {
+    /// Command line of the run (run-level only)
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Start time in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub start_time: i64,
+
+    /// End time in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub end_time: i64,
+
+    /// Process exit code
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+/// Source control state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScmState {
+    /// SCM type (e.g. `git`)
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+
+    /// Commit SHA
+    #[serde(default)]
+    pub sha: Option<String>,
+
+    /// Branch name
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+/// Summary of a single task execution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSummary {
+    /// Task id (`package#task`, or `task` in single-package repos)
+    pub task_id: String,
+
+    /// Task name
+    #[serde(default)]
+    pub task: String,
+
+    /// Package name
+    #[serde(default)]
+    pub package: Option<String>,
+
+    /// Task hash (cache key)
+    #[serde(default)]
+    pub hash: String,
+
+    /// Input files and their hashes
+    #[serde(default)]
+    pub inputs: IndexMap<String, String>,
+
+    /// Hash of the package's external dependencies
+    #[serde(default)]
+    pub hash_of_external_dependencies: Option<String>,
+
+    /// Cache lookup result
+    #[serde(default)]
+    pub cache: CacheState,
+
+    /// Script command
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Package directory relative to the repo root
+    #[serde(default)]
+    pub directory: Option<String>,
+
+    /// Task ids this task depends on
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub dependencies: Vec<String>,
+
+    /// Task ids that depend on this task
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub dependents: Vec<String>,
+
+    /// Task definition after `extends` / package overrides were applied
+    #[serde(default)]
+    pub resolved_task_definition: IndexMap<String, serde_json::Value>,
+
+    /// Environment variables that contributed to the hash
+    #[serde(default)]
+    pub environment_variables: EnvironmentVariables,
+
+    /// Task timings
+    #[serde(default)]
+    pub execution: Option<ExecutionSummary>,
+}
+
+/// Cache state of a task
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheState {
+    /// `HIT` or `MISS`
+    #[serde(default)]
+    pub status: CacheStatus,
+
+    /// Where a hit was restored from (`LOCAL` / `REMOTE`)
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Milliseconds saved by the cache hit
+    #[serde(default)]
+    pub time_saved: u64,
+}
+
+/// Cache lookup outcome
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CacheStatus {
+    /// Restored from cache
+    Hit,
+    /// Executed
+    #[default]
+    #[serde(other)]
+    Miss,
+}
+
+impl RunSummary {
+    /// Load and parse a run summary from a specific path
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be read or parsed
+    pub async fn load(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| Error::ReadFile {
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+
+        Self::parse(&content, Some(path.to_path_buf()))
+    }
+
+    /// Parse run summary content
+    ///
+    /// The id falls back to the file stem when the summary does not carry one.
+    ///
+    /// # Errors
+    /// Returns `Error::ParseJson` if content is invalid JSON
+    pub fn parse(content: &str, path: Option<PathBuf>) -> Result<Self> {
+        let mut summary: Self = serde_json::from_str(content).map_err(|e| Error::ParseJson {
+            path: path.clone().unwrap_or_default(),
+            message: e.to_string(),
+        })?;
+
+        if summary.id.is_empty()
+            && let Some(stem) = path.as_deref().and_then(Path::file_stem)
+        {
+            summary.id = stem.to_string_lossy().into_owned();
+        }
+        summary.path = path;
+        Ok(summary)
+    }
+
+    /// Load every run summary under `<repo_root>/.turbo/runs`, oldest first
+    ///
+    /// Files that fail to parse are skipped.
+    ///
+    /// # Errors
+    /// Returns error if the runs directory cannot be read
+    pub async fn load_all(repo_root: &Path) -> Result<Vec<Self>> {
+        let dir = repo_root.join(RUNS_DIR);
+        let mut entries = tokio::fs::read_dir(&dir)
+            .await
+            .map_err(|e| Error::ReadFile {
+                path: dir.clone(),
+                source: e,
+            })?;
+
+        let mut runs = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            match Self::load(&path).await {
+                Ok(summary) => runs.push(summary),
+                Err(e) => tracing::warn!("Skipping run summary: {e}"),
+            }
+        }
+
+        runs.sort_by(|a, b| {
+            a.start_time()
+                .cmp(&b.start_time())
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        Ok(runs)
+    }
+
+    /// Load a single run summary by id
+    ///
+    /// # Errors
+    /// Returns `Error::RunNotFound` if no summary with that id exists
+    pub async fn load_by_id(repo_root: &Path, id: &str) -> Result<Self> {
+        if !is_run_id(id) {
+            return Err(Error::RunNotFound(id.to_string()));
+        }
+        let path = repo_root.join(RUNS_DIR).join(format!("{id}.json"));
+        if !path.is_file() {
+            return Err(Error::RunNotFound(id.to_string()));
+        }
+        Self::load(&path).await
+    }
+
+    /// Run start time in milliseconds since the Unix epoch
+    #[must_use]
+    pub fn start_time(&self) -> i64 {
+        self.execution.as_ref().map_or(0, |e| e.start_time)
+    }
+
+    /// Get a task summary by task id
+    #[must_use]
+    pub fn task(&self, task_id: &str) -> Option<&TaskSummary> {
+        self.tasks.iter().find(|task| task.task_id == task_id)
+    }
+}
+
+impl TaskSummary {
+    /// Wall-clock duration in milliseconds, if the task was executed
+    #[must_use]
+    pub fn duration_ms(&self) -> Option<u64> {
+        let execution = self.execution.as_ref()?;
+        u64::try_from(execution.end_time - execution.start_time).ok()
+    }
+
+    /// Whether the task was restored from cache
+    #[must_use]
+    pub fn is_cache_hit(&self) -> bool {
+        self.cache.status == CacheStatus::Hit
+    }
+}
+
+/// Why a task's hash differs between two runs
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheMissExplanation {
+    /// Task id being compared
+    pub task_id: String,
+    /// Run id of the baseline
+    pub previous_run: String,
+    /// Run id being explained
+    pub current_run: String,
+    /// Hash in the baseline run
+    pub previous_hash: String,
+    /// Hash in the explained run
+    pub current_hash: String,
+    /// Cache status in the explained run
+    pub cache_status: CacheStatus,
+    /// Whether the task hash changed at all
+    pub hash_changed: bool,
+    /// Task input file changes
+    pub inputs: KeyedChanges,
+    /// Environment variable changes (names only)
+    pub env_vars: KeyedChanges,
+    /// Dependency tasks whose hash changed
+    pub dependencies: Vec<DependencyChange>,
+    /// Resolved task definition fields that changed
+    pub task_definition: Vec<FieldChange>,
+    /// Whether the package's external dependencies hash changed
+    pub external_dependencies_changed: bool,
+    /// Global inputs that changed (files, env, lockfile)
+    pub global: GlobalChanges,
+}
+
+/// Added / removed / modified keys between two hashed maps
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct KeyedChanges {
+    /// Keys only present in the current run
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    /// Keys only present in the previous run
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    /// Keys present in both runs with different hashes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modified: Vec<String>,
+}
+
+/// Dependency hash change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyChange {
+    /// Dependency task id
+    pub task_id: String,
+    /// Hash in the previous run (`None` if newly added)
+    pub previous_hash: Option<String>,
+    /// Hash in the current run (`None` if removed)
+    pub current_hash: Option<String>,
+}
+
+/// Task definition field change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    /// Field name (e.g. `outputs`)
+    pub field: String,
+    /// Previous value
+    pub previous: Option<serde_json::Value>,
+    /// Current value
+    pub current: Option<serde_json::Value>,
+}
+
+/// Global input changes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalChanges {
+    /// Global dependency file changes
+    pub files: KeyedChanges,
+    /// Global environment variable changes (names only)
+    pub env_vars: KeyedChanges,
+    /// Whether the root lockfile hash changed
+    pub external_dependencies_changed: bool,
+}
+
+impl KeyedChanges {
+    /// Whether nothing changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    fn between(previous: &IndexMap<String, String>, current: &IndexMap<String, String>) -> Self {
+        let mut changes = Self::default();
+        for (key, hash) in current {
+            match previous.get(key) {
+                None => changes.added.push(key.clone()),
+                Some(old) if old != hash => changes.modified.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        changes.removed = previous
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned()
+            .collect();
+        changes
+    }
+}
+
+impl EnvironmentVariables {
+    /// All `NAME -> hash` pairs; values are never exposed
+    #[must_use]
+    pub fn hashes(&self) -> IndexMap<String, String> {
+        self.configured
+            .iter()
+            .chain(&self.inferred)
+            .chain(&self.passthrough)
+            .map(|entry| {
+                entry.split_once('=').map_or_else(
+                    || (entry.clone(), String::new()),
+                    |(name, hash)| (name.to_string(), hash.to_string()),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Explain why `task_id` has a different hash in `current` than in `previous`
+///
+/// # Errors
+/// Returns `Error::TaskNotFound` if the task is missing from either run
+pub fn explain_cache_miss(
+    previous: &RunSummary,
+    current: &RunSummary,
+    task_id: &str,
+) -> Result<CacheMissExplanation> {
+    let before = previous
+        .task(task_id)
+        .ok_or_else(|| Error::TaskNotFound(format!("{task_id} in run {}", previous.id)))?;
+    let after = current
+        .task(task_id)
+        .ok_or_else(|| Error::TaskNotFound(format!("{task_id} in run {}", current.id)))?;
+
+    let dependency_ids: IndexSet<&String> = before
+        .dependencies
+        .iter()
+        .chain(&after.dependencies)
+        .collect();
+    let dependencies = dependency_ids
+        .into_iter()
+        .filter_map(|id| {
+            let previous_hash = previous.task(id).map(|t| t.hash.clone());
+            let current_hash = current.task(id).map(|t| t.hash.clone());
+            (previous_hash != current_hash).then(|| DependencyChange {
+                task_id: id.clone(),
+                previous_hash,
+                current_hash,
+            })
+        })
+        .collect();
+
+    let field_names: IndexSet<&String> = before
+        .resolved_task_definition
+        .keys()
+        .chain(after.resolved_task_definition.keys())
+        .collect();
+    let task_definition = field_names
+        .into_iter()
+        .filter_map(|field| {
+            let old = before.resolved_task_definition.get(field);
+            let new = after.resolved_task_definition.get(field);
+            (old != new).then(|| FieldChange {
+                field: field.clone(),
+                previous: old.cloned(),
+                current: new.cloned(),
+            })
+        })
+        .collect();
+
+    let global_before = &previous.global_cache_inputs;
+    let global_after = &current.global_cache_inputs;
+
+    Ok(CacheMissExplanation {
+        task_id: task_id.to_string(),
+        previous_run: previous.id.clone(),
+        current_run: current.id.clone(),
+        previous_hash: before.hash.clone(),
+        current_hash: after.hash.clone(),
+        cache_status: after.cache.status,
+        hash_changed: before.hash != after.hash,
+        inputs: KeyedChanges::between(&before.inputs, &after.inputs),
+        env_vars: KeyedChanges::between(
+            &before.environment_variables.hashes(),
+            &after.environment_variables.hashes(),
+        ),
+        dependencies,
+        task_definition,
+        external_dependencies_changed: before.hash_of_external_dependencies
+            != after.hash_of_external_dependencies,
+        global: GlobalChanges {
+            files: KeyedChanges::between(&global_before.files, &global_after.files),
+            env_vars: KeyedChanges::between(
+                &global_before.environment_variables.hashes(),
+                &global_after.environment_variables.hashes(),
+            ),
+            external_dependencies_changed: global_before.hash_of_external_dependencies
+                != global_after.hash_of_external_dependencies,
+        },
+    })
+}
+
+/// Aggregate statistics for a task across run history
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStats {
+    /// Number of runs the task appeared in
+    pub runs: usize,
+    /// Number of cache hits
+    pub cache_hits: usize,
+    /// Fraction of runs restored from cache (0.0 - 1.0)
+    pub cache_hit_rate: f64,
+    /// Average duration of executed (non-cached) runs in milliseconds
+    pub average_ms: u64,
+    /// 95th percentile duration of executed runs in milliseconds
+    pub p95_ms: u64,
+    /// Exit code of the most recent run
+    pub last_exit_code: Option<i32>,
+}
+
+/// Per-task statistics keyed by task id (`web#build`), from runs ordered oldest first
+#[must_use]
+pub fn task_stats(runs: &[RunSummary]) -> IndexMap<String, TaskStats> {
+    aggregate_stats(runs, |task| task.task_id.as_str())
+}
+
+/// Per-task statistics keyed by task name (`build`), merged across packages
+#[must_use]
+pub fn task_name_stats(runs: &[RunSummary]) -> IndexMap<String, TaskStats> {
+    aggregate_stats(runs, |task| task.task.as_str())
+}
+
+fn aggregate_stats(
+    runs: &[RunSummary],
+    key: impl Fn(&TaskSummary) -> &str,
+) -> IndexMap<String, TaskStats> {
+    let mut durations: IndexMap<String, Vec<u64>> = IndexMap::new();
+    let mut stats: IndexMap<String, TaskStats> = IndexMap::new();
+
+    for run in runs {
+        for task in &run.tasks {
+            let name = key(task);
+            let entry = stats.entry(name.to_string()).or_default();
+            entry.runs += 1;
+            if task.is_cache_hit() {
+                entry.cache_hits += 1;
+            } else if let Some(duration) = task.duration_ms() {
+                durations
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(duration);
+            }
+            if let Some(code) = task.execution.as_ref().and_then(|e| e.exit_code) {
+                entry.last_exit_code = Some(code);
+            }
+        }
+    }
+
+    for entry in stats.values_mut() {
+        entry.cache_hit_rate = ratio(entry.cache_hits, entry.runs);
+    }
+
+    for (name, mut samples) in durations {
+        let Some(entry) = stats.get_mut(&name) else {
+            continue;
+        };
+        samples.sort_unstable();
+        let total: u64 = samples.iter().sum();
+        entry.average_ms = total / samples.len() as u64;
+        // Nearest-rank percentile
+        let rank = (samples.len() * 95).div_ceil(100).max(1);
+        entry.p95_ms = samples[rank - 1];
+    }
+
+    stats
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    part as f64 / total as f64
+}
+
+/// Whether `id` names a file directly inside the runs directory
+///
+/// Ids come from MCP parameters, so separators and leading dots are rejected
+/// to keep `../` out of the joined path.
+fn is_run_id(id: &str) -> bool {
+    !id.is_empty() && !id.contains(['/', '\\']) && !id.starts_with('.')
+}
+
+/// Treat `null` the same as a missing field (turbo emits `null` for empty lists)
+fn null_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str, build_hash: &str, src_hash: &str, env: &str) -> RunSummary {
+        let content = format!(
+            r#"{{
+  "id": "{id}",
+  "globalCacheInputs": {{ "files": {{}}, "environmentVariables": {{ "configured": null }} }},
+  "execution": {{ "startTime": 1, "endTime": 2, "exitCode": 0 }},
+  "tasks": [
+    {{
+      "taskId": "ui#build",
+      "task": "build",
+      "package": "ui",
+      "hash": "ui-{build_hash}",
+      "inputs": {{}},
+      "dependencies": [],
+      "resolvedTaskDefinition": {{ "outputs": ["dist/**"] }}
+    }},
+    {{
+      "taskId": "web#build",
+      "task": "build",
+      "package": "web",
+      "hash": "{build_hash}",
+      "inputs": {{ "src/index.ts": "{src_hash}", "package.json": "p1" }},
+      "cache": {{ "status": "MISS", "timeSaved": 0 }},
+      "dependencies": ["ui#build"],
+      "resolvedTaskDefinition": {{ "outputs": ["dist/**"] }},
+      "environmentVariables": {{ "configured": ["{env}"], "inferred": [] }}
+    }}
+  ]
+}}"#
+        );
+        RunSummary::parse(&content, None).unwrap()
+    }
+
+    #[test]
+    fn test_parse_summary_falls_back_to_file_stem() {
+        let summary =
+            RunSummary::parse(r#"{"tasks": []}"#, Some(PathBuf::from("runs/abc.json"))).unwrap();
+        assert_eq!(summary.id, "abc");
+    }
+
+    #[test]
+    fn test_explain_cache_miss_reports_changes() {
+        let previous = summary("a", "h1", "s1", "API_URL=e1");
+        let current = summary("b", "h2", "s2", "API_URL=e2");
+
+        let explanation = explain_cache_miss(&previous, &current, "web#build").unwrap();
+
+        assert!(explanation.hash_changed);
+        assert_eq!(explanation.inputs.modified, vec!["src/index.ts"]);
+        assert_eq!(explanation.env_vars.modified, vec!["API_URL"]);
+        assert_eq!(explanation.dependencies.len(), 1);
+        assert_eq!(explanation.dependencies[0].task_id, "ui#build");
+        assert_eq!(explanation.task_definition, Vec::new());
+    }
+
+    #[test]
+    fn test_task_stats_aggregates_history() {
+        let mut first = summary("a", "h1", "s1", "API_URL=e1");
+        let mut second = summary("b", "h2", "s2", "API_URL=e2");
+        first.tasks[1].execution = Some(ExecutionSummary {
+            start_time: 0,
+            end_time: 1000,
+            exit_code: Some(0),
+            ..ExecutionSummary::default()
+        });
+        second.tasks[1].execution = Some(ExecutionSummary {
+            start_time: 0,
+            end_time: 3000,
+            exit_code: Some(1),
+            ..ExecutionSummary::default()
+        });
+
+        let stats = task_stats(&[first, second]);
+        let web = &stats["web#build"];
+
+        assert_eq!(web.runs, 2);
+        assert_eq!(web.average_ms, 2000);
+        assert_eq!(web.p95_ms, 3000);
+        assert_eq!(web.last_exit_code, Some(1));
+        assert!(web.cache_hit_rate < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_run_ids_stay_inside_runs_dir() {
+        assert!(is_run_id("2fkjT0Jbz0b"));
+        assert!(!is_run_id("../../secret"));
+        assert!(!is_run_id("..\\secret"));
+        assert!(!is_run_id(".hidden"));
+        assert!(!is_run_id(""));
+    }
+
+    #[test]
+    fn test_explain_cache_miss_unknown_task() {
+        let previous = summary("a", "h1", "s1", "API_URL=e1");
+        assert!(explain_cache_miss(&previous, &previous, "docs#build").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Test fixtures shared by the turbo crates
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::Package;
+
+static NEXT_DIR: AtomicU64 = AtomicU64::new(0);
+
+/// Fresh directory under the system temp dir, removed again on drop (also when a test panics)
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create an empty directory; `name` only makes leftovers recognizable
+    ///
+    /// # Panics
+    ///
+    /// Panics if the directory cannot be created.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "turbo-{name}-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write `content` to `relative`, creating parent directories
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be written.
+    pub fn write(&self, relative: &str, content: impl AsRef<[u8]>) {
+        let path = self.path.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, content).unwrap();
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Start a [`Package`] named `name` at `/repo/packages/<name>`
+pub fn package(name: &str) -> PackageBuilder {
+    PackageBuilder(Package::new(name, format!("/repo/packages/{name}")))
+}
+
+/// Fills in the scripts and dependencies of a test [`Package`]
+#[derive(Debug)]
+#[must_use]
+pub struct PackageBuilder(Package);
+
+impl PackageBuilder {
+    /// Move the package to `path`
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.0.package_json_path = path.join("package.json");
+        self.0.path = path;
+        self
+    }
+
+    /// Add a script running `command`
+    pub fn script(mut self, name: &str, command: &str) -> Self {
+        self.0.scripts.insert(name.to_string(), command.to_string());
+        self
+    }
+
+    /// Add scripts with empty commands
+    pub fn scripts(self, names: &[&str]) -> Self {
+        names
+            .iter()
+            .fold(self, |builder, name| builder.script(name, ""))
+    }
+
+    /// Add dependency names
+    pub fn dependencies(mut self, names: &[&str]) -> Self {
+        self.0
+            .dependencies
+            .extend(names.iter().map(ToString::to_string));
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Package {
+        self.0
+    }
+}
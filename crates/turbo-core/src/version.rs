@@ -0,0 +1,157 @@
+//! Detecting the turbo version a repository uses
+
+use std::{fmt, path::Path, process::Stdio, time::Duration};
+
+use serde::Serialize;
+
+/// A `major.minor.patch` turbo version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct TurboVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl TurboVersion {
+    #[must_use]
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version or the lower bound of a simple range (`^2.3.0`, `~1.13`, `>=2`, `2.x`)
+    ///
+    /// Returns `None` for tags and protocols such as `latest` or `workspace:*`.
+    #[must_use]
+    pub fn parse(input: &str) -> Option<Self> {
+        let version = input
+            .trim()
+            .trim_start_matches(['^', '~', '>', '=', 'v', ' '])
+            .split([' ', '-', '+'])
+            .next()?;
+        let mut parts = version.split('.').map(|part| match part {
+            "x" | "X" | "*" => Some(0),
+            part => part.parse().ok(),
+        });
+        let major = parts.next()??;
+        let minor = parts.next().unwrap_or(Some(0))?;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for TurboVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Files [`detect`] reads, relative to the repo root
+pub const VERSION_FILES: &[&str] = &["node_modules/turbo/package.json", "package.json"];
+
+/// How long `turbo --version` may take before detection gives up
+const BINARY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where a detected version came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VersionSource {
+    /// `node_modules/turbo/package.json`
+    Installed,
+    /// The `turbo` entry of the root package.json (lower bound of the range)
+    PackageJson,
+    /// `turbo --version`
+    Binary,
+}
+
+/// Turbo version of the repo at `root`
+///
+/// Prefers the installed package, then the root package.json range, then the
+/// `turbo` binary on `PATH`, which is killed after [`BINARY_TIMEOUT`].
+pub async fn detect(root: &Path) -> Option<(TurboVersion, VersionSource)> {
+    let installed = tokio::fs::read_to_string(root.join("node_modules/turbo/package.json"))
+        .await
+        .ok()
+        .and_then(|content| {
+            let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+            TurboVersion::parse(json.get("version")?.as_str()?)
+        });
+    if let Some(version) = installed {
+        return Some((version, VersionSource::Installed));
+    }
+
+    let declared = tokio::fs::read_to_string(root.join("package.json"))
+        .await
+        .ok()
+        .and_then(|content| declared_version(&content));
+    if let Some(version) = declared {
+        return Some((version, VersionSource::PackageJson));
+    }
+
+    let output = tokio::process::Command::new("turbo")
+        .arg("--version")
+        .current_dir(root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(BINARY_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| TurboVersion::parse(&String::from_utf8_lossy(&output.stdout)))?
+        .map(|version| (version, VersionSource::Binary))
+}
+
+/// Lower bound of the `turbo` range in a package.json's dev or regular dependencies
+#[must_use]
+pub fn declared_version(package_json: &str) -> Option<TurboVersion> {
+    let json: serde_json::Value = serde_json::from_str(package_json).ok()?;
+    ["devDependencies", "dependencies"]
+        .iter()
+        .find_map(|section| json.get(section)?.get("turbo")?.as_str())
+        .and_then(TurboVersion::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ranges() {
+        assert_eq!(
+            TurboVersion::parse("^2.3.1"),
+            Some(TurboVersion::new(2, 3, 1))
+        );
+        assert_eq!(
+            TurboVersion::parse("~1.13"),
+            Some(TurboVersion::new(1, 13, 0))
+        );
+        assert_eq!(
+            TurboVersion::parse(">= 2.x"),
+            Some(TurboVersion::new(2, 0, 0))
+        );
+        assert_eq!(
+            TurboVersion::parse("2.5.0-canary.1\n"),
+            Some(TurboVersion::new(2, 5, 0))
+        );
+        assert_eq!(TurboVersion::parse("latest"), None);
+        assert_eq!(TurboVersion::parse("workspace:*"), None);
+        assert!(TurboVersion::new(1, 13, 0) < TurboVersion::new(2, 0, 0));
+    }
+
+    #[test]
+    fn test_declared_version() {
+        assert_eq!(
+            declared_version(r#"{"devDependencies": {"turbo": "^2.4.0"}}"#),
+            Some(TurboVersion::new(2, 4, 0))
+        );
+        assert_eq!(declared_version(r#"{"dependencies": {}}"#), None);
+    }
+}
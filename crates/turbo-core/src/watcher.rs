@@ -0,0 +1,470 @@
+//! Workspace file watching with typed, debounced change events
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use indexmap::{IndexMap, IndexSet};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::{
+    config::CONFIG_FILES,
+    discovery::Package,
+    error::{Error, Result},
+    runs::RUNS_DIR,
+    workspace::{WORKSPACE_FILES, WorkspaceSnapshot},
+};
+
+/// Default quiet period before a batch of file changes is reported
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// A change to the workspace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum WorkspaceEvent {
+    /// A package.json appeared under a workspace glob
+    PackageAdded { name: String, path: PathBuf },
+    /// A package disappeared or its package.json became unreadable
+    PackageRemoved { name: String, path: PathBuf },
+    /// Scripts of a package were added, removed or edited
+    ScriptsChanged {
+        name: String,
+        path: PathBuf,
+        added: Vec<String>,
+        removed: Vec<String>,
+        modified: Vec<String>,
+    },
+    /// A root or package turbo.json changed
+    ConfigChanged { path: PathBuf },
+    /// pnpm-workspace.yaml or a lockfile changed
+    WorkspaceConfigChanged { path: PathBuf },
+    /// A run summary in `.turbo/runs` was written or removed
+    RunsChanged { path: PathBuf },
+}
+
+/// Watches turbo.json files, every package.json, workspace config, lockfiles
+/// and run summaries
+///
+/// Directories are watched non-recursively (the root, each package and each
+/// package's parent, so new packages are noticed) to stay clear of
+/// `node_modules`. Of the directories created later only those next to
+/// existing packages are watched, so build output inside packages or `.turbo`
+/// adds no watches. Events are batched until nothing changed for the debounce
+/// period. Dropping the watcher stops it.
+pub struct WorkspaceWatcher {
+    events: mpsc::UnboundedReceiver<Vec<WorkspaceEvent>>,
+}
+
+impl WorkspaceWatcher {
+    /// Start watching the workspace at `root`
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// # Errors
+    /// Returns error if packages cannot be discovered or the OS watcher cannot be created
+    pub async fn start(root: impl Into<PathBuf>, debounce: Duration) -> Result<Self> {
+        let snapshot = WorkspaceSnapshot::load(root).await?;
+
+        let (raw_tx, raw) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let _ = raw_tx.send(event);
+            }
+        })
+        .map_err(|e| Error::WatchFailed {
+            path: snapshot.root().to_path_buf(),
+            message: e.to_string(),
+        })?;
+
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let mut task = WatchTask {
+            watcher,
+            watched: IndexSet::new(),
+            snapshot,
+            raw,
+            events: events_tx,
+            debounce,
+        };
+        task.sync_watches();
+        tokio::spawn(task.run());
+
+        Ok(Self { events })
+    }
+
+    /// Next batch of events; `None` once the watcher has stopped
+    pub async fn next(&mut self) -> Option<Vec<WorkspaceEvent>> {
+        self.events.recv().await
+    }
+}
+
+/// Background task owning the OS watcher and the snapshot used for diffing
+struct WatchTask {
+    watcher: RecommendedWatcher,
+    watched: IndexSet<PathBuf>,
+    snapshot: WorkspaceSnapshot,
+    raw: mpsc::UnboundedReceiver<notify::Event>,
+    events: mpsc::UnboundedSender<Vec<WorkspaceEvent>>,
+    debounce: Duration,
+}
+
+impl WatchTask {
+    async fn run(mut self) {
+        while let Some(event) = self.raw.recv().await {
+            let mut paths = IndexSet::new();
+            self.collect(event, &mut paths);
+            while let Ok(Some(event)) = tokio::time::timeout(self.debounce, self.raw.recv()).await {
+                self.collect(event, &mut paths);
+            }
+
+            if self.events.is_closed() {
+                break;
+            }
+            if paths.is_empty() {
+                continue;
+            }
+            let events = self.apply(&paths).await;
+            if !events.is_empty() && self.events.send(events).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Keep the paths of an OS event that can affect the workspace
+    fn collect(&mut self, event: notify::Event, paths: &mut IndexSet<PathBuf>) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            if path.components().any(|component| {
+                let name = component.as_os_str();
+                name == "node_modules" || name == ".git"
+            }) {
+                continue;
+            }
+            if matches!(event.kind, EventKind::Create(_)) && path.is_dir() {
+                let root = self.snapshot.root();
+                if path == root.join(".turbo") || path == root.join(RUNS_DIR) {
+                    self.watch(&path);
+                } else if self.is_package_candidate(&path) {
+                    // a new directory next to packages may become a package
+                    self.watch(&path);
+                    paths.insert(path.join("package.json"));
+                }
+            } else if is_watched_file(&path) || self.is_run_summary(&path) {
+                paths.insert(path);
+            } else if matches!(event.kind, EventKind::Remove(_))
+                && self.snapshot.packages().iter().any(|p| p.path == path)
+            {
+                paths.insert(path.join("package.json"));
+            }
+        }
+    }
+
+    /// Refresh the snapshot and describe what changed
+    async fn apply(&mut self, paths: &IndexSet<PathBuf>) -> Vec<WorkspaceEvent> {
+        let before = self.snapshot.packages().to_vec();
+        let (runs, paths): (Vec<_>, Vec<_>) =
+            paths.iter().partition(|path| self.is_run_summary(path));
+        let runs = runs
+            .into_iter()
+            .map(|path| WorkspaceEvent::RunsChanged { path: path.clone() });
+        for path in &paths {
+            self.snapshot.invalidate(path);
+        }
+        if let Err(e) = self.snapshot.refresh().await {
+            tracing::warn!("Failed to refresh workspace: {e}");
+            return runs.collect();
+        }
+
+        let mut events = diff_packages(&before, self.snapshot.packages());
+        for path in paths {
+            let name = path.file_name().and_then(|name| name.to_str());
+            if name.is_some_and(|name| CONFIG_FILES.contains(&name)) {
+                events.push(WorkspaceEvent::ConfigChanged { path: path.clone() });
+            } else if name.is_some_and(|name| name != "package.json")
+                && path.parent() == Some(self.snapshot.root())
+            {
+                events.push(WorkspaceEvent::WorkspaceConfigChanged { path: path.clone() });
+            }
+        }
+        events.extend(runs);
+        self.sync_watches();
+        events
+    }
+
+    /// Whether a new directory can become a package: a direct child of a directory
+    /// holding packages, except `.turbo` and directories inside a package
+    fn is_package_candidate(&self, dir: &Path) -> bool {
+        let root = self.snapshot.root();
+        let (Some(parent), Some(name)) = (dir.parent(), dir.file_name()) else {
+            return false;
+        };
+        let packages = self.snapshot.packages();
+        name != ".turbo"
+            && (parent == root || !packages.iter().any(|package| package.path == parent))
+            && packages
+                .iter()
+                .any(|package| package.path.parent() == Some(parent))
+    }
+
+    /// Whether `path` is a run summary directly inside `.turbo/runs`
+    fn is_run_summary(&self, path: &Path) -> bool {
+        path.parent() == Some(self.snapshot.root().join(RUNS_DIR).as_path())
+            && path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+    }
+
+    /// Watch the root, the runs directory, every package directory and every package's parent
+    fn sync_watches(&mut self) {
+        let root = self.snapshot.root().to_path_buf();
+        let mut dirs = IndexSet::new();
+        dirs.insert(root.clone());
+        // `.turbo` too, so a runs directory created later is picked up
+        for dir in [root.join(".turbo"), root.join(RUNS_DIR)] {
+            if dir.is_dir() {
+                dirs.insert(dir);
+            }
+        }
+        for package in self.snapshot.packages() {
+            dirs.insert(package.path.clone());
+            if let Some(parent) = package.path.parent()
+                && parent.starts_with(&root)
+            {
+                dirs.insert(parent.to_path_buf());
+            }
+        }
+        for dir in dirs {
+            self.watch(&dir);
+        }
+        // the OS drops watches of deleted directories
+        self.watched.retain(|dir| dir.exists());
+    }
+
+    fn watch(&mut self, dir: &Path) {
+        if self.watched.contains(dir) {
+            return;
+        }
+        match self.watcher.watch(dir, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                self.watched.insert(dir.to_path_buf());
+            }
+            Err(e) => tracing::warn!("Failed to watch {}: {e}", dir.display()),
+        }
+    }
+}
+
+fn is_watched_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| CONFIG_FILES.contains(&name) || WORKSPACE_FILES.contains(&name))
+}
+
+/// Package-level events between two package lists
+fn diff_packages(before: &[Package], after: &[Package]) -> Vec<WorkspaceEvent> {
+    let before: IndexMap<&Path, &Package> = before.iter().map(|p| (p.path.as_path(), p)).collect();
+    let after: IndexMap<&Path, &Package> = after.iter().map(|p| (p.path.as_path(), p)).collect();
+
+    let mut events = Vec::new();
+    for (path, package) in &after {
+        let Some(previous) = before.get(path) else {
+            events.push(WorkspaceEvent::PackageAdded {
+                name: package.name.clone(),
+                path: package.path.clone(),
+            });
+            continue;
+        };
+
+        let names = |scripts: &IndexMap<String, String>, other: &IndexMap<String, String>| {
+            scripts
+                .keys()
+                .filter(|name| !other.contains_key(*name))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let added = names(&package.scripts, &previous.scripts);
+        let removed = names(&previous.scripts, &package.scripts);
+        let modified: Vec<_> = package
+            .scripts
+            .iter()
+            .filter(|(name, command)| {
+                previous
+                    .scripts
+                    .get(*name)
+                    .is_some_and(|old| old != *command)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if !added.is_empty() || !removed.is_empty() || !modified.is_empty() {
+            events.push(WorkspaceEvent::ScriptsChanged {
+                name: package.name.clone(),
+                path: package.path.clone(),
+                added,
+                removed,
+                modified,
+            });
+        }
+    }
+    for (path, package) in &before {
+        if !after.contains_key(path) {
+            events.push(WorkspaceEvent::PackageRemoved {
+                name: package.name.clone(),
+                path: package.path.clone(),
+            });
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{TempDir, package};
+
+    #[test]
+    fn test_diff_packages() {
+        let before = vec![
+            package("ui")
+                .script("build", "tsc")
+                .script("dev", "tsc -w")
+                .build(),
+            package("old").build(),
+        ];
+        let after = vec![
+            package("ui")
+                .script("build", "tsup")
+                .script("lint", "eslint")
+                .build(),
+            package("new").build(),
+        ];
+
+        let events = diff_packages(&before, &after);
+
+        assert_eq!(
+            events,
+            vec![
+                WorkspaceEvent::ScriptsChanged {
+                    name: "ui".to_string(),
+                    path: PathBuf::from("/repo/packages/ui"),
+                    added: vec!["lint".to_string()],
+                    removed: vec!["dev".to_string()],
+                    modified: vec!["build".to_string()],
+                },
+                WorkspaceEvent::PackageAdded {
+                    name: "new".to_string(),
+                    path: PathBuf::from("/repo/packages/new"),
+                },
+                WorkspaceEvent::PackageRemoved {
+                    name: "old".to_string(),
+                    path: PathBuf::from("/repo/packages/old"),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reports_script_changes() {
+        let root = TempDir::new("watcher");
+        root.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["packages/*"]}"#,
+        );
+        root.write("packages/ui/package.json", r#"{"name": "ui"}"#);
+
+        let mut watcher = WorkspaceWatcher::start(root.path(), Duration::from_millis(50))
+            .await
+            .unwrap();
+        root.write(
+            "packages/ui/package.json",
+            r#"{"name": "ui", "scripts": {"lint": "eslint"}}"#,
+        );
+
+        let events = tokio::time::timeout(Duration::from_secs(5), watcher.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            WorkspaceEvent::ScriptsChanged { name, added, .. } if name == "ui" && added == &["lint"]
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reports_new_run_summaries() {
+        let root = TempDir::new("watcher-runs");
+        root.write("package.json", r#"{"name": "repo"}"#);
+        std::fs::create_dir_all(root.join(RUNS_DIR)).unwrap();
+
+        let mut watcher = WorkspaceWatcher::start(root.path(), Duration::from_millis(50))
+            .await
+            .unwrap();
+        root.write(&format!("{RUNS_DIR}/2abc.json"), "{}");
+
+        let events = tokio::time::timeout(Duration::from_secs(5), watcher.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![WorkspaceEvent::RunsChanged {
+                path: root.join(RUNS_DIR).join("2abc.json"),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_output_adds_no_watches() {
+        let root = TempDir::new("watcher-build-output");
+        root.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["packages/*"]}"#,
+        );
+        root.write("packages/a/package.json", r#"{"name": "a"}"#);
+        let (raw_tx, raw) = mpsc::unbounded_channel();
+        let (events, _events) = mpsc::unbounded_channel();
+        let mut task = WatchTask {
+            watcher: notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    let _ = raw_tx.send(event);
+                }
+            })
+            .unwrap(),
+            watched: IndexSet::new(),
+            snapshot: WorkspaceSnapshot::load(root.path()).await.unwrap(),
+            raw,
+            events,
+            debounce: DEFAULT_DEBOUNCE,
+        };
+        task.sync_watches();
+        let watched = task.watched.clone();
+        let created = |path: PathBuf| {
+            notify::Event::new(EventKind::Create(notify::event::CreateKind::Folder)).add_path(path)
+        };
+
+        let mut paths = IndexSet::new();
+        for dir in [
+            "packages/a/dist",
+            "packages/a/dist/x",
+            ".turbo",
+            ".turbo/cache",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            task.collect(created(root.join(dir)), &mut paths);
+        }
+        assert!(paths.is_empty());
+        let mut expected = watched;
+        expected.insert(root.join(".turbo"));
+        assert_eq!(task.watched, expected);
+
+        std::fs::create_dir_all(root.join("packages/b")).unwrap();
+        task.collect(created(root.join("packages/b")), &mut paths);
+        assert_eq!(
+            paths,
+            IndexSet::from([root.join("packages/b/package.json")])
+        );
+        assert!(task.watched.contains(&root.join("packages/b")));
+    }
+}
@@ -0,0 +1,376 @@
+//! Long-lived workspace snapshot with per-file invalidation
+
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{
+    config::{CONFIG_FILES, TurboConfig},
+    discovery::{Package, PackageDiscovery, WorkspaceMatcher},
+    error::Result,
+};
+
+/// Root-level files whose change can add or remove packages
+pub const WORKSPACE_FILES: &[&str] = &[
+    "package.json",
+    "pnpm-workspace.yaml",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "bun.lock",
+    "bun.lockb",
+];
+
+/// Cheap change detection: modification time and size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Fingerprint {
+    async fn of(path: &Path) -> Option<Self> {
+        let metadata = tokio::fs::metadata(path).await.ok()?;
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+/// What [`WorkspaceSnapshot::refresh`] reloaded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Refresh {
+    /// Packages were re-discovered (workspace config, lockfile or a new package.json)
+    pub workspace: bool,
+    /// turbo.json was reloaded
+    pub config: bool,
+    /// package.json files reloaded individually
+    pub packages: Vec<PathBuf>,
+}
+
+impl Refresh {
+    /// Whether nothing was reloaded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.workspace && !self.config && self.packages.is_empty()
+    }
+}
+
+/// Packages and turbo.json of a repo, kept up to date file by file
+///
+/// [`refresh`](Self::refresh) compares the modification time and size of every
+/// tracked file and reloads only what changed: a single package.json, the
+/// turbo.json, or the whole package list when workspace config or a lockfile
+/// changed. Callers whose file watcher reports every change use
+/// [`refresh_invalidated`](Self::refresh_invalidated) instead, which skips the
+/// comparison.
+#[derive(Debug, Clone)]
+pub struct WorkspaceSnapshot {
+    root: PathBuf,
+    packages: Vec<Package>,
+    config: Option<TurboConfig>,
+    /// Workspace globs, to tell a new package from any other package.json
+    matcher: Option<WorkspaceMatcher>,
+    fingerprints: IndexMap<PathBuf, Option<Fingerprint>>,
+    dirty: IndexSet<PathBuf>,
+    generation: u64,
+}
+
+impl WorkspaceSnapshot {
+    /// Discover packages and load turbo.json under `root`
+    ///
+    /// # Errors
+    /// Returns error if package discovery fails
+    pub async fn load(root: impl Into<PathBuf>) -> Result<Self> {
+        let mut snapshot = Self {
+            root: root.into(),
+            packages: Vec::new(),
+            config: None,
+            matcher: None,
+            fingerprints: IndexMap::new(),
+            dirty: IndexSet::new(),
+            generation: 0,
+        };
+        snapshot.rediscover().await?;
+        snapshot.reload_config().await;
+        Ok(snapshot)
+    }
+
+    /// Repo root
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Workspace packages, including the root package
+    #[must_use]
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    /// Parsed turbo.json, if it exists and is valid
+    #[must_use]
+    pub const fn config(&self) -> Option<&TurboConfig> {
+        self.config.as_ref()
+    }
+
+    /// Incremented every time a refresh reloads something
+    #[must_use]
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Mark a path as changed (e.g. from a file watcher) so the next refresh reloads it
+    ///
+    /// Paths inside `node_modules` are ignored.
+    pub fn invalidate(&mut self, path: &Path) {
+        if !path
+            .components()
+            .any(|component| component.as_os_str() == "node_modules")
+        {
+            self.dirty.insert(path.to_path_buf());
+        }
+    }
+
+    /// Reload whatever changed since the last refresh
+    ///
+    /// # Errors
+    /// Returns error if packages have to be re-discovered and discovery fails
+    pub async fn refresh(&mut self) -> Result<Refresh> {
+        let mut changed = std::mem::take(&mut self.dirty);
+        for (path, fingerprint) in &self.fingerprints {
+            if Fingerprint::of(path).await != *fingerprint {
+                changed.insert(path.clone());
+            }
+        }
+        self.reload(changed).await
+    }
+
+    /// Reload only the paths passed to [`invalidate`](Self::invalidate) since the last refresh
+    ///
+    /// # Errors
+    /// Returns error if packages have to be re-discovered and discovery fails
+    pub async fn refresh_invalidated(&mut self) -> Result<Refresh> {
+        let changed = std::mem::take(&mut self.dirty);
+        self.reload(changed).await
+    }
+
+    async fn reload(&mut self, changed: IndexSet<PathBuf>) -> Result<Refresh> {
+        if changed.is_empty() {
+            return Ok(Refresh::default());
+        }
+
+        let mut refresh = Refresh::default();
+        if changed.iter().any(|path| self.affects_package_list(path)) {
+            self.rediscover().await?;
+            refresh.workspace = true;
+        } else {
+            for path in &changed {
+                if self.packages.iter().any(|p| &p.package_json_path == path) {
+                    self.reload_package(path).await;
+                    refresh.packages.push(path.clone());
+                }
+            }
+        }
+        if changed
+            .iter()
+            .any(|path| self.is_root_file(path, CONFIG_FILES))
+        {
+            self.reload_config().await;
+            refresh.config = true;
+        }
+
+        if !refresh.is_empty() {
+            self.generation += 1;
+        }
+        Ok(refresh)
+    }
+
+    fn is_root_file(&self, path: &Path, names: &[&str]) -> bool {
+        path.parent() == Some(self.root.as_path())
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| names.contains(&name))
+    }
+
+    /// Workspace config, lockfiles and package.json files of unknown packages in
+    /// directories the workspace globs match
+    ///
+    /// Other package.json files, such as build output or test fixtures, cannot add
+    /// a package, so they do not cost a re-discovery.
+    fn affects_package_list(&self, path: &Path) -> bool {
+        if self.is_root_file(path, WORKSPACE_FILES) {
+            return true;
+        }
+        let Some(dir) = path
+            .parent()
+            .filter(|_| path.file_name().is_some_and(|name| name == "package.json"))
+        else {
+            return false;
+        };
+        !self.packages.iter().any(|p| p.package_json_path == path)
+            && dir.strip_prefix(&self.root).is_ok_and(|relative| {
+                !relative
+                    .components()
+                    .any(|component| component.as_os_str() == "node_modules")
+                    && self
+                        .matcher
+                        .as_ref()
+                        .is_some_and(|matcher| matcher.is_match(relative))
+            })
+    }
+
+    async fn rediscover(&mut self) -> Result<()> {
+        let discovery = PackageDiscovery::new(self.root.clone());
+        let mut packages = discovery.discover_packages().await?;
+        self.matcher = discovery.workspace_matcher().await;
+        if !packages.iter().any(|package| package.path == self.root)
+            && let Ok(root_package) = PackageDiscovery::load_package(&self.root).await
+        {
+            packages.push(root_package);
+        }
+
+        self.fingerprints
+            .retain(|path, _| path.parent() == Some(self.root.as_path()));
+        for name in WORKSPACE_FILES {
+            self.track(self.root.join(name)).await;
+        }
+        for package in &packages {
+            self.track(package.package_json_path.clone()).await;
+        }
+        self.packages = packages;
+        Ok(())
+    }
+
+    async fn reload_package(&mut self, package_json_path: &Path) {
+        let Some(index) = self
+            .packages
+            .iter()
+            .position(|package| package.package_json_path == package_json_path)
+        else {
+            return;
+        };
+        let dir = self.packages[index].path.clone();
+        match PackageDiscovery::load_package(&dir).await {
+            Ok(package) => self.packages[index] = package,
+            Err(e) => {
+                tracing::warn!("Dropping package {}: {e}", dir.display());
+                self.packages.remove(index);
+            }
+        }
+        self.track(package_json_path.to_path_buf()).await;
+    }
+
+    async fn reload_config(&mut self) {
+        self.config = TurboConfig::find_and_load(&self.root).await.ok();
+        for name in CONFIG_FILES {
+            self.track(self.root.join(name)).await;
+        }
+    }
+
+    async fn track(&mut self, path: PathBuf) {
+        let fingerprint = Fingerprint::of(&path).await;
+        self.fingerprints.insert(path, fingerprint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn temp_repo(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        dir.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["packages/*"]}"#,
+        );
+        dir.write("turbo.json", r#"{"tasks": {"build": {}}}"#);
+        dir.write(
+            "packages/a/package.json",
+            r#"{"name": "a", "scripts": {"build": "tsc"}}"#,
+        );
+        dir
+    }
+
+    fn scripts(snapshot: &WorkspaceSnapshot, name: &str) -> Vec<String> {
+        snapshot
+            .packages()
+            .iter()
+            .find(|package| package.name == name)
+            .map(|package| package.scripts.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reloads_single_package() {
+        let dir = temp_repo("snapshot-package");
+        let mut snapshot = WorkspaceSnapshot::load(dir.path()).await.unwrap();
+        assert_eq!(scripts(&snapshot, "a"), vec!["build"]);
+        assert!(snapshot.refresh().await.unwrap().is_empty());
+
+        dir.write(
+            "packages/a/package.json",
+            r#"{"name": "a", "scripts": {"build": "tsc", "test": "vitest"}}"#,
+        );
+
+        let refresh = snapshot.refresh().await.unwrap();
+        assert!(!refresh.workspace);
+        assert_eq!(refresh.packages, vec![dir.join("packages/a/package.json")]);
+        assert_eq!(scripts(&snapshot, "a"), vec!["build", "test"]);
+        assert_eq!(snapshot.generation(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_new_package_triggers_rediscovery() {
+        let dir = temp_repo("snapshot-workspace");
+        let mut snapshot = WorkspaceSnapshot::load(dir.path()).await.unwrap();
+
+        for outside in [
+            "node_modules/x/package.json",
+            "packages/a/dist/package.json",
+            "fixtures/c/package.json",
+        ] {
+            dir.write(outside, r#"{"name": "not-a-package"}"#);
+            snapshot.invalidate(&dir.join(outside));
+        }
+        assert!(snapshot.refresh_invalidated().await.unwrap().is_empty());
+
+        dir.write("packages/b/package.json", r#"{"name": "b"}"#);
+        snapshot.invalidate(&dir.join("packages/b/package.json"));
+
+        let refresh = snapshot.refresh().await.unwrap();
+        assert!(refresh.workspace);
+        assert!(!refresh.config);
+        assert!(
+            snapshot
+                .packages()
+                .iter()
+                .any(|package| package.name == "b")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_invalidated_reloads_only_reported_paths() {
+        let dir = temp_repo("snapshot-invalidated");
+        let mut snapshot = WorkspaceSnapshot::load(dir.path()).await.unwrap();
+        dir.write(
+            "packages/a/package.json",
+            r#"{"name": "a", "scripts": {"lint": "eslint"}}"#,
+        );
+
+        assert!(snapshot.refresh_invalidated().await.unwrap().is_empty());
+        assert_eq!(scripts(&snapshot, "a"), vec!["build"]);
+
+        snapshot.invalidate(&dir.join("packages/a/package.json"));
+        let refresh = snapshot.refresh_invalidated().await.unwrap();
+        assert_eq!(refresh.packages, vec![dir.join("packages/a/package.json")]);
+        assert_eq!(scripts(&snapshot, "a"), vec!["lint"]);
+    }
+}
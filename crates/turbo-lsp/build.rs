@@ -1,4 +1,11 @@
-use std::{collections::BTreeMap, env, error::Error, fmt::Write as _, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::PathBuf,
+};
 
 use reqwest::{Url, blocking::Client};
 use serde::Deserialize;
@@ -6,6 +13,12 @@ use serde_json::Value;
 
 const DOC_LINKS_FILE: &str = "doc_links.ts";
 const SCHEMA_URL: &str = "https://turborepo.dev/schema.json";
+const EMBEDDED_SCHEMA_FILE: &str = "schema.json";
+/// Definitions whose keys the embedded schema validates
+const EMBEDDED_DEFINITIONS: &[&str] = &["RootSchema", "WorkspaceSchema", "Pipeline", "RemoteCache"];
+/// Removed keys the embedded schema keeps, so they are reported as removed
+/// rather than unknown
+const REMOVED_KEYS: &[&str] = &["pipeline"];
 
 #[derive(Debug, Deserialize)]
 struct HoverSpec {
@@ -17,8 +30,15 @@ struct HoverSpec {
     summary_override: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct EnumValueSpec {
+    value: String,
+    description: String,
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     println!("cargo:rerun-if-changed={DOC_LINKS_FILE}");
+    println!("cargo:rerun-if-changed={EMBEDDED_SCHEMA_FILE}");
 
     let source = fs::read_to_string(DOC_LINKS_FILE)?;
     let doc_links = parse_string_map(&source, "DOC_LINKS")?;
@@ -28,6 +48,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         parse_json_const::<BTreeMap<String, HoverSpec>>(&source, "TOP_LEVEL_HOVERS_JSON")?;
     let task_field_hovers =
         parse_json_const::<BTreeMap<String, HoverSpec>>(&source, "TASK_FIELD_HOVERS_JSON")?;
+    let enum_values =
+        parse_json_const::<BTreeMap<String, Vec<EnumValueSpec>>>(&source, "ENUM_VALUES_JSON")?;
 
     let client = Client::builder()
         .user_agent("turbo-lsp-doc-checker")
@@ -40,8 +62,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         &top_level_hovers,
         &task_field_hovers,
     )?;
+    validate_enum_values(&enum_values)?;
     let schema = fetch_schema(&client)?;
     validate_schema_keys(&schema, &top_level_keys, &task_field_keys)?;
+    validate_embedded_schema(&schema)?;
 
     let top_level_summaries = schema_summaries(&schema, &top_level_keys, SchemaSection::TopLevel)?;
     let task_field_summaries =
@@ -53,6 +77,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         &task_field_hovers,
         &top_level_summaries,
         &task_field_summaries,
+        &enum_values,
+        &top_level_keys,
+        &task_field_keys,
     )?;
 
     Ok(())
@@ -253,6 +280,19 @@ fn validate_hover_specs(
     Ok(())
 }
 
+fn validate_enum_values(
+    enum_values: &BTreeMap<String, Vec<EnumValueSpec>>,
+) -> Result<(), Box<dyn Error>> {
+    for (key, values) in enum_values {
+        if values.is_empty() {
+            return Err(
+                format!("ENUM_VALUES_JSON key `{key}` has no values in {DOC_LINKS_FILE}").into(),
+            );
+        }
+    }
+    Ok(())
+}
+
 fn fetch_schema(client: &Client) -> Result<Value, Box<dyn Error>> {
     Ok(client
         .get(SCHEMA_URL)
@@ -290,6 +330,46 @@ fn validate_schema_keys(
     Ok(())
 }
 
+/// Fail when the embedded schema allows other keys than the published one in a
+/// definition it validates, since a missing key would be reported as unknown
+fn validate_embedded_schema(schema: &Value) -> Result<(), Box<dyn Error>> {
+    let embedded: Value = serde_json::from_str(&fs::read_to_string(EMBEDDED_SCHEMA_FILE)?)?;
+    for name in EMBEDDED_DEFINITIONS {
+        let published = property_names(schema, name)
+            .ok_or_else(|| format!("schema missing {name}.properties"))?;
+        let ours = property_names(&embedded, name)
+            .ok_or_else(|| format!("{EMBEDDED_SCHEMA_FILE} missing {name}.properties"))?;
+        if ours != published {
+            let missing: Vec<_> = published.difference(&ours).collect();
+            let unexpected: Vec<_> = ours.difference(&published).collect();
+            return Err(format!(
+                "{EMBEDDED_SCHEMA_FILE} `{name}` differs from {SCHEMA_URL}: \
+                 missing {missing:?}, unexpected {unexpected:?}"
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Property names of a schema definition, without [`REMOVED_KEYS`]
+///
+/// The published schema may describe `RootSchema` at its top level instead.
+fn property_names<'a>(schema: &'a Value, name: &str) -> Option<BTreeSet<&'a str>> {
+    let definition = match &schema["definitions"][name] {
+        Value::Null if name == "RootSchema" => schema,
+        definition => definition,
+    };
+    Some(
+        definition["properties"]
+            .as_object()?
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !REMOVED_KEYS.contains(key))
+            .collect(),
+    )
+}
+
 #[derive(Clone, Copy)]
 enum SchemaSection {
     TopLevel,
@@ -339,6 +419,9 @@ fn write_generated_module(
     task_field_hovers: &BTreeMap<String, HoverSpec>,
     top_level_summaries: &BTreeMap<String, String>,
     task_field_summaries: &BTreeMap<String, String>,
+    enum_values: &BTreeMap<String, Vec<EnumValueSpec>>,
+    top_level_keys: &[String],
+    task_field_keys: &[String],
 ) -> Result<(), Box<dyn Error>> {
     let out_dir = PathBuf::from(env::var("OUT_DIR")?);
     let out_path = out_dir.join("doc_links_generated.rs");
@@ -362,6 +445,9 @@ fn write_generated_module(
     write_hover_meta_fn(&mut generated, "task_field_hover_meta", task_field_hovers);
     write_summary_fn(&mut generated, "top_level_summary", top_level_summaries);
     write_summary_fn(&mut generated, "task_field_summary", task_field_summaries);
+    write_enum_values_fn(&mut generated, enum_values);
+    write_key_list(&mut generated, "DOCUMENTED_TOP_LEVEL_KEYS", top_level_keys);
+    write_key_list(&mut generated, "DOCUMENTED_TASK_FIELDS", task_field_keys);
 
     fs::write(out_path, generated)?;
     Ok(())
@@ -402,6 +488,30 @@ fn write_summary_fn(generated: &mut String, fn_name: &str, entries: &BTreeMap<St
     generated.push_str("        _ => None,\n    }\n}\n");
 }
 
+fn write_enum_values_fn(generated: &mut String, entries: &BTreeMap<String, Vec<EnumValueSpec>>) {
+    generated.push_str(
+        "\npub(crate) fn enum_values(name: &str) -> Option<&'static [(&'static str, &'static str)]> {\n",
+    );
+    generated.push_str("    match name {\n");
+    for (key, values) in entries {
+        let _ = write!(generated, "        {key:?} => Some(&[");
+        for spec in values {
+            let _ = write!(generated, "({:?}, {:?}), ", spec.value, spec.description);
+        }
+        generated.push_str("]),\n");
+    }
+    generated.push_str("        _ => None,\n    }\n}\n");
+}
+
+/// `DOCUMENTED_TOP_LEVEL_KEYS` / `DOCUMENTED_TASK_FIELDS` constants, for tests checking
+/// them against the embedded schema
+fn write_key_list(generated: &mut String, const_name: &str, keys: &[String]) {
+    let _ = writeln!(
+        generated,
+        "\n#[cfg(test)]\npub(crate) const {const_name}: &[&str] = &{keys:?};"
+    );
+}
+
 fn option_str_literal(value: Option<&str>) -> String {
     value.map_or_else(|| "None".to_string(), |value| format!("Some({value:?})"))
 }
@@ -153,3 +153,21 @@ export const TASK_FIELD_HOVERS_JSON = String.raw`{
     "example": "{\n  \"tasks\": {\n    \"test\": {\n      \"outputLogs\": \"new-only\"\n    }\n  }\n}"
   }
 }`;
+
+export const ENUM_VALUES_JSON = String.raw`{
+  "outputLogs": [
+    { "value": "full", "description": "Show all logs." },
+    { "value": "hash-only", "description": "Only show the task hash." },
+    { "value": "new-only", "description": "Only show logs of cache misses." },
+    { "value": "errors-only", "description": "Only show logs of failed tasks." },
+    { "value": "none", "description": "Hide all task logs." }
+  ],
+  "ui": [
+    { "value": "tui", "description": "Interactive terminal UI." },
+    { "value": "stream", "description": "Stream logs as they are produced." }
+  ],
+  "envMode": [
+    { "value": "strict", "description": "Only declared environment variables reach tasks." },
+    { "value": "loose", "description": "Tasks see the whole environment." }
+  ]
+}`;
@@ -0,0 +1,222 @@
+//! Task graph edges of the root turbo.json for the call hierarchy
+//!
+//! Labels are turbo.json keys (`build`, `web#build`) or `package#task` for a
+//! `^` dependency expanded across workspace packages.
+
+use std::ops::Range;
+
+use indexmap::IndexMap;
+use jsonc_parser::{CollectOptions, ParseOptions, ast::Value, parse_to_ast};
+use turbo_core::Package;
+
+use crate::{TaskReference, collapse_string_range, symbols::task_definitions};
+
+/// A `dependsOn` entry of a task definition
+#[derive(Debug, Clone)]
+struct Dependency {
+    /// Key of the task definition holding the entry
+    key: String,
+    entry: String,
+    range: Range<usize>,
+}
+
+/// Tasks connected to a label, with the `dependsOn` entry ranges linking them
+pub type Calls = IndexMap<String, Vec<Range<usize>>>;
+
+/// Byte range of the definition key for `label`, falling back to its generic task
+pub fn definition_range(text: &str, label: &str) -> Option<Range<usize>> {
+    let task = TaskReference::parse(label).task;
+    let keys = task_definitions(text);
+    keys.iter()
+        .find(|(key, _)| key == label)
+        .or_else(|| keys.iter().find(|(key, _)| key == task))
+        .map(|(_, range)| range.clone())
+}
+
+/// Tasks `label` depends on; `^task` entries of a package task expand to its dependencies
+pub fn outgoing(text: &str, label: &str, packages: &[Package]) -> Calls {
+    let reference = TaskReference::parse(label);
+    let key = if task_definitions(text).iter().any(|(key, _)| key == label) {
+        label
+    } else {
+        reference.task
+    };
+
+    let mut calls = Calls::new();
+    for dependency in dependencies(text).into_iter().filter(|d| d.key == key) {
+        let targets = match (dependency.entry.strip_prefix('^'), reference.package) {
+            (Some(task), Some(package)) => dependency_packages(packages, package)
+                .filter(|dependency| dependency.scripts.contains_key(task))
+                .map(|dependency| format!("{}#{task}", dependency.name))
+                .collect(),
+            (Some(_), None) => vec![dependency.entry.clone()],
+            (None, _) => vec![dependency.entry.trim_start_matches('$').to_string()],
+        };
+        for target in targets {
+            calls
+                .entry(target)
+                .or_default()
+                .push(dependency.range.clone());
+        }
+    }
+    calls
+}
+
+/// Tasks depending on `label`, directly or through `^` from dependent packages
+pub fn incoming(text: &str, label: &str, packages: &[Package]) -> Calls {
+    let target = TaskReference::parse(label);
+    let mut calls = Calls::new();
+    for dependency in dependencies(text) {
+        let (upstream, entry) = dependency
+            .entry
+            .strip_prefix('^')
+            .map_or((false, dependency.entry.as_str()), |entry| (true, entry));
+        let reference = TaskReference::parse(entry);
+        if reference.task != target.task {
+            continue;
+        }
+
+        let callers = match (upstream, target.package) {
+            (true, Some(package)) => {
+                let caller = TaskReference::parse(&dependency.key);
+                dependent_packages(packages, package)
+                    .filter(|dependent| {
+                        caller.package.map_or_else(
+                            || dependent.scripts.contains_key(caller.task),
+                            |name| name == dependent.name,
+                        )
+                    })
+                    .map(|dependent| format!("{}#{}", dependent.name, caller.task))
+                    .collect()
+            }
+            (false, Some(package)) if reference.package.is_some_and(|name| name != package) => {
+                Vec::new()
+            }
+            (false, None) if reference.package.is_some() => Vec::new(),
+            _ => vec![dependency.key.clone()],
+        };
+        for caller in callers {
+            calls
+                .entry(caller)
+                .or_default()
+                .push(dependency.range.clone());
+        }
+    }
+    calls
+}
+
+/// Every `dependsOn` string entry of the `tasks` / `pipeline` definitions
+fn dependencies(text: &str) -> Vec<Dependency> {
+    let Ok(parse) = parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()) else {
+        return Vec::new();
+    };
+    let Some(config) = parse.value.as_ref().and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut dependencies = Vec::new();
+
+    let tasks = ["tasks", "pipeline"]
+        .iter()
+        .filter_map(|group| config.get_object(group))
+        .flat_map(|tasks| &tasks.properties);
+    for task in tasks {
+        let key = task.name.as_str().to_string();
+        let entries = task
+            .value
+            .as_object()
+            .and_then(|fields| fields.get_array("dependsOn"))
+            .map(|array| array.elements.as_slice())
+            .unwrap_or_default();
+        for string in entries.iter().filter_map(Value::as_string_lit) {
+            dependencies.push(Dependency {
+                key: key.clone(),
+                entry: string.value.to_string(),
+                range: collapse_string_range(string.range),
+            });
+        }
+    }
+    dependencies
+}
+
+/// Workspace packages `name` depends on
+fn dependency_packages<'a>(
+    packages: &'a [Package],
+    name: &'a str,
+) -> impl Iterator<Item = &'a Package> {
+    let dependencies = packages
+        .iter()
+        .find(|package| package.name == name)
+        .map(|package| package.dependencies.as_slice())
+        .unwrap_or_default();
+    packages
+        .iter()
+        .filter(move |package| dependencies.contains(&package.name))
+}
+
+/// Workspace packages depending on `name`
+fn dependent_packages<'a>(
+    packages: &'a [Package],
+    name: &'a str,
+) -> impl Iterator<Item = &'a Package> {
+    packages.iter().filter(move |package| {
+        package
+            .dependencies
+            .iter()
+            .any(|dependency| dependency == name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_core::test_util::package;
+
+    use super::*;
+
+    const TURBO_JSON: &str = r#"{
+  "tasks": {
+    "build": { "dependsOn": ["^build", "codegen"] },
+    "codegen": {},
+    "test": { "dependsOn": ["build", "web#lint"] },
+    "web#lint": {}
+  }
+}"#;
+
+    #[test]
+    fn outgoing_expands_upstream_dependencies() {
+        let packages = [
+            package("web")
+                .scripts(&["build"])
+                .dependencies(&["ui", "react"])
+                .build(),
+            package("ui").scripts(&["build"]).build(),
+        ];
+        let labels = |label: &str| -> Vec<String> {
+            outgoing(TURBO_JSON, label, &packages).into_keys().collect()
+        };
+        assert_eq!(labels("build"), vec!["^build", "codegen"]);
+        assert_eq!(labels("web#build"), vec!["ui#build", "codegen"]);
+        assert_eq!(labels("test"), vec!["build", "web#lint"]);
+    }
+
+    #[test]
+    fn incoming_includes_dependent_packages() {
+        let packages = [
+            package("web")
+                .scripts(&["build", "test"])
+                .dependencies(&["ui"])
+                .build(),
+            package("ui").scripts(&["build"]).build(),
+        ];
+        let labels = |label: &str| -> Vec<String> {
+            incoming(TURBO_JSON, label, &packages).into_keys().collect()
+        };
+        assert_eq!(labels("build"), vec!["build", "test"]);
+        assert_eq!(labels("ui#build"), vec!["web#build", "test"]);
+        assert_eq!(labels("web#lint"), vec!["test"]);
+        assert_eq!(labels("ui#lint"), Vec::<String>::new());
+
+        let range = definition_range(TURBO_JSON, "ui#codegen").unwrap();
+        assert_eq!(&TURBO_JSON[range], "codegen");
+    }
+}
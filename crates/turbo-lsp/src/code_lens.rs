@@ -0,0 +1,138 @@
+//! Task code lenses: run commands up front, run history and package pickers on `codeLens/resolve`
+
+use serde_json::json;
+use tower_lsp::lsp_types::{CodeLens, Command, Range, Url};
+
+/// Lens whose command needs workspace or run history data, filled in by `codeLens/resolve`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lazy {
+    /// Cache hit/miss and duration of the latest run
+    LastRun,
+    CriticalPath,
+    /// "Run in N packages…"
+    Packages,
+}
+
+impl Lazy {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::LastRun => "lastRun",
+            Self::CriticalPath => "criticalPath",
+            Self::Packages => "packages",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Self::LastRun, Self::CriticalPath, Self::Packages]
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+/// Unresolved lens for `task`, remembering the document it belongs to
+pub fn lazy(kind: Lazy, task: &str, uri: &Url, range: Range) -> CodeLens {
+    CodeLens {
+        range,
+        command: None,
+        data: Some(json!({ "kind": kind.name(), "task": task, "uri": uri })),
+    }
+}
+
+/// Kind, task and document of a lens created by [`lazy`]
+pub fn lazy_data(lens: &CodeLens) -> Option<(Lazy, String, Url)> {
+    let data = lens.data.as_ref()?;
+    Some((
+        Lazy::from_name(data.get("kind")?.as_str()?)?,
+        data.get("task")?.as_str()?.to_string(),
+        Url::parse(data.get("uri")?.as_str()?).ok()?,
+    ))
+}
+
+/// "Run", "Dry run" and "Show graph" for a task; `filter` limits them to one package
+pub fn run_commands(task: &str, uri: &Url, filter: Option<&str>) -> Vec<Command> {
+    let arguments = |dry_run: bool| {
+        Some(vec![
+            json!(task),
+            json!({ "uri": uri, "filter": filter, "dryRun": dry_run }),
+        ])
+    };
+    vec![
+        Command {
+            title: format!("Run {task}"),
+            command: "turbo.run".to_string(),
+            arguments: arguments(false),
+        },
+        Command {
+            title: "Dry run".to_string(),
+            command: "turbo.run".to_string(),
+            arguments: arguments(true),
+        },
+        Command {
+            title: "Show graph".to_string(),
+            command: "turbo.graph".to_string(),
+            arguments: arguments(false),
+        },
+    ]
+}
+
+/// "Run in N packages…", opening a picker of the packages defining `task`
+pub fn packages_command(task: &str, uri: &Url, count: usize) -> Command {
+    let title = match count {
+        0 => format!("No package defines `{task}`"),
+        1 => "Run in 1 package…".to_string(),
+        count => format!("Run in {count} packages…"),
+    };
+    Command {
+        title,
+        command: if count == 0 {
+            String::new()
+        } else {
+            "turbo.runInPackage".to_string()
+        },
+        arguments: Some(vec![json!(task), json!({ "uri": uri })]),
+    }
+}
+
+/// Title-only lens command for information without an action
+pub const fn label(title: String) -> Command {
+    Command {
+        title,
+        command: String::new(),
+        arguments: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+
+    use super::*;
+
+    #[test]
+    fn lazy_lenses_round_trip_their_data() {
+        let uri = Url::parse("file:///repo/turbo.json").unwrap();
+        let range = Range::new(Position::new(2, 4), Position::new(2, 11));
+        let lens = lazy(Lazy::Packages, "build", &uri, range);
+        assert!(lens.command.is_none());
+        assert_eq!(
+            lazy_data(&lens),
+            Some((Lazy::Packages, "build".to_string(), uri.clone()))
+        );
+        assert_eq!(
+            packages_command("build", &uri, 3).title,
+            "Run in 3 packages…"
+        );
+        assert_eq!(packages_command("build", &uri, 0).command, "");
+    }
+
+    #[test]
+    fn run_commands_pass_filter_and_dry_run() {
+        let uri = Url::parse("file:///repo/apps/web/turbo.json").unwrap();
+        let commands = run_commands("build", &uri, Some("web"));
+        let titles: Vec<&str> = commands.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Run build", "Dry run", "Show graph"]);
+        let options = &commands[1].arguments.as_ref().unwrap()[1];
+        assert_eq!(options["filter"], "web");
+        assert_eq!(options["dryRun"], true);
+    }
+}
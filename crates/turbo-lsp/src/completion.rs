@@ -0,0 +1,916 @@
+//! Position-aware completion for turbo.json keys and values
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use indexmap::{IndexMap, IndexSet};
+use jsonc_parser::{
+    CollectOptions, ParseOptions,
+    ast::{Object, ObjectPropName, Value},
+    common::Ranged,
+    parse_to_ast,
+};
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Documentation, MarkupContent,
+    MarkupKind, TextEdit,
+};
+use turbo_core::{
+    Package,
+    env::EnvSource,
+    glob::{TURBO_DEFAULT, TURBO_ROOT},
+};
+
+use crate::{
+    ROOT_PACKAGE_NAME, WorkspaceState, byte_range_to_lsp_range, collapse_string_range,
+    deprecations, enum_values, globs::GLOBAL_GLOB_KEYS, markdown_card, schema,
+    task_field_hover_meta, task_field_summary, top_level_hover, top_level_hover_meta,
+};
+
+/// Keys whose values are booleans
+const BOOLEAN_KEYS: &[&str] = &[
+    "cache",
+    "persistent",
+    "interactive",
+    "daemon",
+    "dangerouslyDisablePackageManagerCheck",
+    "noUpdateNotifier",
+];
+
+/// Environment variable arrays inside a task definition
+const TASK_ENV_KEYS: &[&str] = &["env", "passThroughEnv"];
+
+/// Environment variable arrays at the root of turbo.json
+const GLOBAL_ENV_KEYS: &[&str] = &["globalEnv", "globalPassThroughEnv"];
+
+/// Documentation lookup for a key
+type Docs = fn(&str) -> Option<String>;
+
+/// What the cursor is positioned on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Slot {
+    /// A property name of the object at `path`
+    Key,
+    /// The value of `key` in the object at `path`
+    Value(String),
+    /// An entry of the `key` array in the object at `path`
+    ArrayEntry(String),
+}
+
+/// Cursor position within the JSON structure of a turbo.json
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorContext {
+    pub slot: Slot,
+    /// Keys leading to the object the cursor is in; empty at the root
+    pub path: Vec<String>,
+    /// Keys already present in that object, except the one being typed
+    pub existing_keys: Vec<String>,
+    /// Byte range of the partially typed token, replaced by completions
+    pub token: Range<usize>,
+    /// Whether the token is inside quotes
+    pub quoted: bool,
+}
+
+impl CursorContext {
+    /// Text typed so far in the current token
+    pub fn prefix<'a>(&self, text: &'a str, offset: usize) -> &'a str {
+        text.get(self.token.start..offset.clamp(self.token.start, self.token.end))
+            .unwrap_or_default()
+    }
+
+    /// Whether the cursor is in a task definition (`tasks.<name>`)
+    pub fn in_task(&self) -> bool {
+        matches!(self.path.as_slice(), [group, _] if group == "tasks" || group == "pipeline")
+    }
+}
+
+#[derive(Debug)]
+struct Frame {
+    is_object: bool,
+    /// Key this container is the value of
+    parent_key: Option<String>,
+    key: Option<String>,
+    after_colon: bool,
+    keys: Vec<String>,
+}
+
+/// Locate the cursor in a possibly incomplete document
+///
+/// Complete documents are located through the jsonc-parser AST. While a key
+/// or value is half typed the text usually does not parse, and [`scan`] takes
+/// over.
+pub fn cursor_context(text: &str, offset: usize) -> Option<CursorContext> {
+    match parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()) {
+        Ok(parse) => object_context(text, parse.value.as_ref()?.as_object()?, offset, &[]),
+        Err(_) => scan(text, offset),
+    }
+}
+
+/// Context within `object`, reached from the root through `path`
+fn object_context(
+    text: &str,
+    object: &Object,
+    offset: usize,
+    path: &[String],
+) -> Option<CursorContext> {
+    if !(object.range.start < offset && offset < object.range.end) {
+        return None;
+    }
+    let keys = |skip: Option<usize>| -> Vec<String> {
+        object
+            .properties
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| Some(*index) != skip)
+            .map(|(_, property)| property.name.as_str().to_string())
+            .collect()
+    };
+    let context = |slot, (token, quoted): (Range<usize>, bool), existing_keys| CursorContext {
+        slot,
+        path: path.to_vec(),
+        existing_keys,
+        token,
+        quoted,
+    };
+    let empty = (offset..offset, false);
+
+    // the property the cursor is on or after
+    let Some(index) = object
+        .properties
+        .iter()
+        .rposition(|property| property.name.start() < offset)
+    else {
+        return Some(context(Slot::Key, empty, keys(None)));
+    };
+    let property = &object.properties[index];
+    let key = property.name.as_str().to_string();
+    let quoted_name = matches!(property.name, ObjectPropName::String(_));
+    if let Some(token) = token_at(&property.name, quoted_name, offset) {
+        return Some(context(Slot::Key, token, keys(Some(index))));
+    }
+    if !text[property.name.end()..offset].contains(':') {
+        return Some(context(Slot::Key, empty, keys(None)));
+    }
+
+    let value = &property.value;
+    if offset <= value.start() {
+        return Some(context(Slot::Value(key), empty, keys(None)));
+    }
+    if let Some(token) = value_token(value, offset) {
+        return Some(context(Slot::Value(key), token, keys(None)));
+    }
+    if offset >= value.end() {
+        let slot = if text[value.end()..offset].contains(',') {
+            Slot::Key
+        } else {
+            Slot::Value(key)
+        };
+        return Some(context(slot, empty, keys(None)));
+    }
+
+    let mut inner_path = path.to_vec();
+    inner_path.push(key.clone());
+    match value {
+        Value::Object(inner) => object_context(text, inner, offset, &inner_path),
+        Value::Array(array) => {
+            let element = array
+                .elements
+                .iter()
+                .find(|element| element.start() < offset && offset <= element.end());
+            match element {
+                Some(Value::Object(inner)) => object_context(text, inner, offset, &inner_path),
+                Some(Value::Array(_)) if offset < value.end() => None,
+                Some(element) => {
+                    let token = value_token(element, offset).unwrap_or(empty);
+                    Some(context(Slot::ArrayEntry(key), token, keys(None)))
+                }
+                None => Some(context(Slot::ArrayEntry(key), empty, keys(None))),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Token of a scalar value the cursor is in
+fn value_token(value: &Value, offset: usize) -> Option<(Range<usize>, bool)> {
+    match value {
+        Value::StringLit(string) => token_at(string, true, offset),
+        Value::NumberLit(_) | Value::BooleanLit(_) | Value::NullKeyword(_) => {
+            token_at(value, false, offset)
+        }
+        Value::Object(_) | Value::Array(_) => None,
+    }
+}
+
+/// Range the cursor is in, without quotes; a word also covers the offset right after it
+fn token_at(node: &impl Ranged, quoted: bool, offset: usize) -> Option<(Range<usize>, bool)> {
+    let range = node.range();
+    if quoted {
+        (range.start < offset && offset < range.end).then(|| (collapse_string_range(range), true))
+    } else {
+        (range.start < offset && offset <= range.end).then_some((range.start..range.end, false))
+    }
+}
+
+/// Locate the cursor by tokenizing text that does not parse
+///
+/// Scans the whole text (strings, comments, brackets) and keeps going past
+/// the cursor to collect the keys that follow it in the same object.
+fn scan(text: &str, offset: usize) -> Option<CursorContext> {
+    let bytes = text.as_bytes();
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut found: Option<(CursorContext, usize)> = None;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if found.is_none() && index >= offset {
+            found = snapshot(&frames, offset..offset, false);
+        }
+        match bytes[index] {
+            b'"' => {
+                let end = string_end(bytes, index);
+                let content = index + 1..end.min(bytes.len());
+                let covers = found.is_none() && offset > index && offset <= end;
+                if covers {
+                    found = snapshot(&frames, content.clone(), true);
+                }
+                if let Some(frame) = frames.last_mut()
+                    && frame.is_object
+                    && !frame.after_colon
+                {
+                    frame.key = Some(text[content].to_string());
+                    if !covers {
+                        frame.keys.push(frame.key.clone().unwrap_or_default());
+                    }
+                }
+                index = end + 1;
+                continue;
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'/') => {
+                index = text[index..]
+                    .find('\n')
+                    .map_or(bytes.len(), |end| index + end);
+                continue;
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                index = text[index + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| index + 2 + end + 2);
+                continue;
+            }
+            open @ (b'{' | b'[') => {
+                let parent_key = frames
+                    .last()
+                    .filter(|frame| frame.is_object && frame.after_colon)
+                    .and_then(|frame| frame.key.clone());
+                frames.push(Frame {
+                    is_object: open == b'{',
+                    parent_key,
+                    key: None,
+                    after_colon: false,
+                    keys: Vec::new(),
+                });
+            }
+            b'}' | b']' => {
+                let closed = frames.pop();
+                if let Some((context, depth)) = &mut found
+                    && frames.len() + 1 == *depth
+                {
+                    context.existing_keys = closed.map(|frame| frame.keys).unwrap_or_default();
+                    return found.map(|(context, _)| context);
+                }
+            }
+            b':' => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.after_colon = true;
+                }
+            }
+            b',' => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.after_colon = false;
+                    frame.key = None;
+                }
+            }
+            byte if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'$' => {
+                // unquoted word: true, false, null, a number or a half typed key
+                let end = bytes[index..]
+                    .iter()
+                    .position(|byte| !(byte.is_ascii_alphanumeric() || b"-_$.".contains(byte)))
+                    .map_or(bytes.len(), |len| index + len);
+                if found.is_none() && offset > index && offset <= end {
+                    found = snapshot(&frames, index..end, false);
+                }
+                index = end;
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    if found.is_none() && offset >= bytes.len() {
+        found = snapshot(&frames, offset..offset, false);
+    }
+    // unterminated object: every key seen so far is all we know
+    let (mut context, depth) = found?;
+    if let Some(frame) = frames.get(depth - 1) {
+        context.existing_keys.clone_from(&frame.keys);
+    }
+    Some(context)
+}
+
+/// Index of the closing quote of the string starting at `start`
+const fn string_end(bytes: &[u8], start: usize) -> usize {
+    let mut index = start + 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'"' | b'\n' => return index,
+            _ => index += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn snapshot(frames: &[Frame], token: Range<usize>, quoted: bool) -> Option<(CursorContext, usize)> {
+    let top = frames.last()?;
+    let path: Vec<String> = frames
+        .iter()
+        .skip(1)
+        .filter_map(|frame| frame.parent_key.clone())
+        .collect();
+
+    let (slot, path, depth) = if top.is_object {
+        let slot = match (&top.key, top.after_colon) {
+            (Some(key), true) => Slot::Value(key.clone()),
+            _ => Slot::Key,
+        };
+        (slot, path, frames.len())
+    } else {
+        // arrays are reported against the object holding them
+        let key = top.parent_key.clone()?;
+        let mut path = path;
+        path.pop();
+        (Slot::ArrayEntry(key), path, frames.len() - 1)
+    };
+
+    Some((
+        CursorContext {
+            slot,
+            path,
+            existing_keys: Vec::new(),
+            token,
+            quoted,
+        },
+        depth.max(1),
+    ))
+}
+
+/// Keys the schema allows in the object at the cursor, without removed ones
+fn allowed_keys(context: &CursorContext, package_config: bool) -> Vec<&'static str> {
+    let keys = if context.path.is_empty() {
+        schema::config_keys(package_config)
+    } else if context.in_task() {
+        schema::task_fields()
+    } else {
+        return Vec::new();
+    };
+    keys.into_iter()
+        .filter(|key| !deprecations::covers(key))
+        .collect()
+}
+
+/// Key completions for the root object or a task object of a root or package turbo.json
+pub fn key_items(text: &str, context: &CursorContext, package_config: bool) -> Vec<CompletionItem> {
+    let documentation: Docs = if context.path.is_empty() {
+        top_level_docs
+    } else {
+        task_field_docs
+    };
+
+    allowed_keys(context, package_config)
+        .iter()
+        .filter(|key| {
+            !context
+                .existing_keys
+                .iter()
+                .any(|existing| existing == *key)
+        })
+        .map(|key| {
+            let new_text = if context.quoted {
+                (*key).to_string()
+            } else {
+                format!("\"{key}\": ")
+            };
+            CompletionItem {
+                label: (*key).to_string(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                documentation: documentation(key).map(markdown),
+                text_edit: Some(edit(text, context, new_text)),
+                ..CompletionItem::default()
+            }
+        })
+        .collect()
+}
+
+/// Enum and boolean value completions
+pub fn value_items(
+    text: &str,
+    context: &CursorContext,
+    key: &str,
+    package_config: bool,
+) -> Vec<CompletionItem> {
+    if !allowed_keys(context, package_config).contains(&key) {
+        return Vec::new();
+    }
+
+    if let Some(values) = enum_values(key) {
+        return values
+            .iter()
+            .map(|(value, description)| CompletionItem {
+                label: (*value).to_string(),
+                kind: Some(CompletionItemKind::ENUM_MEMBER),
+                documentation: Some(markdown((*description).to_string())),
+                text_edit: Some(edit(
+                    text,
+                    context,
+                    if context.quoted {
+                        (*value).to_string()
+                    } else {
+                        format!("\"{value}\"")
+                    },
+                )),
+                ..CompletionItem::default()
+            })
+            .collect();
+    }
+
+    if BOOLEAN_KEYS.contains(&key) && !context.quoted {
+        return ["true", "false"]
+            .iter()
+            .map(|value| CompletionItem {
+                label: (*value).to_string(),
+                kind: Some(CompletionItemKind::VALUE),
+                text_edit: Some(edit(text, context, (*value).to_string())),
+                ..CompletionItem::default()
+            })
+            .collect();
+    }
+
+    Vec::new()
+}
+
+/// `dependsOn` entries for what has been typed so far
+///
+/// After `^` only tasks of dependency packages are offered: those of the
+/// package owning a package-level turbo.json, or of every package some
+/// workspace package depends on for the root config. After `pkg#` (or `//#`)
+/// only that package's scripts are offered.
+pub fn depends_on_items(
+    text: &str,
+    offset: usize,
+    context: &CursorContext,
+    workspace: &WorkspaceState,
+    root: &Path,
+    package: Option<&Package>,
+) -> Vec<CompletionItem> {
+    let prefix = context.prefix(text, offset);
+
+    let labels = if prefix.starts_with('^') {
+        let dependencies: IndexSet<&str> = package
+            .map_or(workspace.packages.as_slice(), std::slice::from_ref)
+            .iter()
+            .flat_map(|package| package.dependencies.iter().map(String::as_str))
+            .collect();
+        script_labels(
+            workspace
+                .packages
+                .iter()
+                .filter(|package| dependencies.contains(package.name.as_str())),
+            "^",
+        )
+    } else if let Some((package_name, _)) = prefix.split_once('#') {
+        script_labels(
+            workspace.packages.iter().filter(|package| {
+                if package_name == ROOT_PACKAGE_NAME {
+                    package.path == root
+                } else {
+                    package.name == package_name && package.path != root
+                }
+            }),
+            &format!("{package_name}#"),
+        )
+    } else {
+        workspace
+            .task_packages
+            .iter()
+            .flat_map(|(task, packages)| {
+                std::iter::once(task.clone()).chain(
+                    packages
+                        .iter()
+                        .map(move |package| format!("{package}#{task}")),
+                )
+            })
+            .collect()
+    };
+
+    labels
+        .into_iter()
+        .map(|label| {
+            let new_text = if context.quoted {
+                label.clone()
+            } else {
+                format!("\"{label}\"")
+            };
+            CompletionItem {
+                filter_text: Some(label.clone()),
+                label,
+                kind: Some(CompletionItemKind::FIELD),
+                text_edit: Some(edit(text, context, new_text)),
+                ..CompletionItem::default()
+            }
+        })
+        .collect()
+}
+
+fn script_labels<'a>(
+    packages: impl Iterator<Item = &'a Package>,
+    label_prefix: &str,
+) -> IndexSet<String> {
+    packages
+        .flat_map(|package| package.scripts.keys())
+        .map(|task| format!("{label_prefix}{task}"))
+        .collect()
+}
+
+/// Whether the cursor is in an `env`-like array
+pub fn is_env_array(context: &CursorContext, key: &str) -> bool {
+    (context.in_task() && TASK_ENV_KEYS.contains(&key))
+        || (context.path.is_empty() && GLOBAL_ENV_KEYS.contains(&key))
+}
+
+/// An environment variable name suggestion
+#[derive(Debug, Clone)]
+pub struct EnvSuggestion {
+    pub source: EnvSource,
+    /// Package the name was found in
+    pub package: String,
+    /// Lower ranks sort first: the packages the config applies to, then the root
+    pub rank: u8,
+}
+
+/// Environment variable name completions, ranked by package relevance
+pub fn env_items(
+    text: &str,
+    context: &CursorContext,
+    names: &IndexMap<String, EnvSuggestion>,
+) -> Vec<CompletionItem> {
+    names
+        .iter()
+        .map(|(name, suggestion)| {
+            let source = match suggestion.source {
+                EnvSource::DotEnv => ".env file",
+                EnvSource::SourceCode => "used in source",
+                EnvSource::Framework => "framework prefix",
+            };
+            let new_text = if context.quoted {
+                name.clone()
+            } else {
+                format!("\"{name}\"")
+            };
+            CompletionItem {
+                label: name.clone(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: Some(format!("{} ({source})", suggestion.package)),
+                sort_text: Some(format!("{}{name}", suggestion.rank)),
+                text_edit: Some(edit(text, context, new_text)),
+                ..CompletionItem::default()
+            }
+        })
+        .collect()
+}
+
+/// File and directory completions for glob arrays, plus `$TURBO_DEFAULT$`, `$TURBO_ROOT$/` and `!`
+///
+/// Paths are listed relative to every base directory (the packages a task
+/// runs in, or the repo root), or to the root after `$TURBO_ROOT$/`.
+pub fn path_items(
+    text: &str,
+    offset: usize,
+    context: &CursorContext,
+    key: &str,
+    bases: &[PathBuf],
+    root: &Path,
+) -> Vec<CompletionItem> {
+    let prefix = context.prefix(text, offset);
+    let (negation, rest) = prefix
+        .strip_prefix('!')
+        .map_or(("", prefix), |rest| ("!", rest));
+    let (turbo_root, rest, dirs) = rest.strip_prefix(TURBO_ROOT).map_or_else(
+        || ("", rest, bases.to_vec()),
+        |rest| {
+            (
+                "$TURBO_ROOT$/",
+                rest.trim_start_matches('/'),
+                vec![root.to_path_buf()],
+            )
+        },
+    );
+    let dir_part = &rest[..rest.rfind('/').map_or(0, |index| index + 1)];
+
+    let mut entries: IndexMap<String, bool> = IndexMap::new();
+    for dir in dirs {
+        let Ok(read_dir) = std::fs::read_dir(dir.join(dir_part)) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name != "node_modules" && name != ".git" {
+                let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                entries.entry(name).or_insert(is_dir);
+            }
+        }
+    }
+    entries.sort_keys();
+
+    let mut items: Vec<_> = entries
+        .into_iter()
+        .map(|(name, is_dir)| {
+            let label = format!(
+                "{negation}{turbo_root}{dir_part}{name}{}",
+                if is_dir { "/" } else { "" }
+            );
+            let kind = if is_dir {
+                CompletionItemKind::FOLDER
+            } else {
+                CompletionItemKind::FILE
+            };
+            token_item(text, context, label, kind, None)
+        })
+        .collect();
+
+    if turbo_root.is_empty() && !rest.contains('/') {
+        if key == "inputs" && negation.is_empty() {
+            items.push(token_item(
+                text,
+                context,
+                TURBO_DEFAULT.to_string(),
+                CompletionItemKind::KEYWORD,
+                Some("Turbo's default inputs"),
+            ));
+        }
+        if !GLOBAL_GLOB_KEYS.contains(&key) {
+            items.push(token_item(
+                text,
+                context,
+                format!("{negation}{TURBO_ROOT}/"),
+                CompletionItemKind::KEYWORD,
+                Some("Relative to the repo root"),
+            ));
+        }
+        if negation.is_empty() {
+            items.push(token_item(
+                text,
+                context,
+                "!".to_string(),
+                CompletionItemKind::OPERATOR,
+                Some("Exclude matching files"),
+            ));
+        }
+    }
+    items
+}
+
+/// An item replacing the typed token with `label`
+fn token_item(
+    text: &str,
+    context: &CursorContext,
+    label: String,
+    kind: CompletionItemKind,
+    detail: Option<&str>,
+) -> CompletionItem {
+    let new_text = if context.quoted {
+        label.clone()
+    } else {
+        format!("\"{label}\"")
+    };
+    CompletionItem {
+        filter_text: Some(label.clone()),
+        label,
+        kind: Some(kind),
+        detail: detail.map(str::to_string),
+        text_edit: Some(edit(text, context, new_text)),
+        ..CompletionItem::default()
+    }
+}
+
+/// Replace the partially typed token
+pub fn edit(text: &str, context: &CursorContext, new_text: String) -> CompletionTextEdit {
+    CompletionTextEdit::Edit(TextEdit {
+        range: byte_range_to_lsp_range(text, context.token.clone()),
+        new_text,
+    })
+}
+
+const fn markdown(value: String) -> Documentation {
+    Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+    })
+}
+
+fn top_level_docs(name: &str) -> Option<String> {
+    top_level_hover_meta(name).map(|_| top_level_hover(name))
+}
+
+fn task_field_docs(name: &str) -> Option<String> {
+    task_field_hover_meta(name).map(|meta| {
+        let summary = meta
+            .summary_override
+            .or_else(|| task_field_summary(name))
+            .unwrap_or("Turbo task field.");
+        markdown_card(name, summary, meta.context, meta.example, meta.docs_url)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_core::test_util::{TempDir, package};
+
+    use super::*;
+
+    fn context_at(text: &str) -> CursorContext {
+        let offset = text.find('|').unwrap();
+        let text = text.replace('|', "");
+        cursor_context(&text, offset).unwrap()
+    }
+
+    #[test]
+    fn parsed_documents_agree_with_the_scanner() {
+        for text in [
+            r#"{"tasks": {"build": {"dependsOn": ["^build", "lint"], "outputs": [], "cache": false}}, "ui": "tui"}"#,
+            "{\n  \"tasks\": {\n    \"build\": { \"inputs\": [\"src/**\"], \"env\": [ ] },\n    \"lint\": {}\n  },\n  // ci\n  \"globalEnv\": [\"CI\"],\n  \"daemon\": true,\n}",
+        ] {
+            assert!(
+                parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()).is_ok()
+            );
+            for offset in 1..text.len() {
+                assert_eq!(
+                    cursor_context(text, offset),
+                    scan(text, offset),
+                    "offset {offset}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_context_finds_slots() {
+        let root = context_at(r#"{ "tasks": {}, "u|" }"#);
+        assert_eq!(root.slot, Slot::Key);
+        assert_eq!(root.path, Vec::<String>::new());
+        assert!(root.quoted);
+        assert_eq!(root.existing_keys, vec!["tasks"]);
+
+        let field = context_at(r#"{ "tasks": { "build": { "cache": true, | } } }"#);
+        assert_eq!(field.slot, Slot::Key);
+        assert_eq!(field.path, vec!["tasks", "build"]);
+        assert_eq!(field.existing_keys, vec!["cache"]);
+
+        let value = context_at(r#"{ "tasks": { "test": { "outputLogs": "n|" "#);
+        assert_eq!(value.slot, Slot::Value("outputLogs".to_string()));
+        assert!(value.in_task());
+
+        let entry = context_at(
+            r#"{ "tasks": { "build": { // deps
+              "dependsOn": ["^b|"] } } }"#,
+        );
+        assert_eq!(entry.slot, Slot::ArrayEntry("dependsOn".to_string()));
+        assert_eq!(entry.path, vec!["tasks", "build"]);
+    }
+
+    #[test]
+    fn items_skip_existing_keys_and_offer_enums() {
+        let text = r#"{ "tasks": { "build": { "cache": false, "|" } } }"#;
+        let context = context_at(text);
+        let labels: Vec<_> = key_items(text, &context, false)
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert!(labels.contains(&"outputs".to_string()));
+        assert!(!labels.contains(&"cache".to_string()));
+        assert!(!labels.contains(&"globalEnv".to_string()));
+
+        let text = r#"{ "ui": | }"#;
+        let context = context_at(text);
+        let labels: Vec<_> = value_items(text, &context, "ui", false)
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert_eq!(labels, vec!["tui", "stream"]);
+        assert_eq!(value_items(text, &context, "ui", true), Vec::new());
+    }
+
+    #[test]
+    fn top_level_keys_depend_on_the_config_kind() {
+        let text = r#"{ "|" }"#;
+        let context = context_at(text);
+        let labels = |package_config: bool| -> Vec<_> {
+            key_items(text, &context, package_config)
+                .into_iter()
+                .map(|item| item.label)
+                .collect()
+        };
+
+        let root = labels(false);
+        assert!(root.contains(&"globalEnv".to_string()));
+        assert!(root.contains(&"boundaries".to_string()));
+        assert!(!root.contains(&"extends".to_string()));
+        assert!(!root.contains(&"pipeline".to_string()));
+
+        let package = labels(true);
+        assert!(package.contains(&"extends".to_string()));
+        assert!(package.contains(&"tags".to_string()));
+        for key in ["globalEnv", "ui", "daemon", "cacheDir", "pipeline"] {
+            assert!(!package.contains(&key.to_string()), "{key}");
+        }
+    }
+
+    #[test]
+    fn path_items_list_relative_entries() {
+        let dir = TempDir::new("lsp-paths");
+        dir.write("src/index.ts", "");
+        std::fs::create_dir_all(dir.join("src/components")).unwrap();
+
+        let text = r#"{"tasks": {"build": {"inputs": ["!src/|"]}}}"#;
+        let offset = text.find('|').unwrap();
+        let text = text.replace('|', "");
+        let context = cursor_context(&text, offset).unwrap();
+        let labels: Vec<_> = path_items(
+            &text,
+            offset,
+            &context,
+            "inputs",
+            &[dir.to_path_buf()],
+            &dir,
+        )
+        .into_iter()
+        .map(|item| item.label)
+        .collect();
+        assert_eq!(labels, vec!["!src/components/", "!src/index.ts"]);
+
+        let text = r#"{"tasks": {"build": {"inputs": ["|"]}}}"#;
+        let offset = text.find('|').unwrap();
+        let text = text.replace('|', "");
+        let context = cursor_context(&text, offset).unwrap();
+        let labels: Vec<_> = path_items(&text, offset, &context, "inputs", &[], &dir)
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert_eq!(labels, vec!["$TURBO_DEFAULT$", "$TURBO_ROOT$/", "!"]);
+    }
+
+    #[test]
+    fn depends_on_items_follow_microsyntax() {
+        let packages = vec![
+            package("repo").path("/repo").scripts(&["format"]).build(),
+            package("web")
+                .path("/repo/apps/web")
+                .scripts(&["build", "dev"])
+                .dependencies(&["ui"])
+                .build(),
+            package("ui").scripts(&["build", "storybook"]).build(),
+        ];
+        let workspace = WorkspaceState {
+            packages,
+            task_packages: IndexMap::new(),
+            package_names: IndexSet::new(),
+            root_config: None,
+            turbo_version: None,
+        };
+        let labels = |text: &str| {
+            let offset = text.find('|').unwrap();
+            let text = text.replace('|', "");
+            let context = cursor_context(&text, offset).unwrap();
+            depends_on_items(
+                &text,
+                offset,
+                &context,
+                &workspace,
+                Path::new("/repo"),
+                None,
+            )
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+        };
+
+        let deps = r#"{"tasks": {"build": {"dependsOn": ["^|"]}}}"#;
+        assert_eq!(labels(deps), vec!["^build", "^storybook"]);
+        let web = r#"{"tasks": {"build": {"dependsOn": ["web#|"]}}}"#;
+        assert_eq!(labels(web), vec!["web#build", "web#dev"]);
+        let root = r#"{"tasks": {"build": {"dependsOn": ["//#f|"]}}}"#;
+        assert_eq!(labels(root), vec!["//#format"]);
+    }
+}
@@ -0,0 +1,291 @@
+//! Removed and not-yet-available turbo.json keys for the repo's turbo version
+
+use jsonc_parser::ast::{Object, ObjectProp};
+use serde_json::json;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, TextEdit,
+};
+use turbo_core::{TurboVersion, VersionSource};
+
+use crate::{byte_range_to_lsp_range, lint::removal_range, name_range};
+
+/// Object a rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Place {
+    Root,
+    Task,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Fix {
+    Rename(&'static str),
+    Remove,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    /// Rejected from `since` on
+    Removed {
+        since: TurboVersion,
+        fix: Fix,
+        hint: &'static str,
+    },
+    /// Not understood before `since`
+    Added { since: TurboVersion },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    key: &'static str,
+    place: Place,
+    kind: Kind,
+}
+
+const V2: TurboVersion = TurboVersion::new(2, 0, 0);
+
+const fn removed(key: &'static str, place: Place, fix: Fix, hint: &'static str) -> Rule {
+    Rule {
+        key,
+        place,
+        kind: Kind::Removed {
+            since: V2,
+            fix,
+            hint,
+        },
+    }
+}
+
+const fn added(key: &'static str, place: Place, since: TurboVersion) -> Rule {
+    Rule {
+        key,
+        place,
+        kind: Kind::Added { since },
+    }
+}
+
+/// Versions follow the turbo release notes
+const RULES: &[Rule] = &[
+    removed(
+        "pipeline",
+        Place::Root,
+        Fix::Rename("tasks"),
+        "Turbo 2.0 renamed `pipeline` to `tasks`.",
+    ),
+    removed(
+        "experimentalUI",
+        Place::Root,
+        Fix::Remove,
+        "Use `\"ui\": \"tui\"` instead.",
+    ),
+    removed(
+        "baseBranch",
+        Place::Root,
+        Fix::Remove,
+        "Set `TURBO_SCM_BASE` or use `--affected` instead.",
+    ),
+    removed(
+        "globalDotEnv",
+        Place::Root,
+        Fix::Remove,
+        "List `.env` files in `globalDependencies` instead.",
+    ),
+    removed(
+        "experimentalSpaces",
+        Place::Root,
+        Fix::Remove,
+        "Spaces are no longer supported.",
+    ),
+    removed(
+        "outputMode",
+        Place::Task,
+        Fix::Rename("outputLogs"),
+        "Turbo 2.0 renamed `outputMode` to `outputLogs`.",
+    ),
+    removed(
+        "dotEnv",
+        Place::Task,
+        Fix::Remove,
+        "List `.env` files in `inputs` instead.",
+    ),
+    added("tasks", Place::Root, V2),
+    added("ui", Place::Root, V2),
+    added("envMode", Place::Root, V2),
+    added("outputLogs", Place::Task, V2),
+    added("interruptible", Place::Task, TurboVersion::new(2, 2, 0)),
+    added("boundaries", Place::Root, TurboVersion::new(2, 4, 0)),
+    added("with", Place::Task, TurboVersion::new(2, 5, 0)),
+];
+
+/// Whether `key` is a removed key reported here rather than as unknown by the schema
+pub fn covers(key: &str) -> bool {
+    RULES
+        .iter()
+        .any(|rule| rule.key == key && matches!(rule.kind, Kind::Removed { .. }))
+}
+
+/// Diagnostics for keys the detected version rejects; an unknown version is treated as current
+pub fn diagnostics(
+    text: &str,
+    config: &Object<'_>,
+    version: Option<(TurboVersion, VersionSource)>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check(text, config, Place::Root, version, &mut diagnostics);
+    for task_group_name in ["tasks", "pipeline"] {
+        let Some(tasks) = config.get_object(task_group_name) else {
+            continue;
+        };
+        for task in &tasks.properties {
+            if let Some(fields) = task.value.as_object() {
+                check(text, fields, Place::Task, version, &mut diagnostics);
+            }
+        }
+    }
+    diagnostics
+}
+
+fn check(
+    text: &str,
+    object: &Object<'_>,
+    place: Place,
+    version: Option<(TurboVersion, VersionSource)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (index, property) in object.properties.iter().enumerate() {
+        let key = property.name.as_str();
+        let Some(rule) = RULES
+            .iter()
+            .find(|rule| rule.key == key && rule.place == place)
+        else {
+            continue;
+        };
+
+        match rule.kind {
+            Kind::Removed { since, fix, hint } => {
+                if version.is_some_and(|(version, _)| version < since) {
+                    continue;
+                }
+                let fix = match fix {
+                    // renaming would collide with an existing key
+                    Fix::Rename(new_name) if object.get(new_name).is_some() => None,
+                    Fix::Rename(new_name) => Some((
+                        format!("Rename to `{new_name}`"),
+                        name_range(&property.name),
+                        new_name.to_string(),
+                    )),
+                    Fix::Remove => Some((
+                        format!("Remove `{key}`"),
+                        removal_range(&object.properties, index),
+                        String::new(),
+                    )),
+                };
+                diagnostics.push(Diagnostic {
+                    message: format!("`{key}` was removed in turbo {since}. {hint}"),
+                    tags: Some(vec![DiagnosticTag::DEPRECATED]),
+                    data: fix.map(|(title, range, new_text)| {
+                        let edit = TextEdit {
+                            range: byte_range_to_lsp_range(text, range),
+                            new_text,
+                        };
+                        json!({ "title": title, "edits": [edit] })
+                    }),
+                    ..diagnostic(text, property, "turbo:removed-key")
+                });
+            }
+            Kind::Added { since } => {
+                let Some((version, source)) = version.filter(|(version, _)| *version < since)
+                else {
+                    continue;
+                };
+                diagnostics.push(Diagnostic {
+                    message: format!(
+                        "`{key}` requires turbo {since}, but this repo uses {version} ({}).",
+                        source_label(source)
+                    ),
+                    ..diagnostic(text, property, "turbo:requires-version")
+                });
+            }
+        }
+    }
+}
+
+fn diagnostic(text: &str, property: &ObjectProp<'_>, code: &str) -> Diagnostic {
+    Diagnostic {
+        range: byte_range_to_lsp_range(text, name_range(&property.name)),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(code.to_string())),
+        ..Diagnostic::default()
+    }
+}
+
+const fn source_label(source: VersionSource) -> &'static str {
+    match source {
+        VersionSource::Installed => "installed in node_modules",
+        VersionSource::PackageJson => "from the root package.json",
+        VersionSource::Binary => "from `turbo --version`",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonc_parser::{CollectOptions, ParseOptions, parse_to_ast};
+
+    use super::*;
+
+    /// Code, the key named in the message and whether a fix is attached
+    fn messages(text: &str, version: Option<TurboVersion>) -> Vec<(String, String, bool)> {
+        let parse =
+            parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()).unwrap();
+        let config = parse
+            .value
+            .as_ref()
+            .and_then(|value| value.as_object())
+            .unwrap();
+        diagnostics(
+            text,
+            config,
+            version.map(|version| (version, VersionSource::PackageJson)),
+        )
+        .into_iter()
+        .map(|diagnostic| {
+            let key = diagnostic.message.split('`').nth(1).unwrap_or_default();
+            let code = match diagnostic.code {
+                Some(NumberOrString::String(code)) => code,
+                _ => String::new(),
+            };
+            (code, key.to_string(), diagnostic.data.is_some())
+        })
+        .collect()
+    }
+
+    #[test]
+    fn removed_keys_depend_on_version() {
+        let text = r#"{"pipeline": {"build": {"outputMode": "full"}}, "baseBranch": "main"}"#;
+        let expected: Vec<_> = ["pipeline", "baseBranch", "outputMode"]
+            .into_iter()
+            .map(|key| ("turbo:removed-key".to_string(), key.to_string(), true))
+            .collect();
+        assert_eq!(messages(text, Some(TurboVersion::new(2, 1, 0))), expected);
+        assert_eq!(messages(text, None), expected);
+        assert_eq!(
+            messages(text, Some(TurboVersion::new(1, 13, 0))),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn new_keys_require_minimum_version() {
+        let text = r#"{"tasks": {"dev": {"with": ["api#dev"]}}}"#;
+        assert_eq!(
+            messages(text, Some(TurboVersion::new(2, 4, 0))),
+            vec![(
+                "turbo:requires-version".to_string(),
+                "with".to_string(),
+                false
+            )]
+        );
+        assert_eq!(messages(text, None), Vec::new());
+        assert!(covers("outputMode"));
+        assert!(!covers("with"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Read-only `turbo-effective:` documents with the resolved task definitions of a package
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use serde_json::Value;
+use tower_lsp::lsp_types::Url;
+
+/// URI scheme of effective config documents, `turbo-effective:///packages/web?root=/repo`
+pub const SCHEME: &str = "turbo-effective";
+
+/// Marker that appends package array entries to the root ones instead of replacing them
+const EXTENDS_MARKER: &str = "$TURBO_EXTENDS$";
+
+/// Where a resolved value was defined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Root,
+    Package,
+    Extends,
+}
+
+impl Origin {
+    const fn comment(self) -> &'static str {
+        match self {
+            Self::Root => "root turbo.json",
+            Self::Package => "package turbo.json",
+            Self::Extends => "$TURBO_EXTENDS$ append",
+        }
+    }
+}
+
+/// A resolved task field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    Value(Value, Origin),
+    /// Array mixing root entries with entries appended by `$TURBO_EXTENDS$`
+    Extended(Vec<(Value, Origin)>),
+}
+
+/// Effective document URI for a package directory relative to the repo root
+///
+/// The root is kept in the `root` query, so packages at the same relative path in
+/// different monorepos get different documents.
+pub fn uri_for(repo_root: &Path, relative_dir: &str) -> Option<Url> {
+    let mut uri = Url::parse(&format!("{SCHEME}:///{}", relative_dir.trim_matches('/'))).ok()?;
+    uri.query_pairs_mut()
+        .append_pair("root", repo_root.to_str()?);
+    Some(uri)
+}
+
+/// Repo root an effective document URI was created for
+pub fn repo_root(uri: &Url) -> Option<PathBuf> {
+    uri.query_pairs()
+        .find(|(name, _)| name == "root")
+        .map(|(_, root)| PathBuf::from(root.as_ref()))
+        .filter(|root| uri.scheme() == SCHEME && root.is_absolute())
+}
+
+/// Package directory, relative to the repo root, of an effective document URI
+pub fn package_dir(uri: &Url) -> Option<String> {
+    (uri.scheme() == SCHEME).then(|| uri.path().trim_matches('/').to_string())
+}
+
+/// File name a snapshot of the document is written to for clients that cannot open
+/// `turbo-effective:` URIs
+///
+/// `%` and `/` are escaped, so `apps/web-ui` and `apps-web/ui` get different files.
+pub fn snapshot_file_name(uri: &Url) -> Option<String> {
+    let dir = package_dir(uri)?;
+    let name = if dir.is_empty() {
+        "root".to_string()
+    } else {
+        dir.replace('%', "%25").replace('/', "%2F")
+    };
+    Some(format!("{name}.jsonc"))
+}
+
+/// Merge root task definitions with a package config the way turbo does
+///
+/// A root `package#task` entry replaces the plain root task for that package;
+/// package config fields replace root fields, except arrays containing
+/// `$TURBO_EXTENDS$`, which are appended to the root array.
+pub fn resolve(
+    root: &Value,
+    package: Option<&Value>,
+    package_name: &str,
+) -> IndexMap<String, IndexMap<String, Field>> {
+    let mut tasks: IndexMap<String, IndexMap<String, Field>> = IndexMap::new();
+
+    let root_tasks = task_group(root);
+    for (name, _) in root_tasks.iter().filter(|(name, _)| !name.contains('#')) {
+        let definition = root_tasks
+            .get(&format!("{package_name}#{name}"))
+            .unwrap_or_else(|| &root_tasks[name]);
+        tasks.insert(name.clone(), fields(definition, Origin::Root));
+    }
+    for (name, definition) in &root_tasks {
+        if let Some(task) = name.strip_prefix(&format!("{package_name}#"))
+            && !tasks.contains_key(task)
+        {
+            tasks.insert(task.to_string(), fields(definition, Origin::Root));
+        }
+    }
+
+    for (name, definition) in package.map(task_group).unwrap_or_default() {
+        let task = tasks.entry(name).or_default();
+        let Some(definition) = definition.as_object() else {
+            continue;
+        };
+        for (field, value) in definition {
+            let resolved = match (value.as_array(), task.get(field)) {
+                (Some(entries), inherited)
+                    if entries.iter().any(|entry| entry == EXTENDS_MARKER) =>
+                {
+                    // nothing (or a non-array) to extend appends to an empty list
+                    let inherited = match inherited {
+                        Some(Field::Value(Value::Array(inherited), _)) => inherited.as_slice(),
+                        _ => &[],
+                    };
+                    Field::Extended(
+                        inherited
+                            .iter()
+                            .map(|entry| (entry.clone(), Origin::Root))
+                            .chain(
+                                entries
+                                    .iter()
+                                    .filter(|entry| *entry != EXTENDS_MARKER)
+                                    .map(|entry| (entry.clone(), Origin::Extends)),
+                            )
+                            .collect(),
+                    )
+                }
+                _ => Field::Value(value.clone(), Origin::Package),
+            };
+            task.insert(field.clone(), resolved);
+        }
+    }
+
+    tasks
+}
+
+fn task_group(config: &Value) -> IndexMap<String, Value> {
+    ["tasks", "pipeline"]
+        .iter()
+        .filter_map(|group| config.get(group)?.as_object())
+        .flat_map(|tasks| {
+            tasks
+                .iter()
+                .map(|(name, task)| (name.clone(), task.clone()))
+        })
+        .collect()
+}
+
+fn fields(definition: &Value, origin: Origin) -> IndexMap<String, Field> {
+    definition
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), Field::Value(value.clone(), origin)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// JSONC document with every value annotated with its origin
+pub fn render(package_name: &str, tasks: &IndexMap<String, IndexMap<String, Field>>) -> String {
+    let mut out = format!(
+        "// Effective task definitions for `{package_name}` (read-only)\n{{\n  \"tasks\": {{\n"
+    );
+    for (task_index, (name, fields)) in tasks.iter().enumerate() {
+        let _ = writeln!(out, "    {}: {{", json(&Value::String(name.clone())));
+        for (field_index, (field, resolved)) in fields.iter().enumerate() {
+            let comma = separator(field_index, fields.len());
+            let key = json(&Value::String(field.clone()));
+            match resolved {
+                Field::Value(value, origin) => {
+                    let _ = writeln!(
+                        out,
+                        "      {key}: {}{comma} // {}",
+                        json(value),
+                        origin.comment()
+                    );
+                }
+                Field::Extended(entries) => {
+                    let _ = writeln!(out, "      {key}: [");
+                    for (entry_index, (entry, origin)) in entries.iter().enumerate() {
+                        let _ = writeln!(
+                            out,
+                            "        {}{} // {}",
+                            json(entry),
+                            separator(entry_index, entries.len()),
+                            origin.comment()
+                        );
+                    }
+                    let _ = writeln!(out, "      ]{comma}");
+                }
+            }
+        }
+        let _ = writeln!(out, "    }}{}", separator(task_index, tasks.len()));
+    }
+    out.push_str("  }\n}\n");
+    out
+}
+
+fn json(value: &Value) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+const fn separator(index: usize, len: usize) -> &'static str {
+    if index + 1 < len { "," } else { "" }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn resolve_merges_package_overrides_and_extends() {
+        let root = json!({
+            "tasks": {
+                "build": {"outputs": ["dist/**"], "cache": true},
+                "web#lint": {"cache": false},
+                "ui#test": {}
+            }
+        });
+        let package = json!({
+            "extends": ["//"],
+            "tasks": {"build": {"outputs": ["$TURBO_EXTENDS$", ".next/**"], "cache": false}}
+        });
+
+        let tasks = resolve(&root, Some(&package), "web");
+
+        assert_eq!(tasks.keys().collect::<Vec<_>>(), vec!["build", "lint"]);
+        assert_eq!(
+            tasks["build"]["outputs"],
+            Field::Extended(vec![
+                (json!("dist/**"), Origin::Root),
+                (json!(".next/**"), Origin::Extends),
+            ])
+        );
+        assert_eq!(
+            tasks["build"]["cache"],
+            Field::Value(json!(false), Origin::Package)
+        );
+        assert_eq!(
+            tasks["lint"]["cache"],
+            Field::Value(json!(false), Origin::Root)
+        );
+
+        let text = render("web", &tasks);
+        assert!(text.contains("\"cache\": false, // package turbo.json"));
+        assert!(text.contains("\".next/**\" // $TURBO_EXTENDS$ append"));
+        let uri = |dir: &str| uri_for(Path::new("/repo/one"), dir).unwrap();
+        assert_eq!(package_dir(&uri("apps/web")).as_deref(), Some("apps/web"));
+        assert_eq!(
+            repo_root(&uri("apps/web")),
+            Some(PathBuf::from("/repo/one"))
+        );
+        assert_ne!(
+            uri("apps/web"),
+            uri_for(Path::new("/repo/two"), "apps/web").unwrap()
+        );
+        assert_eq!(
+            snapshot_file_name(&uri("apps/web-ui")).as_deref(),
+            Some("apps%2Fweb-ui.jsonc")
+        );
+        assert_eq!(
+            snapshot_file_name(&uri("apps-web/ui")).as_deref(),
+            Some("apps-web%2Fui.jsonc")
+        );
+    }
+
+    #[test]
+    fn extends_without_root_array_appends_to_nothing() {
+        let root = json!({"tasks": {"build": {"inputs": "src/**"}, "test": {}}});
+        let package = json!({"tasks": {
+            "build": {"inputs": ["$TURBO_EXTENDS$", "lib/**"]},
+            "test": {"env": ["$TURBO_EXTENDS$", "CI"]}
+        }});
+
+        let tasks = resolve(&root, Some(&package), "web");
+
+        assert_eq!(
+            tasks["build"]["inputs"],
+            Field::Extended(vec![(json!("lib/**"), Origin::Extends)])
+        );
+        assert_eq!(
+            tasks["test"]["env"],
+            Field::Extended(vec![(json!("CI"), Origin::Extends)])
+        );
+        assert!(!render("web", &tasks).contains("\"$TURBO_EXTENDS$\""));
+    }
+}
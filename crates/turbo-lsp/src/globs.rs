@@ -0,0 +1,421 @@
+//! `inputs`, `outputs` and `globalDependencies` entries: where they apply and what they match
+
+use std::{
+    fmt::Write as _,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use jsonc_parser::ast::Object;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Url,
+};
+use turbo_core::{Package, glob};
+
+use crate::{ROOT_PACKAGE_NAME, TaskReference, byte_range_to_lsp_range, collapse_string_range};
+
+/// Glob arrays inside a task definition
+pub const TASK_GLOB_KEYS: &[&str] = &["inputs", "outputs"];
+
+/// Glob arrays at the root of turbo.json
+pub const GLOBAL_GLOB_KEYS: &[&str] = &["globalDependencies"];
+
+/// Stop counting matches past this many files
+const MATCH_LIMIT: usize = 10_000;
+
+/// Packages listed individually in a hover
+const HOVER_PACKAGES: usize = 5;
+
+/// Packages a glob is evaluated in, as `(name, directory)`
+///
+/// `globalDependencies` (no task) apply to the repo root. Task globs apply to
+/// the package owning a package-level turbo.json, or to every package that
+/// implements the task.
+pub fn bases(
+    packages: &[Package],
+    root: &Path,
+    task_name: Option<&str>,
+    own: Option<&Package>,
+) -> Vec<(String, PathBuf)> {
+    let Some(task_name) = task_name else {
+        return vec![(ROOT_PACKAGE_NAME.to_string(), root.to_path_buf())];
+    };
+    if let Some(own) = own {
+        return vec![(own.name.clone(), own.path.clone())];
+    }
+
+    let task_ref = TaskReference::parse(task_name);
+    packages
+        .iter()
+        .filter(|package| match task_ref.package {
+            Some(ROOT_PACKAGE_NAME) => package.path == root,
+            Some(name) => package.name == name,
+            None => package.scripts.contains_key(task_ref.task),
+        })
+        .map(|package| (package.name.clone(), package.path.clone()))
+        .collect()
+}
+
+/// Matches of a glob in each base, `None` where it is not a path (`$TURBO_DEFAULT$`)
+///
+/// Reads the file system; call from a blocking context.
+pub fn count_matches(
+    entry: &str,
+    bases: &[(String, PathBuf)],
+    root: &Path,
+    limit: usize,
+) -> Option<Result<Vec<(String, usize)>, String>> {
+    let mut counts = Vec::new();
+    for (name, dir) in bases {
+        let resolved = glob::resolve(entry, dir, root)?;
+        match resolved.count_matches(limit) {
+            Ok(count) => counts.push((name.clone(), count)),
+            Err(e) => return Some(Err(e.to_string())),
+        }
+    }
+    Some(Ok(counts))
+}
+
+/// Outcome of matching a glob in one package directory
+#[derive(Debug, Clone)]
+enum Matched {
+    /// `$TURBO_DEFAULT$`
+    NotAPath,
+    Invalid(String),
+    Files(bool),
+}
+
+/// Whether globs match anything in a package directory, kept between checks
+///
+/// Each result remembers the directory its glob was evaluated in, so a created or
+/// deleted file only drops results that could see it.
+#[derive(Debug, Clone, Default)]
+pub struct MatchCache {
+    matches: IndexMap<(String, PathBuf), (Option<PathBuf>, Matched)>,
+}
+
+impl MatchCache {
+    /// Forget results of globs evaluated in a directory containing `path`;
+    /// whether any were dropped
+    pub fn invalidate(&mut self, path: &Path) -> bool {
+        let before = self.matches.len();
+        self.matches
+            .retain(|_, (dir, _)| !dir.as_ref().is_some_and(|dir| path.starts_with(dir)));
+        self.matches.len() != before
+    }
+
+    /// Directories cached globs were evaluated in, where created or deleted files matter
+    pub fn dirs(&self) -> impl Iterator<Item = &Path> {
+        self.matches.values().filter_map(|(dir, _)| dir.as_deref())
+    }
+
+    /// Cached result for `entry` in `dir`, evaluated first if `scan` is set
+    ///
+    /// Reads the file system when scanning; call from a blocking context.
+    fn any_match(&mut self, entry: &str, dir: &Path, root: &Path, scan: bool) -> Option<Matched> {
+        let key = (entry.to_string(), dir.to_path_buf());
+        if let Some((_, found)) = self.matches.get(&key) {
+            return Some(found.clone());
+        }
+        if !scan {
+            return None;
+        }
+        let resolved = glob::resolve(entry, dir, root);
+        let found = resolved.as_ref().map_or(Matched::NotAPath, |resolved| {
+            resolved.count_matches(1).map_or_else(
+                |e| Matched::Invalid(e.to_string()),
+                |count| Matched::Files(count > 0),
+            )
+        });
+        self.matches
+            .insert(key, (resolved.map(|resolved| resolved.dir), found.clone()));
+        Some(found)
+    }
+}
+
+/// Hover previewing how many files a glob matches in each package
+pub fn hover_markdown(
+    entry: &str,
+    field_name: &str,
+    bases: &[(String, PathBuf)],
+    root: &Path,
+) -> String {
+    let mut markdown = format!("### `{entry}`\n\n");
+    match count_matches(entry, bases, root, MATCH_LIMIT) {
+        None => {
+            markdown.push_str("Turbo's default inputs: every file tracked by git in the package.");
+        }
+        Some(Err(message)) => {
+            let _ = write!(markdown, "Invalid glob: {message}");
+        }
+        Some(Ok(counts)) if counts.is_empty() => {
+            let _ = write!(
+                markdown,
+                "No package implements this task, so `{field_name}` matches nothing."
+            );
+        }
+        Some(Ok(counts)) => {
+            let total: usize = counts.iter().map(|(_, count)| count).sum();
+            let limit = if counts.iter().any(|(_, count)| *count >= MATCH_LIMIT) {
+                "+"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                markdown,
+                "Matches **{total}{limit}** file(s) in {} package(s).\n",
+                counts.len()
+            );
+            for (name, count) in counts.iter().take(HOVER_PACKAGES) {
+                let _ = writeln!(markdown, "- `{name}`: {count}");
+            }
+            if counts.len() > HOVER_PACKAGES {
+                let _ = writeln!(markdown, "- ...");
+            }
+        }
+    }
+    markdown
+}
+
+/// A glob string in `inputs`, `outputs` or `globalDependencies`
+#[derive(Debug, Clone)]
+pub struct GlobEntry {
+    /// `None` for `globalDependencies`
+    pub task_name: Option<String>,
+    pub field_name: String,
+    pub entry: String,
+    /// Byte range of the string contents
+    pub range: Range<usize>,
+}
+
+/// Every glob entry of a turbo.json
+pub fn entries(config: &Object<'_>) -> Vec<GlobEntry> {
+    let mut entries = Vec::new();
+    let mut push = |task_name: Option<&str>, field: &jsonc_parser::ast::ObjectProp<'_>| {
+        let Some(array) = field.value.as_array() else {
+            return;
+        };
+        for string in array
+            .elements
+            .iter()
+            .filter_map(|value| value.as_string_lit())
+        {
+            entries.push(GlobEntry {
+                task_name: task_name.map(str::to_string),
+                field_name: field.name.as_str().to_string(),
+                entry: string.value.to_string(),
+                range: collapse_string_range(string.range),
+            });
+        }
+    };
+
+    for property in &config.properties {
+        if GLOBAL_GLOB_KEYS.contains(&property.name.as_str()) {
+            push(None, property);
+        }
+    }
+    for task_group_name in ["tasks", "pipeline"] {
+        let Some(tasks) = config.get_object(task_group_name) else {
+            continue;
+        };
+        for task in &tasks.properties {
+            let Some(fields) = task.value.as_object() else {
+                continue;
+            };
+            for field in &fields.properties {
+                if TASK_GLOB_KEYS.contains(&field.name.as_str()) {
+                    push(Some(task.name.as_str()), field);
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Why a glob entry deserves a diagnostic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobProblem {
+    Invalid(String),
+    /// An `inputs` or `globalDependencies` glob matching nothing
+    NoMatch,
+    /// An `outputs` glob matching nothing although the task has run
+    NoOutputs,
+}
+
+/// Evaluate an entry in its bases; negated entries and `$TURBO_DEFAULT$` are not checked
+///
+/// Without `scan` only cached results are used, and entries missing from the
+/// cache are not checked. Reads the file system when scanning; call from a
+/// blocking context.
+pub fn check(
+    entry: &GlobEntry,
+    bases: &[(String, PathBuf)],
+    root: &Path,
+    task_ran: bool,
+    cache: &mut MatchCache,
+    scan: bool,
+) -> Option<GlobProblem> {
+    if entry.entry.starts_with('!') || bases.is_empty() {
+        return None;
+    }
+    let mut matched = false;
+    for (_, dir) in bases {
+        match cache.any_match(&entry.entry, dir, root, scan)? {
+            Matched::NotAPath => return None,
+            Matched::Invalid(message) => return Some(GlobProblem::Invalid(message)),
+            Matched::Files(any) => matched |= any,
+        }
+    }
+    if matched {
+        return None;
+    }
+    match entry.field_name.as_str() {
+        "outputs" if task_ran => Some(GlobProblem::NoOutputs),
+        "outputs" => None,
+        _ => Some(GlobProblem::NoMatch),
+    }
+}
+
+/// Diagnostic for a problem, listing the checked packages as related information
+pub fn diagnostic(
+    text: &str,
+    entry: &GlobEntry,
+    problem: &GlobProblem,
+    bases: &[(String, PathBuf)],
+) -> Diagnostic {
+    let (severity, code, message) = match problem {
+        GlobProblem::Invalid(message) => (
+            DiagnosticSeverity::ERROR,
+            "turbo:invalid-glob",
+            message.clone(),
+        ),
+        GlobProblem::NoMatch => (
+            DiagnosticSeverity::WARNING,
+            "turbo:glob-no-match",
+            format!(
+                "`{}` matches no files in {} checked package(s), so it never affects the cache key.",
+                entry.entry,
+                bases.len()
+            ),
+        ),
+        GlobProblem::NoOutputs => (
+            DiagnosticSeverity::INFORMATION,
+            "turbo:outputs-no-match",
+            format!(
+                "`{}` matched no files in {} package(s) after the task ran; nothing will be cached.",
+                entry.entry,
+                bases.len()
+            ),
+        ),
+    };
+
+    let related_information = (!matches!(problem, GlobProblem::Invalid(_))).then(|| {
+        bases
+            .iter()
+            .filter_map(|(name, dir)| {
+                Some(DiagnosticRelatedInformation {
+                    location: Location::new(
+                        Url::from_file_path(dir.join("package.json")).ok()?,
+                        tower_lsp::lsp_types::Range::default(),
+                    ),
+                    message: format!("checked `{name}`"),
+                })
+            })
+            .collect()
+    });
+
+    Diagnostic {
+        message,
+        range: byte_range_to_lsp_range(text, entry.range.clone()),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        related_information,
+        ..Diagnostic::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_core::test_util::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn check_reports_missing_inputs_and_outputs() {
+        let dir = TempDir::new("lsp-globs");
+        dir.write("src/index.ts", "");
+        let bases = vec![("web".to_string(), dir.to_path_buf())];
+        let entry = |field_name: &str, entry: &str| GlobEntry {
+            task_name: Some("build".to_string()),
+            field_name: field_name.to_string(),
+            entry: entry.to_string(),
+            range: 0..0,
+        };
+        let mut cache = MatchCache::default();
+        let mut check = |entry: &GlobEntry, task_ran: bool| {
+            check(entry, &bases, &dir, task_ran, &mut cache, true)
+        };
+
+        assert_eq!(check(&entry("inputs", "src/**"), false), None);
+        assert_eq!(
+            check(&entry("inputs", "lib/**"), false),
+            Some(GlobProblem::NoMatch)
+        );
+        assert_eq!(check(&entry("inputs", "!lib/**"), false), None);
+        assert_eq!(check(&entry("outputs", "dist/**"), false), None);
+        assert_eq!(
+            check(&entry("outputs", "dist/**"), true),
+            Some(GlobProblem::NoOutputs)
+        );
+        assert!(matches!(
+            check(&entry("inputs", "src/[a"), false),
+            Some(GlobProblem::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn check_without_scan_only_uses_cached_matches() {
+        let dir = TempDir::new("lsp-glob-cache");
+        let bases = vec![("web".to_string(), dir.to_path_buf())];
+        let entry = GlobEntry {
+            task_name: Some("build".to_string()),
+            field_name: "inputs".to_string(),
+            entry: "src/**".to_string(),
+            range: 0..0,
+        };
+        let mut cache = MatchCache::default();
+
+        assert_eq!(check(&entry, &bases, &dir, false, &mut cache, false), None);
+        assert_eq!(
+            check(&entry, &bases, &dir, false, &mut cache, true),
+            Some(GlobProblem::NoMatch)
+        );
+        dir.write("src/index.ts", "");
+        assert_eq!(
+            check(&entry, &bases, &dir, false, &mut cache, false),
+            Some(GlobProblem::NoMatch)
+        );
+        assert!(!cache.invalidate(&dir.with_extension("other").join("src/index.ts")));
+        assert!(cache.invalidate(&dir.join("src/index.ts")));
+        assert_eq!(check(&entry, &bases, &dir, false, &mut cache, true), None);
+    }
+
+    #[test]
+    fn check_resolves_parent_directory_globs() {
+        let dir = TempDir::new("lsp-glob-parent");
+        dir.write("packages/shared/index.ts", "");
+        let web = dir.join("packages/web");
+        std::fs::create_dir_all(&web).unwrap();
+        let bases = vec![("web".to_string(), web)];
+        let entry = GlobEntry {
+            task_name: Some("build".to_string()),
+            field_name: "inputs".to_string(),
+            entry: "../shared/**".to_string(),
+            range: 0..0,
+        };
+
+        let mut cache = MatchCache::default();
+        assert_eq!(check(&entry, &bases, &dir, false, &mut cache, true), None);
+    }
+}
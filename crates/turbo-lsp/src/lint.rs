@@ -0,0 +1,476 @@
+//! Turborepo-specific semantic checks on task definitions
+//!
+//! Fixable diagnostics carry `{"title", "edits"}` in `data`, turned into quick
+//! fixes by `replacement_action`.
+
+use std::ops::Range;
+
+use indexmap::{IndexMap, IndexSet};
+use jsonc_parser::{
+    ast::{Array, Object, ObjectProp, Value},
+    common::Ranged,
+};
+use serde_json::json;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location,
+    NumberOrString, TextEdit, Url,
+};
+use turbo_core::TurboConfig;
+
+use crate::{TaskReference, byte_range_to_lsp_range, collapse_string_range, name_range};
+
+/// Semantic problems in a turbo.json; `root_config` supplies task definitions
+/// a package config inherits
+pub fn diagnostics(
+    text: &str,
+    uri: &Url,
+    config: &Object<'_>,
+    root_config: Option<&TurboConfig>,
+) -> Vec<Diagnostic> {
+    let mut lint = Lint {
+        text,
+        uri,
+        diagnostics: Vec::new(),
+    };
+    lint.duplicate_keys(config);
+    lint.duplicate_tasks(config);
+
+    let persistent = persistent_tasks(config);
+    let defined: IndexSet<&str> = task_definitions(config)
+        .map(|task| task.name.as_str())
+        .collect();
+    let is_defined = |label: &str| {
+        let task = TaskReference::parse(label).task;
+        [label, task].into_iter().any(|name| {
+            defined.contains(name) || root_config.is_some_and(|root| root.get_task(name).is_some())
+        })
+    };
+    let is_persistent = |label: &str| {
+        let task = TaskReference::parse(label).task;
+        persistent
+            .get(label)
+            .or_else(|| persistent.get(task))
+            .copied()
+            .or_else(|| {
+                let root = root_config?;
+                root.get_task(label)
+                    .or_else(|| root.get_task(task))?
+                    .persistent
+            })
+            .unwrap_or(false)
+    };
+
+    for task_group_name in ["tasks", "pipeline"] {
+        let Some(tasks) = config.get_object(task_group_name) else {
+            continue;
+        };
+        for task in &tasks.properties {
+            if let Some(fields) = task.value.as_object() {
+                lint.task(task.name.as_str(), fields, &is_persistent, &is_defined);
+            }
+        }
+    }
+    lint.diagnostics
+}
+
+/// `persistent` of every task defined in the document
+fn persistent_tasks<'a>(config: &'a Object<'a>) -> IndexMap<&'a str, bool> {
+    task_definitions(config)
+        .filter_map(|task| {
+            let persistent = bool_field(task.value.as_object()?, "persistent")?;
+            Some((task.name.as_str(), persistent))
+        })
+        .collect()
+}
+
+fn task_definitions<'a>(config: &'a Object<'a>) -> impl Iterator<Item = &'a ObjectProp<'a>> {
+    ["tasks", "pipeline"]
+        .into_iter()
+        .filter_map(|group| config.get_object(group))
+        .flat_map(|tasks| &tasks.properties)
+}
+
+fn bool_field(fields: &Object<'_>, name: &str) -> Option<bool> {
+    match &fields.get(name)?.value {
+        Value::BooleanLit(value) => Some(value.value),
+        _ => None,
+    }
+}
+
+struct Lint<'t> {
+    text: &'t str,
+    uri: &'t Url,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Lint<'_> {
+    fn duplicate_keys(&mut self, object: &Object<'_>) {
+        let mut seen: IndexMap<&str, &ObjectProp<'_>> = IndexMap::new();
+        for (index, property) in object.properties.iter().enumerate() {
+            let name = property.name.as_str();
+            if let Some(first) = seen.get(name) {
+                let related = self.related(name_range(&first.name), "first defined here");
+                let removal = removal_range(&object.properties, index);
+                let diagnostic = self.diagnostic(
+                    name_range(&property.name),
+                    DiagnosticSeverity::ERROR,
+                    "turbo:duplicate-key",
+                    format!("Duplicate key `{name}`; turbo rejects objects with repeated keys."),
+                );
+                self.diagnostics.push(Diagnostic {
+                    related_information: Some(vec![related]),
+                    data: Some(self.fix("Remove duplicate key", &[(removal, String::new())])),
+                    ..diagnostic
+                });
+            } else {
+                seen.insert(name, property);
+            }
+
+            match &property.value {
+                Value::Object(child) => self.duplicate_keys(child),
+                Value::Array(array) => {
+                    for element in &array.elements {
+                        if let Value::Object(child) = element {
+                            self.duplicate_keys(child);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn duplicate_tasks(&mut self, config: &Object<'_>) {
+        let (Some(tasks), Some(pipeline)) =
+            (config.get_object("tasks"), config.get_object("pipeline"))
+        else {
+            return;
+        };
+        for task in &pipeline.properties {
+            let name = task.name.as_str();
+            let Some(definition) = tasks.get(name) else {
+                continue;
+            };
+            let related = self.related(name_range(&definition.name), "also defined in `tasks`");
+            let diagnostic = self.diagnostic(
+                name_range(&task.name),
+                DiagnosticSeverity::WARNING,
+                "turbo:duplicate-task",
+                format!("`{name}` is defined in both `tasks` and `pipeline`."),
+            );
+            self.diagnostics.push(Diagnostic {
+                related_information: Some(vec![related]),
+                ..diagnostic
+            });
+        }
+    }
+
+    fn task(
+        &mut self,
+        task_name: &str,
+        fields: &Object<'_>,
+        is_persistent: &dyn Fn(&str) -> bool,
+        is_defined: &dyn Fn(&str) -> bool,
+    ) {
+        let persistent = bool_field(fields, "persistent").unwrap_or(false);
+        let cache = bool_field(fields, "cache");
+
+        if !persistent && let Some(depends_on) = fields.get("dependsOn") {
+            self.persistent_dependencies(task_name, fields, depends_on, is_persistent);
+        }
+        if let Some(with) = fields.get("with").and_then(|with| with.value.as_array()) {
+            self.with_entries(task_name, with, is_defined);
+        }
+
+        if let Some(interactive) = fields.get("interactive")
+            && matches!(interactive.value, Value::BooleanLit(ref value) if value.value)
+            && cache != Some(false)
+        {
+            let edit = fields.get("cache").map_or_else(
+                || {
+                    (
+                        interactive.range.start..interactive.range.start,
+                        "\"cache\": false, ".to_string(),
+                    )
+                },
+                |cache| (value_range(&cache.value), "false".to_string()),
+            );
+            let diagnostic = self.diagnostic(
+                name_range(&interactive.name),
+                DiagnosticSeverity::WARNING,
+                "turbo:interactive-cache",
+                format!("Interactive task `{task_name}` must also set `\"cache\": false`."),
+            );
+            self.diagnostics.push(Diagnostic {
+                data: Some(self.fix("Set `\"cache\": false`", &[edit])),
+                ..diagnostic
+            });
+        }
+
+        if cache == Some(false)
+            && let Some(index) = fields
+                .properties
+                .iter()
+                .position(|property| property.name.as_str() == "outputs")
+        {
+            let outputs = &fields.properties[index];
+            let diagnostic = self.diagnostic(
+                name_range(&outputs.name),
+                DiagnosticSeverity::WARNING,
+                "turbo:outputs-without-cache",
+                format!("`{task_name}` is not cached, so its `outputs` are never stored."),
+            );
+            self.diagnostics.push(Diagnostic {
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                data: Some(self.fix(
+                    "Remove `outputs`",
+                    &[(removal_range(&fields.properties, index), String::new())],
+                )),
+                ..diagnostic
+            });
+        }
+    }
+
+    /// A non-persistent task cannot wait for a persistent one; run it alongside with `with`
+    fn persistent_dependencies(
+        &mut self,
+        task_name: &str,
+        fields: &Object<'_>,
+        depends_on: &ObjectProp<'_>,
+        is_persistent: &dyn Fn(&str) -> bool,
+    ) {
+        let Some(entries) = depends_on.value.as_array() else {
+            return;
+        };
+        for (index, entry) in entries.elements.iter().enumerate() {
+            let Some(string) = entry.as_string_lit() else {
+                continue;
+            };
+            let label = string.value.trim_start_matches('^');
+            if !is_persistent(label) {
+                continue;
+            }
+
+            let mut edits = vec![(removal_range(&entries.elements, index), String::new())];
+            if !string.value.starts_with('^') {
+                edits.push(append_to_with(fields, depends_on, label));
+            }
+            let diagnostic = self.diagnostic(
+                collapse_string_range(string.range),
+                DiagnosticSeverity::ERROR,
+                "turbo:depends-on-persistent",
+                format!(
+                    "`{task_name}` depends on the persistent task `{label}`, which never exits; turbo refuses to run this. List it in `with` to run it alongside instead."
+                ),
+            );
+            let title = if edits.len() > 1 {
+                format!("Move `{label}` to `with`")
+            } else {
+                format!("Remove `{}`", string.value)
+            };
+            self.diagnostics.push(Diagnostic {
+                data: Some(self.fix(&title, &edits)),
+                ..diagnostic
+            });
+        }
+    }
+
+    /// `with` starts tasks of the same package alongside this one, so they must exist
+    /// and cannot use `^`
+    fn with_entries(
+        &mut self,
+        task_name: &str,
+        with: &Array<'_>,
+        is_defined: &dyn Fn(&str) -> bool,
+    ) {
+        for (index, entry) in with.elements.iter().enumerate() {
+            let Some(string) = entry.as_string_lit() else {
+                continue;
+            };
+            let (code, message) = if string.value.starts_with('^') {
+                (
+                    "turbo:with-dependency",
+                    format!(
+                        "`with` runs tasks alongside `{task_name}` and does not accept `^`; use `dependsOn` to run `{}` in dependencies first.",
+                        string.value.trim_start_matches('^')
+                    ),
+                )
+            } else if !is_defined(&string.value) {
+                (
+                    "turbo:with-undefined-task",
+                    format!("`{}` is not a task defined in turbo.json.", string.value),
+                )
+            } else {
+                continue;
+            };
+            let diagnostic = self.diagnostic(
+                collapse_string_range(string.range),
+                DiagnosticSeverity::ERROR,
+                code,
+                message,
+            );
+            self.diagnostics.push(Diagnostic {
+                data: Some(self.fix(
+                    &format!("Remove `{}`", string.value),
+                    &[(removal_range(&with.elements, index), String::new())],
+                )),
+                ..diagnostic
+            });
+        }
+    }
+
+    fn diagnostic(
+        &self,
+        range: Range<usize>,
+        severity: DiagnosticSeverity,
+        code: &str,
+        message: String,
+    ) -> Diagnostic {
+        Diagnostic {
+            message,
+            range: byte_range_to_lsp_range(self.text, range),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(code.to_string())),
+            ..Diagnostic::default()
+        }
+    }
+
+    fn related(&self, range: Range<usize>, message: &str) -> DiagnosticRelatedInformation {
+        DiagnosticRelatedInformation {
+            location: Location::new(self.uri.clone(), byte_range_to_lsp_range(self.text, range)),
+            message: message.to_string(),
+        }
+    }
+
+    fn fix(&self, title: &str, edits: &[(Range<usize>, String)]) -> serde_json::Value {
+        let edits: Vec<TextEdit> = edits
+            .iter()
+            .map(|(range, new_text)| TextEdit {
+                range: byte_range_to_lsp_range(self.text, range.clone()),
+                new_text: new_text.clone(),
+            })
+            .collect();
+        json!({ "title": title, "edits": edits })
+    }
+}
+
+/// Edit adding `label` to the task's `with` array, creating it after `dependsOn` if needed
+fn append_to_with(
+    fields: &Object<'_>,
+    depends_on: &ObjectProp<'_>,
+    label: &str,
+) -> (Range<usize>, String) {
+    let quoted = serde_json::Value::from(label).to_string();
+    match fields.get("with").map(|with| &with.value) {
+        Some(Value::Array(Array { range, elements })) => elements.last().map_or_else(
+            || (range.start + 1..range.start + 1, quoted.clone()),
+            |last| (last.range().end..last.range().end, format!(", {quoted}")),
+        ),
+        _ => (
+            depends_on.range.end..depends_on.range.end,
+            format!(", \"with\": [{quoted}]"),
+        ),
+    }
+}
+
+/// Range removing one of several siblings together with its separating comma
+pub fn removal_range<T: Ranged>(siblings: &[T], index: usize) -> Range<usize> {
+    let current = siblings[index].range();
+    siblings.get(index + 1).map_or_else(
+        || {
+            index
+                .checked_sub(1)
+                .and_then(|index| siblings.get(index))
+                .map_or(current.start..current.end, |previous| {
+                    previous.range().end..current.end
+                })
+        },
+        |next| current.start..next.range().start,
+    )
+}
+
+fn value_range(value: &Value<'_>) -> Range<usize> {
+    value.range().start..value.range().end
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonc_parser::{CollectOptions, ParseOptions, parse_to_ast};
+
+    use super::*;
+
+    fn codes(text: &str) -> Vec<String> {
+        let parse =
+            parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()).unwrap();
+        let config = parse.value.as_ref().and_then(Value::as_object).unwrap();
+        let uri = Url::parse("file:///repo/turbo.json").unwrap();
+        diagnostics(text, &uri, config, None)
+            .into_iter()
+            .filter_map(|diagnostic| match diagnostic.code? {
+                NumberOrString::String(code) => Some(code),
+                NumberOrString::Number(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_task_rules() {
+        let text = r#"{
+  "tasks": {
+    "dev": { "persistent": true, "cache": false },
+    "test": { "dependsOn": ["dev"] },
+    "e2e": { "interactive": true },
+    "lint": { "cache": false, "outputs": [] }
+  },
+  "pipeline": { "lint": {} }
+}"#;
+        assert_eq!(
+            codes(text),
+            vec![
+                "turbo:duplicate-task",
+                "turbo:depends-on-persistent",
+                "turbo:interactive-cache",
+                "turbo:outputs-without-cache",
+            ]
+        );
+        assert_eq!(
+            codes(r#"{"tasks": {"dev": {"persistent": true, "dependsOn": ["dev"]}}}"#),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn reports_with_entries() {
+        let text = r#"{
+  "tasks": {
+    "dev": { "persistent": true, "with": ["api#watch", "^codegen", "missing"] },
+    "watch": { "persistent": true },
+    "codegen": {}
+  }
+}"#;
+        assert_eq!(
+            codes(text),
+            vec!["turbo:with-dependency", "turbo:with-undefined-task"]
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_keys() {
+        assert_eq!(
+            codes(r#"{"tasks": {"build": {"cache": true, "cache": false}}, "ui": "tui"}"#),
+            vec!["turbo:duplicate-key"]
+        );
+    }
+
+    #[test]
+    fn removal_range_includes_separator() {
+        let text = r#"["a", "b", "c"]"#;
+        let parse =
+            parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()).unwrap();
+        let Some(Value::Array(array)) = parse.value else {
+            unreachable!()
+        };
+        assert_eq!(&text[removal_range(&array.elements, 0)], r#""a", "#);
+        assert_eq!(&text[removal_range(&array.elements, 2)], r#", "c""#);
+    }
+}
@@ -2,39 +2,76 @@
 //!
 //! Local-first Language Server Protocol implementation for Turborepo.
 
+mod call_hierarchy;
+mod code_lens;
+mod completion;
+mod deprecations;
+mod effective_config;
+mod globs;
+mod lint;
+mod package_config;
+mod rename;
+mod run_stats;
+mod schema;
+mod symbols;
+mod task_run;
+mod watched_files;
+
 use std::{
+    collections::BTreeSet,
+    hash::{DefaultHasher, Hash, Hasher},
     path::{Path, PathBuf},
-    sync::Mutex,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::SystemTime,
 };
 
 use indexmap::{IndexMap, IndexSet};
 use jsonc_parser::{
     CollectOptions, ParseOptions,
-    ast::{ObjectPropName, StringLit},
+    ast::{ObjectPropName, StringLit, Value},
     common::Range as JsonRange,
     parse_to_ast,
 };
-use tokio::io;
+use tokio::io::{self, AsyncWriteExt};
 use tower_lsp::{
     Client, LspService, Server,
     lsp_types::{
-        CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
-        CodeActionProviderCapability, CodeActionResponse, CodeLens, CodeLensOptions,
-        CodeLensParams, Command, CompletionItem, CompletionItemKind, CompletionOptions,
-        CompletionParams, CompletionResponse, Diagnostic, DiagnosticSeverity, DiagnosticTag,
-        DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
-        DidChangeWorkspaceFoldersParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-        DidSaveTextDocumentParams, ExecuteCommandOptions, ExecuteCommandParams,
+        CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+        CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+        CallHierarchyServerCapability, CodeAction, CodeActionKind, CodeActionOrCommand,
+        CodeActionParams, CodeActionProviderCapability, CodeActionResponse, CodeLens,
+        CodeLensOptions, CodeLensParams, Command, CompletionItem, CompletionItemKind,
+        CompletionOptions, CompletionParams, CompletionResponse, Diagnostic, DiagnosticSeverity,
+        DiagnosticTag, DidChangeConfigurationParams, DidChangeTextDocumentParams,
+        DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams, DidCloseTextDocumentParams,
+        DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentSymbolParams,
+        DocumentSymbolResponse, ExecuteCommandOptions, ExecuteCommandParams, FileChangeType,
         GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
-        InitializeParams, InitializeResult, InitializedParams, Location, MarkupContent, MarkupKind,
-        NumberOrString, OneOf, OptionalVersionedTextDocumentIdentifier, Position, Range,
-        ReferenceParams, ReferencesOptions, ServerCapabilities, ServerInfo,
-        TextDocumentContentChangeEvent, TextDocumentEdit, TextDocumentSyncCapability,
-        TextDocumentSyncKind, TextEdit, Url, WorkDoneProgressOptions, WorkspaceEdit,
-        WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
+        InitializeParams, InitializeResult, InitializedParams, InlayHint, InlayHintLabel,
+        InlayHintParams, Location, MarkupContent, MarkupKind, MessageActionItem, MessageType,
+        NumberOrString, OneOf, OptionalVersionedTextDocumentIdentifier, Position,
+        PrepareRenameResponse, Range, ReferenceParams, ReferencesOptions, RenameOptions,
+        RenameParams, ServerCapabilities, ServerInfo, ShowDocumentParams, SymbolInformation,
+        SymbolKind, TextDocumentContentChangeEvent, TextDocumentEdit, TextDocumentIdentifier,
+        TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit,
+        Url, WorkDoneProgressCancelParams, WorkDoneProgressOptions, WorkspaceEdit, WorkspaceFolder,
+        WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities, WorkspaceSymbolParams,
     },
 };
-use turbo_core::{Package, PackageDiscovery, TurboConfig};
+use turbo_core::{
+    Package, TaskStats, TurboConfig, TurboVersion, VersionSource, WorkspaceSnapshot,
+    config::CONFIG_FILES, env::EnvSource,
+};
+
+use crate::{
+    code_lens::Lazy,
+    completion::{CursorContext, EnvSuggestion, Slot},
+    run_stats::RunStats,
+    task_run::RunRequest,
+};
 
 include!(concat!(env!("OUT_DIR"), "/doc_links_generated.rs"));
 
@@ -70,8 +107,20 @@ impl<'a> TaskReference<'a> {
 
 struct TurboBackend {
     client: Client,
-    repo_root: Mutex<Option<PathBuf>>,
+    workspace_folders: Mutex<Vec<PathBuf>>,
+    /// `TurboConfig::find_repo_root` per document directory, cleared on watched-file changes
+    document_roots: Mutex<IndexMap<PathBuf, Option<PathBuf>>>,
     files: Mutex<IndexMap<Url, String>>,
+    /// Workspace snapshots keyed by monorepo root, each behind its own lock so loading
+    /// one monorepo does not hold up requests for another
+    workspaces: Mutex<IndexMap<PathBuf, WorkspaceSlot>>,
+    watch_files_dynamically: AtomicBool,
+    /// Whether file watchers may be relative to a base directory
+    relative_watch_patterns: AtomicBool,
+    /// Directories the glob create/delete watchers are registered for
+    glob_watch_dirs: tokio::sync::Mutex<BTreeSet<PathBuf>>,
+    work_done_progress: AtomicBool,
+    runs: task_run::Runs,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -86,6 +135,12 @@ enum HoverTarget {
         task_name: String,
         entry: String,
     },
+    /// Entry of `inputs` / `outputs`, or of `globalDependencies` without a task
+    GlobEntry {
+        task_name: Option<String>,
+        field_name: String,
+        entry: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -93,29 +148,219 @@ struct WorkspaceState {
     packages: Vec<Package>,
     task_packages: IndexMap<String, Vec<String>>,
     package_names: IndexSet<String>,
+    root_config: Option<TurboConfig>,
+    turbo_version: Option<(TurboVersion, VersionSource)>,
+}
+
+/// Long-lived snapshot plus the state derived from it, rebuilt only when the snapshot changes
+struct CachedWorkspace {
+    snapshot: WorkspaceSnapshot,
+    state: Arc<WorkspaceState>,
+    /// `turbo_core::env::collect` per package directory and source scan, dropped when a
+    /// watched file below the directory changes
+    env_names: IndexMap<(PathBuf, bool), Arc<IndexMap<String, EnvSource>>>,
+    /// Watched-file events seen, so scans started before one are not cached
+    file_events: u64,
+    /// Parsed `.turbo/runs` with the directory's modification time when it was read
+    run_stats: Option<(Option<SystemTime>, Option<Arc<RunStats>>)>,
+    /// Glob diagnostic results, dropped when a watched file is created or deleted
+    glob_matches: globs::MatchCache,
+    /// [`version_fingerprint`] when `state.turbo_version` was detected
+    version_files: VersionFingerprint,
+}
+
+/// Snapshot of one root, `None` until its first load succeeds
+type WorkspaceSlot = Arc<tokio::sync::Mutex<Option<CachedWorkspace>>>;
+
+/// Modification time and size of each of `turbo_core::version::VERSION_FILES`
+type VersionFingerprint = Vec<Option<(Option<SystemTime>, u64)>>;
+
+async fn version_fingerprint(root: &Path) -> VersionFingerprint {
+    let mut fingerprint = Vec::new();
+    for name in turbo_core::version::VERSION_FILES {
+        let metadata = tokio::fs::metadata(root.join(name)).await.ok();
+        fingerprint.push(metadata.map(|metadata| (metadata.modified().ok(), metadata.len())));
+    }
+    fingerprint
+}
+
+impl CachedWorkspace {
+    fn new(
+        snapshot: WorkspaceSnapshot,
+        state: Arc<WorkspaceState>,
+        version_files: VersionFingerprint,
+    ) -> Self {
+        Self {
+            snapshot,
+            state,
+            version_files,
+            env_names: IndexMap::new(),
+            file_events: 0,
+            run_stats: None,
+            glob_matches: globs::MatchCache::default(),
+        }
+    }
+
+    /// Forget everything derived from a file that changed; whether glob results were dropped
+    fn invalidate(&mut self, path: &Path, change: FileChangeType) -> bool {
+        if watched_files::is_workspace_config(path) {
+            self.snapshot.invalidate(path);
+        }
+        let globs_changed = change != FileChangeType::CHANGED && self.glob_matches.invalidate(path);
+        self.env_names.retain(|(dir, _), _| !path.starts_with(dir));
+        self.file_events += 1;
+        globs_changed
+    }
+}
+
+impl WorkspaceState {
+    fn from_snapshot(
+        snapshot: &WorkspaceSnapshot,
+        turbo_version: Option<(TurboVersion, VersionSource)>,
+    ) -> Self {
+        let root = snapshot.root();
+        let packages = snapshot.packages().to_vec();
+        let mut task_packages: IndexMap<String, Vec<String>> = IndexMap::new();
+        let mut package_names = IndexSet::new();
+
+        for package in &packages {
+            let package_name = if package.path == root {
+                ROOT_PACKAGE_NAME.to_string()
+            } else {
+                package.name.clone()
+            };
+            package_names.insert(package_name.clone());
+
+            for script_name in package.scripts.keys() {
+                task_packages
+                    .entry(script_name.clone())
+                    .or_default()
+                    .push(package_name.clone());
+            }
+        }
+
+        if let Some(config) = snapshot.config() {
+            for task_name in config.task_names() {
+                task_packages.entry(task_name.to_string()).or_default();
+            }
+        }
+
+        Self {
+            packages,
+            task_packages,
+            package_names,
+            root_config: snapshot.config().cloned(),
+            turbo_version,
+        }
+    }
 }
 
 impl TurboBackend {
     fn new(client: Client) -> Self {
         Self {
             client,
-            repo_root: Mutex::new(None),
+            workspace_folders: Mutex::new(Vec::new()),
+            document_roots: Mutex::new(IndexMap::new()),
             files: Mutex::new(IndexMap::new()),
+            workspaces: Mutex::new(IndexMap::new()),
+            watch_files_dynamically: AtomicBool::new(false),
+            relative_watch_patterns: AtomicBool::new(false),
+            glob_watch_dirs: tokio::sync::Mutex::default(),
+            work_done_progress: AtomicBool::new(false),
+            runs: task_run::Runs::default(),
         }
     }
 
-    fn remember_root(&self, params: &InitializeParams) {
-        let path = params
-            .root_uri
-            .as_ref()
-            .and_then(|uri| uri.to_file_path().ok());
-        if let Ok(mut repo_root) = self.repo_root.lock() {
-            *repo_root = path;
+    fn remember_workspace_folders(&self, params: &InitializeParams) {
+        let folders = match &params.workspace_folders {
+            Some(folders) if !folders.is_empty() => folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect(),
+            _ => params
+                .root_uri
+                .as_ref()
+                .and_then(|uri| uri.to_file_path().ok())
+                .into_iter()
+                .collect(),
+        };
+        if let Ok(mut workspace_folders) = self.workspace_folders.lock() {
+            *workspace_folders = folders;
         }
     }
 
-    fn repo_root(&self) -> Option<PathBuf> {
-        self.repo_root.lock().ok()?.clone()
+    /// Apply added and removed workspace folders, returning the removed ones
+    fn change_workspace_folders(&self, params: &DidChangeWorkspaceFoldersParams) -> Vec<PathBuf> {
+        let to_paths = |folders: &[WorkspaceFolder]| -> Vec<PathBuf> {
+            folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect()
+        };
+        let removed = to_paths(&params.event.removed);
+        if let Ok(mut workspace_folders) = self.workspace_folders.lock() {
+            workspace_folders.retain(|folder| !removed.contains(folder));
+            for folder in to_paths(&params.event.added) {
+                if !workspace_folders.contains(&folder) {
+                    workspace_folders.push(folder);
+                }
+            }
+        }
+        removed
+    }
+
+    /// Monorepo root of a document: its nearest turbo.json root, else its workspace folder
+    fn root_for(&self, uri: &Url) -> Option<PathBuf> {
+        let path = uri.to_file_path().ok()?;
+        let dir = path.parent()?;
+        let cached = self
+            .document_roots
+            .lock()
+            .ok()
+            .and_then(|roots| roots.get(dir).cloned());
+        let root = cached.unwrap_or_else(|| {
+            let root = TurboConfig::find_repo_root(dir);
+            if let Ok(mut roots) = self.document_roots.lock() {
+                roots.insert(dir.to_path_buf(), root.clone());
+            }
+            root
+        });
+        root.or_else(|| {
+            self.workspace_folders
+                .lock()
+                .ok()?
+                .iter()
+                .filter(|folder| path.starts_with(folder))
+                .max_by_key(|folder| folder.as_os_str().len())
+                .cloned()
+        })
+    }
+
+    /// Monorepo root of every workspace folder
+    fn repo_roots(&self) -> Vec<PathBuf> {
+        let Ok(folders) = self.workspace_folders.lock() else {
+            return Vec::new();
+        };
+        folders
+            .iter()
+            .map(|folder| TurboConfig::find_repo_root(folder).unwrap_or_else(|| folder.clone()))
+            .collect()
+    }
+
+    /// Root for commands without a document: the first workspace folder's monorepo
+    fn default_root(&self) -> Option<PathBuf> {
+        self.repo_roots().into_iter().next()
+    }
+
+    /// Unsaved editor contents if the file is open, otherwise what is on disk
+    async fn read_file(&self, path: &Path) -> Option<String> {
+        if let Some(text) = Url::from_file_path(path)
+            .ok()
+            .and_then(|uri| self.read_open_file(&uri))
+        {
+            return Some(text);
+        }
+        tokio::fs::read_to_string(path).await.ok()
     }
 
     fn read_open_file(&self, uri: &Url) -> Option<String> {
@@ -146,64 +391,278 @@ impl TurboBackend {
         }
     }
 
-    async fn workspace_state(&self) -> Option<WorkspaceState> {
-        let root = self.repo_root()?;
-        let discovery = PackageDiscovery::new(root.clone());
-        let mut packages = discovery.discover_packages().await.ok()?;
+    /// Slot of `root`, created empty on first use
+    fn workspace_slot(&self, root: &Path) -> Option<WorkspaceSlot> {
+        let mut workspaces = self.workspaces.lock().ok()?;
+        Some(Arc::clone(
+            workspaces.entry(root.to_path_buf()).or_default(),
+        ))
+    }
 
-        if let Some(root_package) = load_root_package(&root).await
-            && !packages
-                .iter()
-                .any(|package| package.path == root_package.path)
-        {
-            packages.push(root_package);
-        }
+    /// Slot of `root` if a request already asked for it
+    fn loaded_slot(&self, root: &Path) -> Option<WorkspaceSlot> {
+        self.workspaces.lock().ok()?.get(root).cloned()
+    }
 
-        let mut task_packages: IndexMap<String, Vec<String>> = IndexMap::new();
-        let mut package_names = IndexSet::new();
+    /// Workspace state of `root` from its long-lived snapshot, reloading only files that changed
+    ///
+    /// With file watchers only reported files are reloaded, otherwise every tracked
+    /// file is compared. The turbo version is detected again only when the files it
+    /// is read from change, and without holding the lock since it may spawn
+    /// `turbo --version`.
+    async fn workspace_state(&self, root: &Path) -> Option<Arc<WorkspaceState>> {
+        let slot = self.workspace_slot(root)?;
+        let version_files = version_fingerprint(root).await;
+        let known_version = slot
+            .lock()
+            .await
+            .as_ref()
+            .filter(|cached| cached.version_files == version_files)
+            .map(|cached| cached.state.turbo_version);
+        let turbo_version = match known_version {
+            Some(turbo_version) => turbo_version,
+            None => turbo_core::version::detect(root).await,
+        };
 
-        for package in &packages {
-            let package_name = if package.path == root {
-                ROOT_PACKAGE_NAME.to_string()
+        let mut slot = slot.lock().await;
+        if let Some(cached) = slot.as_mut() {
+            let refresh = if self.watch_files_dynamically.load(Ordering::Relaxed) {
+                cached.snapshot.refresh_invalidated().await
             } else {
-                package.name.clone()
+                cached.snapshot.refresh().await
             };
-            package_names.insert(package_name.clone());
+            if !refresh.ok()?.is_empty() || cached.version_files != version_files {
+                cached.state = Arc::new(WorkspaceState::from_snapshot(
+                    &cached.snapshot,
+                    turbo_version,
+                ));
+                cached.version_files = version_files;
+            }
+            return Some(Arc::clone(&cached.state));
+        }
 
-            for script_name in package.scripts.keys() {
-                task_packages
-                    .entry(script_name.clone())
-                    .or_default()
-                    .push(package_name.clone());
+        let snapshot = WorkspaceSnapshot::load(root).await.ok()?;
+        let state = Arc::new(WorkspaceState::from_snapshot(&snapshot, turbo_version));
+        *slot = Some(CachedWorkspace::new(
+            snapshot,
+            Arc::clone(&state),
+            version_files,
+        ));
+        drop(slot);
+        Some(state)
+    }
+
+    /// Mark files reported by the client as changed in the workspaces containing them
+    ///
+    /// Returns whether cached glob results were dropped.
+    async fn invalidate_workspace_files(&self, changes: &[(PathBuf, FileChangeType)]) -> bool {
+        // a new or removed turbo.json / package.json can move a document to another root
+        if changes
+            .iter()
+            .any(|(path, _)| watched_files::is_workspace_config(path))
+            && let Ok(mut roots) = self.document_roots.lock()
+        {
+            roots.clear();
+        }
+        let slots: Vec<(PathBuf, WorkspaceSlot)> = match self.workspaces.lock() {
+            Ok(workspaces) => workspaces
+                .iter()
+                .map(|(root, slot)| (root.clone(), Arc::clone(slot)))
+                .collect(),
+            Err(_) => return false,
+        };
+        let mut globs_changed = false;
+        for (root, slot) in slots {
+            if let Some(cached) = slot.lock().await.as_mut() {
+                for (path, change) in changes {
+                    if path.starts_with(&root) {
+                        globs_changed |= cached.invalidate(path, *change);
+                    }
+                }
             }
         }
+        globs_changed
+    }
 
-        if let Ok(config) = TurboConfig::find_and_load(&root).await {
-            for task_name in config.task_names() {
-                task_packages.entry(task_name.to_string()).or_default();
+    /// Point the create/delete watchers at the directories cached glob results were
+    /// evaluated in, re-registering them when that set changed
+    async fn watch_glob_dirs(&self) {
+        let slots: Vec<WorkspaceSlot> = match self.workspaces.lock() {
+            Ok(workspaces) => workspaces.values().cloned().collect(),
+            Err(_) => return,
+        };
+        let mut dirs = BTreeSet::new();
+        for slot in slots {
+            if let Some(cached) = slot.lock().await.as_ref() {
+                dirs.extend(cached.glob_matches.dirs().map(Path::to_path_buf));
             }
         }
 
-        Some(WorkspaceState {
-            packages,
-            task_packages,
-            package_names,
+        let mut registered = self.glob_watch_dirs.lock().await;
+        if *registered == dirs {
+            return;
+        }
+        if !registered.is_empty() {
+            let _ = self
+                .client
+                .unregister_capability(vec![watched_files::glob_dirs_unregistration()])
+                .await;
+            registered.clear();
+        }
+        if dirs.is_empty() {
+            return;
+        }
+        let relative = self.relative_watch_patterns.load(Ordering::Relaxed);
+        let registration = watched_files::glob_dirs_registration(&dirs, relative);
+        let result = self.client.register_capability(vec![registration]).await;
+        if result.is_ok() {
+            *registered = dirs;
+        }
+        drop(registered);
+        if let Err(e) = result {
+            // nothing reports created files, so glob results may not be cached
+            self.watch_files_dynamically.store(false, Ordering::Relaxed);
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Failed to register glob directory watchers: {e}"),
+                )
+                .await;
+        }
+    }
+
+    /// Run history of `root`, re-read only when a summary was added to or removed from
+    /// `.turbo/runs`
+    async fn run_stats(&self, root: &Path) -> Option<Arc<RunStats>> {
+        let modified = tokio::fs::metadata(root.join(turbo_core::runs::RUNS_DIR))
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let slot = self.loaded_slot(root);
+        if let Some(slot) = &slot
+            && let Some(cached) = slot.lock().await.as_ref()
+            && let Some((cached_modified, stats)) = &cached.run_stats
+            && modified.is_some()
+            && *cached_modified == modified
+        {
+            return stats.clone();
+        }
+
+        let stats = RunStats::load(root).await.map(Arc::new);
+        if let Some(slot) = &slot
+            && let Some(cached) = slot.lock().await.as_mut()
+        {
+            cached.run_stats = Some((modified, stats.clone()));
+        }
+        stats
+    }
+
+    /// `turbo_core::env::collect` for each `(package, include_sources)`, scanning only
+    /// packages without a cached result
+    ///
+    /// Results are only cached while file watchers report changes.
+    async fn env_names(
+        &self,
+        root: &Path,
+        requests: Vec<(Package, bool)>,
+    ) -> Option<Vec<Arc<IndexMap<String, EnvSource>>>> {
+        let watching = self.watch_files_dynamically.load(Ordering::Relaxed);
+        let slot = self.loaded_slot(root).filter(|_| watching);
+        let (found, file_events) = {
+            let guard = match &slot {
+                Some(slot) => Some(slot.lock().await),
+                None => None,
+            };
+            let cached = guard.as_ref().and_then(|guard| guard.as_ref());
+            let found: Vec<_> = requests
+                .iter()
+                .map(|(package, sources)| {
+                    cached?
+                        .env_names
+                        .get(&(package.path.clone(), *sources))
+                        .cloned()
+                })
+                .collect();
+            let file_events = cached.map(|cached| cached.file_events);
+            drop(guard);
+            (found, file_events)
+        };
+
+        let missing: Vec<_> = requests
+            .into_iter()
+            .zip(&found)
+            .filter(|(_, found)| found.is_none())
+            .map(|(request, _)| request)
+            .collect();
+        let scanned = tokio::task::spawn_blocking(move || {
+            missing
+                .into_iter()
+                .map(|(package, sources)| {
+                    let names = Arc::new(turbo_core::env::collect(&package, sources));
+                    ((package.path, sources), names)
+                })
+                .collect::<Vec<_>>()
         })
+        .await
+        .ok()?;
+
+        if let Some(file_events) = file_events
+            && let Some(slot) = &slot
+            && let Some(cached) = slot.lock().await.as_mut()
+            && cached.file_events == file_events
+        {
+            cached.env_names.extend(scanned.iter().cloned());
+        }
+        let mut scanned = scanned.into_iter().map(|(_, names)| names);
+        found
+            .into_iter()
+            .map(|names| names.or_else(|| scanned.next()))
+            .collect()
     }
 
-    async fn publish_diagnostics(&self, uri: Url, version: Option<i32>) {
+    /// Drop snapshots of monorepos inside removed workspace folders
+    fn forget_workspaces(&self, removed: &[PathBuf]) {
+        if let Ok(mut workspaces) = self.workspaces.lock() {
+            workspaces.retain(|root, _| !removed.iter().any(|folder| root.starts_with(folder)));
+        }
+    }
+
+    /// Re-publish diagnostics of every open turbo.json, e.g. after a package.json changed
+    async fn republish_turbo_json_diagnostics(&self) {
+        let uris: Vec<Url> = match self.files.lock() {
+            Ok(files) => files
+                .keys()
+                .filter(|uri| watched_files::is_turbo_json(uri))
+                .cloned()
+                .collect(),
+            Err(_) => return,
+        };
+        for uri in uris {
+            self.publish_diagnostics(uri, None, true).await;
+        }
+    }
+
+    /// Publish diagnostics of an open document
+    ///
+    /// Globs are only matched against the file system when `scan_globs` is set
+    /// (open, save, watched files); while typing, cached results are reused.
+    async fn publish_diagnostics(&self, uri: Url, version: Option<i32>, scan_globs: bool) {
         let Some(text) = self.read_open_file(&uri) else {
             return;
         };
 
-        let diagnostics = self.collect_diagnostics(&text).await;
+        let mut diagnostics = self.collect_diagnostics(&uri, &text).await;
+        diagnostics.extend(self.glob_diagnostics(&uri, &text, scan_globs).await);
         self.client
             .publish_diagnostics(uri, diagnostics, version)
             .await;
     }
 
-    async fn collect_diagnostics(&self, text: &str) -> Vec<Diagnostic> {
-        let Some(workspace) = self.workspace_state().await else {
+    async fn collect_diagnostics(&self, uri: &Url, text: &str) -> Vec<Diagnostic> {
+        let Some(repo_root) = self.root_for(uri) else {
+            return Vec::new();
+        };
+        let Some(workspace) = self.workspace_state(&repo_root).await else {
             return Vec::new();
         };
 
@@ -212,12 +671,16 @@ impl TurboBackend {
             return Vec::new();
         };
 
-        let Some(root) = parse.value.as_ref().and_then(|value| value.as_object()) else {
+        let Some(value) = parse.value.as_ref() else {
+            return Vec::new();
+        };
+        let Some(root) = value.as_object() else {
             return Vec::new();
         };
 
         let rope = text;
-        let mut diagnostics = Vec::new();
+        let package = package_config::package_for_config(&workspace.packages, &repo_root, uri);
+        let mut diagnostics = document_diagnostics(rope, uri, value, package, &workspace);
 
         for task_group_name in ["tasks", "pipeline"] {
             let Some(task_group) = root.get_object(task_group_name) else {
@@ -225,7 +688,10 @@ impl TurboBackend {
             };
 
             for property in &task_group.properties {
-                if let ObjectPropName::String(name) = &property.name {
+                // package configs check their keys against the package's own scripts
+                if package.is_none()
+                    && let ObjectPropName::String(name) = &property.name
+                {
                     report_invalid_packages_and_tasks(&workspace, rope, &mut diagnostics, name);
                 }
 
@@ -298,8 +764,214 @@ impl TurboBackend {
         diagnostics
     }
 
-    async fn completions(&self) -> Option<Vec<CompletionItem>> {
-        let workspace = self.workspace_state().await?;
+    /// Globs that match nothing in the packages running their task
+    async fn glob_diagnostics(&self, uri: &Url, text: &str, scan: bool) -> Vec<Diagnostic> {
+        let entries = parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default())
+            .ok()
+            .and_then(|parse| Some(globs::entries(parse.value?.as_object()?)))
+            .unwrap_or_default();
+        if entries.is_empty() {
+            return Vec::new();
+        }
+        let Some(root) = self.root_for(uri) else {
+            return Vec::new();
+        };
+        let Some(workspace) = self.workspace_state(&root).await else {
+            return Vec::new();
+        };
+
+        let own = package_config::package_for_config(&workspace.packages, &root, uri);
+        let stats = self.run_stats(&root).await;
+        let checks: Vec<_> = entries
+            .into_iter()
+            .map(|entry| {
+                let task_name = entry.task_name.as_deref();
+                let bases = globs::bases(&workspace.packages, &root, task_name, own);
+                let label = stats_label(
+                    own.map(|own| own.name.as_str()),
+                    task_name.unwrap_or_default(),
+                );
+                let ran = stats
+                    .as_ref()
+                    .is_some_and(|stats| stats.for_label(&label).is_some());
+                (entry, bases, ran)
+            })
+            .collect();
+
+        // without watchers nothing invalidates the cache, so every scan starts over
+        let watching = self.watch_files_dynamically.load(Ordering::Relaxed);
+        let slot = self.loaded_slot(&root);
+        let (mut cache, file_events) = match &slot {
+            Some(slot) => slot.lock().await.as_ref().map(|cached| {
+                let cache = if scan && !watching {
+                    globs::MatchCache::default()
+                } else {
+                    cached.glob_matches.clone()
+                };
+                (cache, Some(cached.file_events))
+            }),
+            None => None,
+        }
+        .unwrap_or_default();
+        let Ok((problems, cache)) = tokio::task::spawn_blocking(move || {
+            let problems = checks
+                .into_iter()
+                .filter_map(|(entry, bases, ran)| {
+                    let problem = globs::check(&entry, &bases, &root, ran, &mut cache, scan)?;
+                    Some((entry, problem, bases))
+                })
+                .collect::<Vec<_>>();
+            (problems, cache)
+        })
+        .await
+        else {
+            return Vec::new();
+        };
+        if let Some(slot) = &slot
+            && let Some(cached) = slot.lock().await.as_mut()
+            && Some(cached.file_events) == file_events
+        {
+            cached.glob_matches = cache;
+        }
+        if scan && watching {
+            self.watch_glob_dirs().await;
+        }
+
+        problems
+            .iter()
+            .map(|(entry, problem, bases)| globs::diagnostic(text, entry, problem, bases))
+            .collect()
+    }
+
+    /// Whether a document is a package turbo.json rather than the root one
+    async fn is_package_config(&self, uri: &Url) -> bool {
+        let Some(root) = self.root_for(uri) else {
+            return false;
+        };
+        self.workspace_state(&root).await.is_some_and(|workspace| {
+            package_config::package_for_config(&workspace.packages, &root, uri).is_some()
+        })
+    }
+
+    async fn completions(&self, params: &CompletionParams) -> Option<Vec<CompletionItem>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let text = self.read_open_file(uri)?;
+        let offset = utf16_position_to_byte_offset(&text, params.text_document_position.position)?;
+        let context = completion::cursor_context(&text, offset)?;
+
+        match &context.slot {
+            Slot::Key if matches!(context.path.as_slice(), [group] if group == "tasks" || group == "pipeline") => {
+                self.task_label_items(uri).await
+            }
+            Slot::Key => Some(completion::key_items(
+                &text,
+                &context,
+                self.is_package_config(uri).await,
+            )),
+            Slot::Value(key) => Some(completion::value_items(
+                &text,
+                &context,
+                key,
+                self.is_package_config(uri).await,
+            )),
+            Slot::ArrayEntry(key) if key == "dependsOn" => {
+                let root = self.root_for(uri)?;
+                let workspace = self.workspace_state(&root).await?;
+                let package = package_config::package_for_config(&workspace.packages, &root, uri);
+                Some(completion::depends_on_items(
+                    &text, offset, &context, &workspace, &root, package,
+                ))
+            }
+            Slot::ArrayEntry(key) if completion::is_env_array(&context, key) => {
+                self.env_items(uri, &text, &context).await
+            }
+            Slot::ArrayEntry(key)
+                if (context.in_task() && globs::TASK_GLOB_KEYS.contains(&key.as_str()))
+                    || (context.path.is_empty()
+                        && globs::GLOBAL_GLOB_KEYS.contains(&key.as_str())) =>
+            {
+                let root = self.root_for(uri)?;
+                let workspace = self.workspace_state(&root).await?;
+                let own = package_config::package_for_config(&workspace.packages, &root, uri);
+                let task_name = context.in_task().then(|| context.path[1].as_str());
+                let bases: Vec<_> = globs::bases(&workspace.packages, &root, task_name, own)
+                    .into_iter()
+                    .map(|(_, dir)| dir)
+                    .collect();
+                Some(completion::path_items(
+                    &text, offset, &context, key, &bases, &root,
+                ))
+            }
+            Slot::ArrayEntry(_) => None,
+        }
+    }
+
+    /// Variable names from the packages a config or task applies to, then the root
+    ///
+    /// Sources are only scanned for those packages; for `globalEnv` every
+    /// package contributes its `.env*` names.
+    async fn env_items(
+        &self,
+        uri: &Url,
+        text: &str,
+        context: &CursorContext,
+    ) -> Option<Vec<CompletionItem>> {
+        let root = self.root_for(uri)?;
+        let workspace = self.workspace_state(&root).await?;
+        let own = package_config::package_for_config(&workspace.packages, &root, uri);
+        let task = context
+            .in_task()
+            .then(|| TaskReference::parse(&context.path[1]));
+
+        let mut ranked: Vec<(Package, u8)> = workspace
+            .packages
+            .iter()
+            .filter_map(|package| {
+                let relevant = own.map_or_else(
+                    || {
+                        task.as_ref().is_some_and(|task| {
+                            task.package.map_or_else(
+                                || package.scripts.contains_key(task.task),
+                                |name| name == package.name,
+                            )
+                        })
+                    },
+                    |own| own.path == package.path,
+                );
+                if relevant {
+                    Some((package.clone(), 0))
+                } else if package.path == root {
+                    Some((package.clone(), 1))
+                } else {
+                    task.is_none().then(|| (package.clone(), 2))
+                }
+            })
+            .collect();
+        ranked.sort_by_key(|(_, rank)| *rank);
+
+        let requests = ranked
+            .iter()
+            .map(|(package, rank)| (package.clone(), *rank == 0))
+            .collect();
+        let found = self.env_names(&root, requests).await?;
+
+        let mut names: IndexMap<String, EnvSuggestion> = IndexMap::new();
+        for ((package, rank), package_names) in ranked.iter().zip(found) {
+            for (name, source) in package_names.iter() {
+                names.entry(name.clone()).or_insert_with(|| EnvSuggestion {
+                    source: *source,
+                    package: package.name.clone(),
+                    rank: *rank,
+                });
+            }
+        }
+
+        Some(completion::env_items(text, context, &names))
+    }
+
+    /// Every task name and `package#task` label in the workspace
+    async fn task_label_items(&self, uri: &Url) -> Option<Vec<CompletionItem>> {
+        let workspace = self.workspace_state(&self.root_for(uri)?).await?;
         let mut seen = IndexSet::new();
         let mut items = Vec::new();
 
@@ -328,20 +1000,22 @@ impl TurboBackend {
     }
 
     async fn references(&self, params: &ReferenceParams) -> Option<Vec<Location>> {
-        let text = self.read_open_file(&params.text_document_position.text_document.uri)?;
+        let uri = &params.text_document_position.text_document.uri;
+        let text = self.read_open_file(uri)?;
         let offset = utf16_position_to_byte_offset(&text, params.text_document_position.position)?;
         let target = hover_target_for_offset(&text, offset)?;
         let label = task_target_label(&target)?;
-        self.script_locations_for_label(&label).await
+        self.script_locations_for_label(&self.root_for(uri)?, &label)
+            .await
     }
 
-    async fn script_locations_for_label(&self, label: &str) -> Option<Vec<Location>> {
-        let workspace = self.workspace_state().await?;
+    async fn script_locations_for_label(&self, root: &Path, label: &str) -> Option<Vec<Location>> {
+        let workspace = self.workspace_state(root).await?;
         let task_ref = TaskReference::parse(label);
 
         let mut locations = Vec::new();
-        for package in workspace.packages {
-            let package_name = if package.path == self.repo_root()? {
+        for package in &workspace.packages {
+            let package_name = if package.path == root {
                 ROOT_PACKAGE_NAME
             } else {
                 package.name.as_str()
@@ -371,6 +1045,284 @@ impl TurboBackend {
         Some(locations)
     }
 
+    /// Task definitions (as `package#task` in package configs) and package.json
+    /// scripts (as `package#script`) of every repo matching `query`
+    async fn workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        let mut found = Vec::new();
+        for root in self.repo_roots() {
+            let Some(workspace) = self.workspace_state(&root).await else {
+                continue;
+            };
+            let relative = |path: &Path| {
+                path.strip_prefix(&root)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string()
+            };
+
+            let package_dirs = workspace
+                .packages
+                .iter()
+                .filter(|package| package.path != root)
+                .map(|package| (Some(package.name.as_str()), package.path.as_path()));
+            for (package, dir) in std::iter::once((None, root.as_path())).chain(package_dirs) {
+                let mut config = None;
+                for name in CONFIG_FILES {
+                    let path = dir.join(name);
+                    if let Some(text) = self.read_file(&path).await {
+                        config = Some((path, text));
+                        break;
+                    }
+                }
+                let Some((path, text)) = config else {
+                    continue;
+                };
+                let Ok(uri) = Url::from_file_path(&path) else {
+                    continue;
+                };
+                for (task, range) in symbols::task_definitions(&text) {
+                    let name =
+                        package.map_or_else(|| task.clone(), |package| format!("{package}#{task}"));
+                    if symbols::matches(query, &name) {
+                        let location =
+                            Location::new(uri.clone(), byte_range_to_lsp_range(&text, range));
+                        found.push(symbols::information(name, location, relative(&path)));
+                    }
+                }
+            }
+
+            for package in &workspace.packages {
+                let package_name = if package.path == root {
+                    ROOT_PACKAGE_NAME
+                } else {
+                    package.name.as_str()
+                };
+                let label = |script: &str| format!("{package_name}#{script}");
+                if !package
+                    .scripts
+                    .keys()
+                    .any(|script| symbols::matches(query, &label(script)))
+                {
+                    continue;
+                }
+                let path = &package.package_json_path;
+                let (Some(content), Ok(uri)) =
+                    (self.read_file(path).await, Url::from_file_path(path))
+                else {
+                    continue;
+                };
+                for (script, range) in symbols::scripts(&content) {
+                    let name = label(&script);
+                    if symbols::matches(query, &name) {
+                        let location =
+                            Location::new(uri.clone(), byte_range_to_lsp_range(&content, range));
+                        found.push(symbols::information(name, location, relative(path)));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Task key or reference under the cursor, as the range of the plain task name
+    fn prepare_rename(&self, params: &TextDocumentPositionParams) -> Option<PrepareRenameResponse> {
+        let text = self.read_open_file(&params.text_document.uri)?;
+        let offset = utf16_position_to_byte_offset(&text, params.position)?;
+        let occurrence = rename::task_at(&text, offset)?;
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: byte_range_to_lsp_range(&text, occurrence.range),
+            placeholder: occurrence.task,
+        })
+    }
+
+    /// One edit renaming a task in every turbo.json and package.json script of the repo
+    ///
+    /// Conflicts are returned as an error so the client applies nothing.
+    async fn rename(
+        &self,
+        params: &RenameParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let Some(occurrence) = self.read_open_file(uri).and_then(|text| {
+            let offset =
+                utf16_position_to_byte_offset(&text, params.text_document_position.position)?;
+            rename::task_at(&text, offset)
+        }) else {
+            return Ok(None);
+        };
+        let Some(root) = self.root_for(uri) else {
+            return Ok(None);
+        };
+        let Some(workspace) = self.workspace_state(&root).await else {
+            return Ok(None);
+        };
+        let (old, new) = (occurrence.task.as_str(), params.new_name.as_str());
+
+        let mut config_paths = IndexSet::new();
+        if let Ok(path) = uri.to_file_path() {
+            config_paths.insert(path);
+        }
+        // turbo.json or turbo.jsonc; packages without one resolve to the root config
+        config_paths.extend(
+            std::iter::once(root.as_path())
+                .chain(
+                    workspace
+                        .packages
+                        .iter()
+                        .map(|package| package.path.as_path()),
+                )
+                .filter_map(|dir| TurboConfig::find_config_path(dir).ok()),
+        );
+        let mut configs = Vec::new();
+        for path in config_paths {
+            if let Some(text) = self.read_file(&path).await {
+                configs.push((path, text));
+            }
+        }
+
+        let texts: Vec<&str> = configs.iter().map(|(_, text)| text.as_str()).collect();
+        if let Some(message) = rename::conflicts(&workspace.packages, &texts, old, new) {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(message));
+        }
+
+        let mut edits: Vec<TextDocumentEdit> = configs
+            .iter()
+            .filter_map(|(path, text)| {
+                file_edit(path, text, rename::turbo_json_edits(text, old), new)
+            })
+            .collect();
+        for package in &workspace.packages {
+            if !package.scripts.contains_key(old) {
+                continue;
+            }
+            let Some(content) = self.read_file(&package.package_json_path).await else {
+                continue;
+            };
+            let ranges = rename::script_range(&content, old).into_iter().collect();
+            edits.extend(file_edit(&package.package_json_path, &content, ranges, new));
+        }
+
+        Ok(Some(WorkspaceEdit {
+            document_changes: Some(tower_lsp::lsp_types::DocumentChanges::Edits(edits)),
+            ..WorkspaceEdit::default()
+        }))
+    }
+
+    /// Task key or `dependsOn` entry under the cursor, as a node of the root task graph
+    ///
+    /// Tasks of a package config are labelled `package#task`.
+    async fn prepare_call_hierarchy(
+        &self,
+        params: &CallHierarchyPrepareParams,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let text = self.read_open_file(uri)?;
+        let offset =
+            utf16_position_to_byte_offset(&text, params.text_document_position_params.position)?;
+        let target = hover_target_for_offset(&text, offset)?;
+        let mut label = task_target_label(&target)?;
+
+        let root = self.root_for(uri)?;
+        if matches!(target, HoverTarget::TaskName(_)) && !label.contains('#') {
+            let workspace = self.workspace_state(&root).await?;
+            if let Some(package) =
+                package_config::package_for_config(&workspace.packages, &root, uri)
+            {
+                label = format!("{}#{label}", package.name);
+            }
+        }
+        Some(vec![self.call_hierarchy_item(&root, &label).await?])
+    }
+
+    /// Tasks whose `dependsOn` reaches the item, including through `^` in dependent packages
+    async fn incoming_calls(
+        &self,
+        item: &CallHierarchyItem,
+    ) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let (root, label) = call_hierarchy_data(item)?;
+        let workspace = self.workspace_state(&root).await?;
+        let text = self
+            .read_file(&TurboConfig::find_config_path(&root).ok()?)
+            .await?;
+
+        let mut calls = Vec::new();
+        for (caller, ranges) in call_hierarchy::incoming(&text, &label, &workspace.packages) {
+            let Some(from) = self.call_hierarchy_item(&root, &caller).await else {
+                continue;
+            };
+            calls.push(CallHierarchyIncomingCall {
+                from,
+                from_ranges: ranges
+                    .into_iter()
+                    .map(|range| byte_range_to_lsp_range(&text, range))
+                    .collect(),
+            });
+        }
+        Some(calls)
+    }
+
+    /// Tasks the item depends on, with `^` expanded to workspace dependencies for package tasks
+    async fn outgoing_calls(
+        &self,
+        item: &CallHierarchyItem,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let (root, label) = call_hierarchy_data(item)?;
+        let workspace = self.workspace_state(&root).await?;
+        let text = self
+            .read_file(&TurboConfig::find_config_path(&root).ok()?)
+            .await?;
+
+        let mut calls = Vec::new();
+        for (callee, ranges) in call_hierarchy::outgoing(&text, &label, &workspace.packages) {
+            let Some(to) = self.call_hierarchy_item(&root, &callee).await else {
+                continue;
+            };
+            calls.push(CallHierarchyOutgoingCall {
+                to,
+                from_ranges: ranges
+                    .into_iter()
+                    .map(|range| byte_range_to_lsp_range(&text, range))
+                    .collect(),
+            });
+        }
+        Some(calls)
+    }
+
+    /// Node for `label`, located at its root turbo.json(c) definition or else its first script
+    async fn call_hierarchy_item(&self, root: &Path, label: &str) -> Option<CallHierarchyItem> {
+        let definition = match TurboConfig::find_config_path(root) {
+            Ok(path) => self.read_file(&path).await.and_then(|text| {
+                let range = call_hierarchy::definition_range(&text, label.trim_start_matches('^'))?;
+                let uri = Url::from_file_path(&path).ok()?;
+                Some(Location::new(uri, byte_range_to_lsp_range(&text, range)))
+            }),
+            Err(_) => None,
+        };
+        let location = match definition {
+            Some(location) => location,
+            None => self
+                .script_locations_for_label(root, label.trim_start_matches('^'))
+                .await?
+                .into_iter()
+                .next()?,
+        };
+
+        Some(CallHierarchyItem {
+            name: label.to_string(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            detail: location.uri.to_file_path().ok().and_then(|file| {
+                file.strip_prefix(root)
+                    .ok()
+                    .map(|file| file.display().to_string())
+            }),
+            uri: location.uri,
+            range: location.range,
+            selection_range: location.range,
+            data: Some(serde_json::json!({ "root": root, "label": label })),
+        })
+    }
+
     async fn goto_definition(
         &self,
         params: &GotoDefinitionParams,
@@ -386,7 +1338,9 @@ impl TurboBackend {
             return Some(GotoDefinitionResponse::Scalar(location));
         }
 
-        let script_locations = self.script_locations_for_label(&label).await?;
+        let script_locations = self
+            .script_locations_for_label(&self.root_for(uri)?, &label)
+            .await?;
         match script_locations.as_slice() {
             [] => None,
             [location] => Some(GotoDefinitionResponse::Scalar(location.clone())),
@@ -394,12 +1348,109 @@ impl TurboBackend {
         }
     }
 
-    fn code_lens(&self, uri: &Url) -> Option<Vec<CodeLens>> {
+    /// Run, dry-run and graph lenses per task; history and package lenses resolve lazily
+    async fn code_lens(&self, uri: &Url) -> Option<Vec<CodeLens>> {
         let text = self.read_open_file(uri)?;
+        let (filter, has_runs) = match self.root_for(uri) {
+            Some(root) => {
+                // tasks of a package config run only in that package
+                let filter = self.workspace_state(&root).await.and_then(|workspace| {
+                    package_config::package_for_config(&workspace.packages, &root, uri)
+                        .map(|package| package.name.clone())
+                });
+                (filter, root.join(turbo_core::runs::RUNS_DIR).is_dir())
+            }
+            None => (None, false),
+        };
+
+        let mut items = Vec::new();
+        for (task, range) in symbols::task_definitions(&text) {
+            let range = byte_range_to_lsp_range(&text, range);
+            let generic = !task.contains('#');
+            items.extend(
+                code_lens::run_commands(&task, uri, filter.as_deref())
+                    .into_iter()
+                    .map(|command| CodeLens {
+                        range,
+                        command: Some(command),
+                        data: None,
+                    }),
+            );
+            if generic && filter.is_none() {
+                items.push(code_lens::lazy(Lazy::Packages, &task, uri, range));
+            }
+            if has_runs {
+                let label = stats_label(filter.as_deref(), &task);
+                items.push(code_lens::lazy(Lazy::LastRun, &label, uri, range));
+                if generic {
+                    items.push(code_lens::lazy(Lazy::CriticalPath, &task, uri, range));
+                }
+            }
+        }
+        Some(items)
+    }
+
+    /// Fill in a lazy lens from the run history or workspace packages
+    async fn resolve_code_lens(&self, mut lens: CodeLens) -> CodeLens {
+        let Some((kind, task, uri)) = code_lens::lazy_data(&lens) else {
+            return lens;
+        };
+        let Some(root) = self.root_for(&uri) else {
+            return lens;
+        };
+        let command = match kind {
+            Lazy::LastRun => self
+                .run_stats(&root)
+                .await
+                .and_then(|stats| stats.last_run(&task))
+                .map_or_else(
+                    || code_lens::label("No recorded run".to_string()),
+                    |last| code_lens::label(run_stats::last_run_title(&last)),
+                ),
+            Lazy::CriticalPath => self
+                .run_stats(&root)
+                .await
+                .and_then(|stats| stats.critical_path(&task))
+                .map_or_else(
+                    || code_lens::label("No critical path recorded".to_string()),
+                    |analysis| Command {
+                        title: run_stats::critical_path_title(&analysis),
+                        command: "turbo.criticalPath".to_string(),
+                        arguments: Some(vec![
+                            serde_json::Value::String(task.clone()),
+                            serde_json::Value::String(uri.to_string()),
+                        ]),
+                    },
+                ),
+            Lazy::Packages => {
+                let count = self
+                    .workspace_state(&root)
+                    .await
+                    .and_then(|workspace| workspace.task_packages.get(&task).map(Vec::len))
+                    .unwrap_or_default();
+                code_lens::packages_command(&task, &uri, count)
+            }
+        };
+        lens.command = Some(command);
+        lens
+    }
+
+    async fn inlay_hints(&self, uri: &Url) -> Option<Vec<InlayHint>> {
+        let text = self.read_open_file(uri)?;
+        let repo_root = self.root_for(uri)?;
+        let stats = self.run_stats(&repo_root).await?;
+        // a package turbo.json shows that package's runs, not all of them
+        let package = self
+            .workspace_state(&repo_root)
+            .await
+            .and_then(|workspace| {
+                package_config::package_for_config(&workspace.packages, &repo_root, uri)
+                    .map(|package| package.name.clone())
+            });
         let parse =
             parse_to_ast(&text, &CollectOptions::default(), &ParseOptions::default()).ok()?;
         let root = parse.value.as_ref()?.as_object()?;
-        let mut items = Vec::new();
+        let mut hints = Vec::new();
 
         for task_group_name in ["tasks", "pipeline"] {
             let Some(task_group) = root.get_object(task_group_name) else {
@@ -407,22 +1458,142 @@ impl TurboBackend {
             };
 
             for property in &task_group.properties {
-                let range = key_range(property.range, property.name.as_str().len());
-                items.push(CodeLens {
-                    command: Some(Command {
-                        title: format!("Run {}", property.name.as_str()),
-                        command: "turbo.run".to_string(),
-                        arguments: Some(vec![serde_json::Value::String(
-                            property.name.as_str().to_string(),
-                        )]),
-                    }),
-                    range: byte_range_to_lsp_range(&text, range),
+                let name = property.name.as_str();
+                let Some(task_stats) = stats.for_label(&stats_label(package.as_deref(), name))
+                else {
+                    continue;
+                };
+                // Place the hint right after the key's closing quote
+                let end = key_range(property.range, name.len()).end + 1;
+                hints.push(InlayHint {
+                    position: byte_offset_to_position(&text, end),
+                    label: InlayHintLabel::String(run_stats::inlay_label(task_stats)),
+                    kind: None,
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: Some(true),
+                    padding_right: None,
                     data: None,
                 });
             }
         }
 
-        Some(items)
+        Some(hints)
+    }
+
+    /// Start `turbo run` in the background; output and the result reach the client as messages
+    async fn run_task(&self, request: RunRequest) {
+        let Some(root) = self.run_root(&request).await else {
+            return;
+        };
+        tokio::spawn(task_run::run(
+            self.client.clone(),
+            Arc::clone(&self.runs),
+            root,
+            request,
+            self.work_done_progress.load(Ordering::Relaxed),
+        ));
+    }
+
+    /// `turbo run <task> --graph` into `.turbo/graph/`, opened externally once written
+    async fn show_graph(&self, mut request: RunRequest) {
+        let Some(root) = self.run_root(&request).await else {
+            return;
+        };
+        let dir = root.join(".turbo/graph");
+        if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+            self.client
+                .show_message(
+                    MessageType::ERROR,
+                    format!("Cannot create {}: {e}", dir.display()),
+                )
+                .await;
+            return;
+        }
+        let file_name = request.task.replace(['#', '/', ':'], "-");
+        request.graph = Some(dir.join(format!("{file_name}.html")));
+        self.run_task(request).await;
+    }
+
+    /// Ask which package defining the task to run it in, then run it filtered to that package
+    async fn run_in_package(&self, mut request: RunRequest) {
+        let Some(root) = self.run_root(&request).await else {
+            return;
+        };
+        let Some(workspace) = self.workspace_state(&root).await else {
+            return;
+        };
+        let actions = workspace
+            .task_packages
+            .get(&request.task)
+            .into_iter()
+            .flatten()
+            .map(|package| MessageActionItem {
+                title: package.clone(),
+                properties: std::iter::empty().collect(),
+            })
+            .collect();
+        let choice = self
+            .client
+            .show_message_request(
+                MessageType::INFO,
+                format!("Run `{}` in which package?", request.task),
+                Some(actions),
+            )
+            .await;
+        if let Ok(Some(package)) = choice {
+            request.filter = Some(package.title);
+            self.run_task(request).await;
+        }
+    }
+
+    /// Repo root a command runs in: its document's root, else the first workspace folder's
+    async fn run_root(&self, request: &RunRequest) -> Option<PathBuf> {
+        let root = request
+            .uri
+            .as_ref()
+            .and_then(|uri| self.root_for(uri))
+            .or_else(|| self.default_root());
+        if root.is_none() {
+            self.client
+                .show_message(
+                    MessageType::ERROR,
+                    format!("No turbo repo to run `{}` in.", request.task),
+                )
+                .await;
+        }
+        root
+    }
+
+    /// `window/workDoneProgress/cancel`: stop the run reporting under that token
+    #[allow(clippy::unused_async)] // `custom_method` handlers return futures
+    async fn cancel_progress(&self, params: WorkDoneProgressCancelParams) {
+        task_run::cancel(&self.runs, &params.token);
+    }
+
+    async fn show_critical_path(&self, arguments: &[serde_json::Value]) {
+        let Some(task) = arguments.first().and_then(serde_json::Value::as_str) else {
+            return;
+        };
+        // the lens passes the document so multi-root windows use the right repo
+        let root = arguments
+            .get(1)
+            .and_then(serde_json::Value::as_str)
+            .and_then(|uri| Url::parse(uri).ok())
+            .and_then(|uri| self.root_for(&uri))
+            .or_else(|| self.default_root());
+        let Some(root) = root else {
+            return;
+        };
+        let report = self
+            .run_stats(&root)
+            .await
+            .and_then(|stats| stats.critical_path(task))
+            .map_or_else(
+                || format!("No run summaries for `{task}`; run `turbo run {task} --summarize`."),
+                |analysis| run_stats::critical_path_report(task, &analysis),
+            );
+        self.client.show_message(MessageType::INFO, report).await;
     }
 
     fn quickfixes(&self, params: &CodeActionParams) -> Vec<CodeActionOrCommand> {
@@ -432,6 +1603,11 @@ impl TurboBackend {
 
         let mut actions = Vec::new();
         for diagnostic in &params.context.diagnostics {
+            if let Some(action) = replacement_action(&params.text_document.uri, diagnostic) {
+                actions.push(action);
+                continue;
+            }
+
             let Some(NumberOrString::String(code)) = &diagnostic.code else {
                 continue;
             };
@@ -450,40 +1626,160 @@ impl TurboBackend {
                 range: byte_range_to_lsp_range(&text, (start - 1)..start),
                 new_text: String::new(),
             };
-            let workspace_edit = WorkspaceEdit {
-                changes: None,
-                document_changes: Some(tower_lsp::lsp_types::DocumentChanges::Edits(vec![
-                    TextDocumentEdit {
-                        text_document: OptionalVersionedTextDocumentIdentifier {
-                            uri: params.text_document.uri.clone(),
-                            version: None,
-                        },
-                        edits: vec![OneOf::Left(edit)],
-                    },
-                ])),
-                change_annotations: None,
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Remove deprecated `$` prefix".to_string(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                is_preferred: Some(true),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(document_edit(&params.text_document.uri, vec![edit])),
+                ..CodeAction::default()
+            }));
+        }
+
+        actions
+    }
+
+    /// `turbo-effective:` URI of the package containing a document
+    fn effective_config_uri(&self, arguments: &[serde_json::Value]) -> Option<Url> {
+        let uri = Url::parse(arguments.first()?.as_str()?).ok()?;
+        let root = self.root_for(&uri)?;
+        let dir = uri.to_file_path().ok()?.parent()?.to_path_buf();
+        let relative = dir.strip_prefix(&root).ok()?;
+        effective_config::uri_for(&root, &relative.to_string_lossy())
+    }
+
+    /// Resolved task definitions of the package an effective config URI points at
+    ///
+    /// URIs without a root are looked up in every workspace folder's monorepo.
+    async fn effective_config(&self, uri: &Url) -> Option<String> {
+        let relative = effective_config::package_dir(uri)?;
+        let roots = match effective_config::repo_root(uri) {
+            Some(root) if TurboConfig::find_repo_root(&root).as_ref() == Some(&root) => {
+                vec![root]
+            }
+            Some(_) => return None,
+            None => self.repo_roots(),
+        };
+        for root in roots {
+            let dir = root.join(&relative);
+            let Some(workspace) = self.workspace_state(&root).await else {
+                continue;
+            };
+            let Some(package) = workspace
+                .packages
+                .iter()
+                .find(|package| package.path == dir)
+            else {
+                continue;
+            };
+
+            let Ok(config_path) = TurboConfig::find_config_path(&root) else {
+                continue;
+            };
+            let Some(root_config) = self
+                .read_file(&config_path)
+                .await
+                .and_then(|text| parse_jsonc_value(&text))
+            else {
+                continue;
             };
+            let mut package_config = None;
+            for name in CONFIG_FILES {
+                if dir != root
+                    && let Some(text) = self.read_file(&dir.join(name)).await
+                {
+                    package_config = parse_jsonc_value(&text);
+                    break;
+                }
+            }
+
+            let tasks =
+                effective_config::resolve(&root_config, package_config.as_ref(), &package.name);
+            return Some(effective_config::render(&package.name, &tasks));
+        }
+        None
+    }
 
-            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-                title: "Remove deprecated `$` prefix".to_string(),
-                kind: Some(CodeActionKind::QUICKFIX),
-                is_preferred: Some(true),
-                diagnostics: Some(vec![diagnostic.clone()]),
-                edit: Some(workspace_edit),
-                ..CodeAction::default()
-            }));
+    /// `turbo.effectiveConfig` command: write the effective config of a document's package
+    /// to a read-only file outside the repo and open it, returning the `turbo-effective:` URI
+    ///
+    /// The file is regenerated on every invocation.
+    async fn open_effective_config(&self, arguments: &[serde_json::Value]) -> Option<Url> {
+        let uri = self.effective_config_uri(arguments)?;
+        let text = self.effective_config(&uri).await?;
+        let document = Url::parse(arguments.first()?.as_str()?).ok()?;
+        let Some(dir) = effective_snapshot_dir(&self.root_for(&document)?) else {
+            self.client
+                .show_message(
+                    MessageType::ERROR,
+                    "No per-user directory to write the effective config to",
+                )
+                .await;
+            return Some(uri);
+        };
+        let path = dir.join(effective_config::snapshot_file_name(&uri)?);
+        if !write_read_only(&dir, &path, &text).await {
+            self.client
+                .show_message(
+                    MessageType::ERROR,
+                    format!("Cannot write {}", path.display()),
+                )
+                .await;
+            return Some(uri);
+        }
+        if let Ok(file) = Url::from_file_path(&path) {
+            let _ = self
+                .client
+                .show_document(ShowDocumentParams {
+                    uri: file,
+                    external: None,
+                    take_focus: Some(true),
+                    selection: None,
+                })
+                .await;
         }
+        Some(uri)
+    }
 
-        actions
+    /// `turbo/effectiveConfig` request: the text of a `turbo-effective:` document
+    async fn effective_config_request(
+        &self,
+        params: TextDocumentIdentifier,
+    ) -> tower_lsp::jsonrpc::Result<String> {
+        self.effective_config(&params.uri).await.ok_or_else(|| {
+            tower_lsp::jsonrpc::Error::invalid_params(format!("No package for {}", params.uri))
+        })
     }
 
     async fn hover_markdown(&self, params: &HoverParams) -> Option<Hover> {
-        let text = self.read_open_file(&params.text_document_position_params.text_document.uri)?;
+        let uri = &params.text_document_position_params.text_document.uri;
+        let text = self.read_open_file(uri)?;
         let offset =
             utf16_position_to_byte_offset(&text, params.text_document_position_params.position)?;
         let target = hover_target_for_offset(&text, offset)?;
-        let repo_root = self.repo_root();
-        let markdown = build_hover_markdown(repo_root.as_deref(), &target).await;
+        let repo_root = self.root_for(uri);
+        let context = match &repo_root {
+            Some(root) => self
+                .workspace_state(root)
+                .await
+                .map(|workspace| HoverContext {
+                    package_name: package_config::package_for_config(
+                        &workspace.packages,
+                        root,
+                        uri,
+                    )
+                    .map(|package| package.name.clone()),
+                    packages: workspace.packages.clone(),
+                    root_path: root.clone(),
+                    root_config: workspace.root_config.clone(),
+                }),
+            None => None,
+        };
+        let stats = match (&repo_root, &target) {
+            (Some(root), HoverTarget::TaskName(_)) => self.run_stats(root).await,
+            _ => None,
+        };
+        let markdown = build_hover_markdown(stats.as_deref(), context.as_ref(), &target).await;
 
         Some(Hover {
             contents: HoverContents::Markup(MarkupContent {
@@ -501,7 +1797,19 @@ impl tower_lsp::LanguageServer for TurboBackend {
         &self,
         params: InitializeParams,
     ) -> tower_lsp::jsonrpc::Result<InitializeResult> {
-        self.remember_root(&params);
+        self.remember_workspace_folders(&params);
+        self.watch_files_dynamically.store(
+            watched_files::supports_dynamic_registration(&params),
+            Ordering::Relaxed,
+        );
+        self.relative_watch_patterns.store(
+            watched_files::supports_relative_patterns(&params),
+            Ordering::Relaxed,
+        );
+        self.work_done_progress.store(
+            task_run::supports_work_done_progress(&params),
+            Ordering::Relaxed,
+        );
 
         Ok(InitializeResult {
             server_info: Some(ServerInfo {
@@ -528,8 +1836,16 @@ impl tower_lsp::LanguageServer for TurboBackend {
                 references_provider: Some(OneOf::Right(ReferencesOptions {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 })),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
                 code_lens_provider: Some(CodeLensOptions {
-                    resolve_provider: None,
+                    resolve_provider: Some(true),
                 }),
                 code_action_provider: Some(CodeActionProviderCapability::Options(
                     tower_lsp::lsp_types::CodeActionOptions {
@@ -539,7 +1855,13 @@ impl tower_lsp::LanguageServer for TurboBackend {
                     },
                 )),
                 execute_command_provider: Some(ExecuteCommandOptions {
-                    commands: vec!["turbo.run".to_string()],
+                    commands: vec![
+                        "turbo.run".to_string(),
+                        "turbo.graph".to_string(),
+                        "turbo.runInPackage".to_string(),
+                        "turbo.criticalPath".to_string(),
+                        "turbo.effectiveConfig".to_string(),
+                    ],
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
                 workspace: Some(WorkspaceServerCapabilities {
@@ -554,7 +1876,23 @@ impl tower_lsp::LanguageServer for TurboBackend {
         })
     }
 
-    async fn initialized(&self, _: InitializedParams) {}
+    async fn initialized(&self, _: InitializedParams) {
+        if self.watch_files_dynamically.load(Ordering::Relaxed)
+            && let Err(e) = self
+                .client
+                .register_capability(vec![watched_files::registration()])
+                .await
+        {
+            // nothing reports changes, so nothing derived from files may be cached
+            self.watch_files_dynamically.store(false, Ordering::Relaxed);
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Failed to register file watchers: {e}"),
+                )
+                .await;
+        }
+    }
 
     async fn shutdown(&self) -> tower_lsp::jsonrpc::Result<()> {
         Ok(())
@@ -562,8 +1900,12 @@ impl tower_lsp::LanguageServer for TurboBackend {
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         self.remember_open_file(&params);
-        self.publish_diagnostics(params.text_document.uri, Some(params.text_document.version))
-            .await;
+        self.publish_diagnostics(
+            params.text_document.uri,
+            Some(params.text_document.version),
+            true,
+        )
+        .await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -571,12 +1913,13 @@ impl tower_lsp::LanguageServer for TurboBackend {
         self.publish_diagnostics(
             params.text_document.uri.clone(),
             Some(params.text_document.version),
+            false,
         )
         .await;
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
-        self.publish_diagnostics(params.text_document.uri, None)
+        self.publish_diagnostics(params.text_document.uri, None, true)
             .await;
     }
 
@@ -589,9 +1932,12 @@ impl tower_lsp::LanguageServer for TurboBackend {
 
     async fn completion(
         &self,
-        _: CompletionParams,
+        params: CompletionParams,
     ) -> tower_lsp::jsonrpc::Result<Option<CompletionResponse>> {
-        Ok(self.completions().await.map(CompletionResponse::Array))
+        Ok(self
+            .completions(&params)
+            .await
+            .map(CompletionResponse::Array))
     }
 
     async fn references(
@@ -608,6 +1954,57 @@ impl tower_lsp::LanguageServer for TurboBackend {
         Ok(self.goto_definition(&params).await)
     }
 
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<DocumentSymbolResponse>> {
+        Ok(self
+            .read_open_file(&params.text_document.uri)
+            .map(|text| DocumentSymbolResponse::Nested(symbols::document_symbols(&text))))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<SymbolInformation>>> {
+        Ok(Some(self.workspace_symbols(&params.query).await))
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<PrepareRenameResponse>> {
+        Ok(self.prepare_rename(&params))
+    }
+
+    async fn rename(
+        &self,
+        params: RenameParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        self.rename(&params).await
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<CallHierarchyItem>>> {
+        Ok(self.prepare_call_hierarchy(&params).await)
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        Ok(self.incoming_calls(&params.item).await)
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        Ok(self.outgoing_calls(&params.item).await)
+    }
+
     async fn hover(&self, params: HoverParams) -> tower_lsp::jsonrpc::Result<Option<Hover>> {
         Ok(self.hover_markdown(&params).await)
     }
@@ -616,7 +2013,18 @@ impl tower_lsp::LanguageServer for TurboBackend {
         &self,
         params: CodeLensParams,
     ) -> tower_lsp::jsonrpc::Result<Option<Vec<CodeLens>>> {
-        Ok(self.code_lens(&params.text_document.uri))
+        Ok(self.code_lens(&params.text_document.uri).await)
+    }
+
+    async fn code_lens_resolve(&self, params: CodeLens) -> tower_lsp::jsonrpc::Result<CodeLens> {
+        Ok(self.resolve_code_lens(params).await)
+    }
+
+    async fn inlay_hint(
+        &self,
+        params: InlayHintParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<InlayHint>>> {
+        Ok(self.inlay_hints(&params.text_document.uri).await)
     }
 
     async fn code_action(
@@ -628,16 +2036,59 @@ impl tower_lsp::LanguageServer for TurboBackend {
 
     async fn execute_command(
         &self,
-        _: ExecuteCommandParams,
+        params: ExecuteCommandParams,
     ) -> tower_lsp::jsonrpc::Result<Option<serde_json::Value>> {
+        match params.command.as_str() {
+            "turbo.run" | "turbo.graph" | "turbo.runInPackage" => {
+                let Some(request) = RunRequest::from_arguments(&params.arguments) else {
+                    return Ok(None);
+                };
+                match params.command.as_str() {
+                    "turbo.graph" => self.show_graph(request).await,
+                    "turbo.runInPackage" => self.run_in_package(request).await,
+                    _ => self.run_task(request).await,
+                }
+            }
+            "turbo.criticalPath" => self.show_critical_path(&params.arguments).await,
+            "turbo.effectiveConfig" => {
+                return Ok(self
+                    .open_effective_config(&params.arguments)
+                    .await
+                    .map(|uri| serde_json::Value::String(uri.to_string())));
+            }
+            _ => {}
+        }
         Ok(None)
     }
 
-    async fn did_change_workspace_folders(&self, _: DidChangeWorkspaceFoldersParams) {}
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let removed = self.change_workspace_folders(&params);
+        self.forget_workspaces(&removed);
+        self.republish_turbo_json_diagnostics().await;
+    }
 
     async fn did_change_configuration(&self, _: DidChangeConfigurationParams) {}
 
-    async fn did_change_watched_files(&self, _: DidChangeWatchedFilesParams) {}
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let changes: Vec<(PathBuf, FileChangeType)> = params
+            .changes
+            .iter()
+            .filter(|change| watched_files::is_workspace_file(&change.uri))
+            .filter_map(|change| Some((change.uri.to_file_path().ok()?, change.typ)))
+            .collect();
+        if changes.is_empty() {
+            return;
+        }
+        // glob diagnostics depend on which files exist, but only where globs look
+        let globs_changed = self.invalidate_workspace_files(&changes).await;
+        if globs_changed
+            || changes
+                .iter()
+                .any(|(path, _)| watched_files::is_workspace_config(path))
+        {
+            self.republish_turbo_json_diagnostics().await;
+        }
+    }
 }
 
 fn apply_content_changes(text: &mut String, changes: &[TextDocumentContentChangeEvent]) {
@@ -655,6 +2106,71 @@ fn apply_content_changes(text: &mut String, changes: &[TextDocumentContentChange
     }
 }
 
+/// Quick fix for diagnostics carrying `{"title", "newText"}` (replacing the
+/// diagnostic range) or `{"title", "edits"}` in `data`
+fn replacement_action(uri: &Url, diagnostic: &Diagnostic) -> Option<CodeActionOrCommand> {
+    let data = diagnostic.data.as_ref()?;
+    let title = data.get("title")?.as_str()?;
+    let edits = match data.get("edits") {
+        Some(edits) => serde_json::from_value(edits.clone()).ok()?,
+        None => vec![TextEdit {
+            range: diagnostic.range,
+            new_text: data.get("newText")?.as_str()?.to_string(),
+        }],
+    };
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.to_string(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        is_preferred: Some(true),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(document_edit(uri, edits)),
+        ..CodeAction::default()
+    }))
+}
+
+fn document_edit(uri: &Url, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEdit {
+        changes: None,
+        document_changes: Some(tower_lsp::lsp_types::DocumentChanges::Edits(vec![
+            TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: None,
+                },
+                edits: edits.into_iter().map(OneOf::Left).collect(),
+            },
+        ])),
+        change_annotations: None,
+    }
+}
+
+/// Edit replacing `ranges` of a file with `new_text`, if there is anything to replace
+fn file_edit(
+    path: &Path,
+    text: &str,
+    ranges: Vec<std::ops::Range<usize>>,
+    new_text: &str,
+) -> Option<TextDocumentEdit> {
+    if ranges.is_empty() {
+        return None;
+    }
+    Some(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier {
+            uri: Url::from_file_path(path).ok()?,
+            version: None,
+        },
+        edits: ranges
+            .into_iter()
+            .map(|range| {
+                OneOf::Left(TextEdit {
+                    range: byte_range_to_lsp_range(text, range),
+                    new_text: new_text.to_string(),
+                })
+            })
+            .collect(),
+    })
+}
+
 fn lsp_range_to_byte_range(text: &str, range: Range) -> Option<std::ops::Range<usize>> {
     let start = utf16_position_to_byte_offset(text, range.start)?;
     let end = utf16_position_to_byte_offset(text, range.end)?;
@@ -714,6 +2230,16 @@ fn hover_target_for_offset(text: &str, offset: usize) -> Option<HoverTarget> {
             return Some(HoverTarget::TopLevelKey(property.name.as_str().to_string()));
         }
 
+        if globs::GLOBAL_GLOB_KEYS.contains(&property.name.as_str())
+            && let Some(entry) = array_entry_at(&property.value, offset)
+        {
+            return Some(HoverTarget::GlobEntry {
+                task_name: None,
+                field_name: property.name.as_str().to_string(),
+                entry,
+            });
+        }
+
         if matches!(property.name.as_str(), "tasks" | "pipeline")
             && let Some(tasks) = property.value.as_object()
         {
@@ -731,19 +2257,21 @@ fn hover_target_for_offset(text: &str, offset: usize) -> Option<HoverTarget> {
                             });
                         }
 
-                        if field.name.as_str() == "dependsOn"
-                            && let Some(array) = field.value.as_array()
-                        {
-                            for element in &array.elements {
-                                if let Some(string) = element.as_string_lit()
-                                    && collapse_string_range(string.range).contains(&offset)
-                                {
-                                    return Some(HoverTarget::DependsOnEntry {
-                                        task_name: task.name.as_str().to_string(),
-                                        entry: string.value.to_string(),
-                                    });
-                                }
-                            }
+                        let Some(entry) = array_entry_at(&field.value, offset) else {
+                            continue;
+                        };
+                        if field.name.as_str() == "dependsOn" {
+                            return Some(HoverTarget::DependsOnEntry {
+                                task_name: task.name.as_str().to_string(),
+                                entry,
+                            });
+                        }
+                        if globs::TASK_GLOB_KEYS.contains(&field.name.as_str()) {
+                            return Some(HoverTarget::GlobEntry {
+                                task_name: Some(task.name.as_str().to_string()),
+                                field_name: field.name.as_str().to_string(),
+                                entry,
+                            });
                         }
                     }
                 }
@@ -754,6 +2282,17 @@ fn hover_target_for_offset(text: &str, offset: usize) -> Option<HoverTarget> {
     None
 }
 
+/// String entry of an array value under the cursor
+fn array_entry_at(value: &Value<'_>, offset: usize) -> Option<String> {
+    value
+        .as_array()?
+        .elements
+        .iter()
+        .filter_map(Value::as_string_lit)
+        .find(|string| collapse_string_range(string.range).contains(&offset))
+        .map(|string| string.value.to_string())
+}
+
 const fn key_range(range: JsonRange, key_len: usize) -> std::ops::Range<usize> {
     let start = range.start + 1;
     let end = start + key_len;
@@ -764,6 +2303,14 @@ const fn collapse_string_range(range: JsonRange) -> std::ops::Range<usize> {
     (range.start + 1)..range.end.saturating_sub(1)
 }
 
+/// Byte range of a property name, without quotes
+const fn name_range(name: &ObjectPropName<'_>) -> std::ops::Range<usize> {
+    match name {
+        ObjectPropName::String(name) => collapse_string_range(name.range),
+        ObjectPropName::Word(name) => name.range.start..name.range.end,
+    }
+}
+
 fn byte_range_to_lsp_range(text: &str, range: std::ops::Range<usize>) -> Range {
     Range {
         start: byte_offset_to_position(text, range.start),
@@ -806,10 +2353,21 @@ fn task_target_label(target: &HoverTarget) -> Option<String> {
                 .trim_start_matches('$')
                 .to_string(),
         ),
-        HoverTarget::TaskField { .. } | HoverTarget::TopLevelKey(_) => None,
+        HoverTarget::TaskField { .. }
+        | HoverTarget::TopLevelKey(_)
+        | HoverTarget::GlobEntry { .. } => None,
     }
 }
 
+/// Repo root and task label stored on a call hierarchy item
+fn call_hierarchy_data(item: &CallHierarchyItem) -> Option<(PathBuf, String)> {
+    let data = item.data.as_ref()?;
+    Some((
+        PathBuf::from(data.get("root")?.as_str()?),
+        data.get("label")?.as_str()?.to_string(),
+    ))
+}
+
 fn task_definition_location(text: &str, uri: &Url, label: &str) -> Option<Location> {
     let parse = parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()).ok()?;
     let root = parse.value.as_ref()?.as_object()?;
@@ -830,6 +2388,36 @@ fn task_definition_location(text: &str, uri: &Url, label: &str) -> Option<Locati
     None
 }
 
+/// Checks of the document as a whole: package config rules, schema, semantic
+/// lints and version deprecations
+fn document_diagnostics(
+    text: &str,
+    uri: &Url,
+    value: &Value<'_>,
+    package: Option<&Package>,
+    workspace: &WorkspaceState,
+) -> Vec<Diagnostic> {
+    let Some(config) = value.as_object() else {
+        return Vec::new();
+    };
+    let mut diagnostics = package
+        .map(|package| package_config::diagnostics(text, config, package))
+        .unwrap_or_default();
+    diagnostics.extend(schema::diagnostics(text, value, package.is_some()));
+    diagnostics.extend(lint::diagnostics(
+        text,
+        uri,
+        config,
+        workspace.root_config.as_ref(),
+    ));
+    diagnostics.extend(deprecations::diagnostics(
+        text,
+        config,
+        workspace.turbo_version,
+    ));
+    diagnostics
+}
+
 fn report_invalid_packages_and_tasks(
     workspace: &WorkspaceState,
     text: &str,
@@ -882,35 +2470,69 @@ fn report_invalid_packages_and_tasks(
     }
 }
 
-async fn load_root_package(root: &Path) -> Option<Package> {
-    let package_json_path = root.join("package.json");
-    let content = tokio::fs::read_to_string(&package_json_path).await.ok()?;
-    let package_json: serde_json::Value = serde_json::from_str(&content).ok()?;
-    let scripts = package_json
-        .get("scripts")
-        .and_then(|value| value.as_object())
-        .map(|object| {
-            object
-                .iter()
-                .filter_map(|(key, value)| {
-                    value
-                        .as_str()
-                        .map(|script| (key.clone(), script.to_string()))
-                })
-                .collect()
-        })
-        .unwrap_or_default();
+/// Per-repo directory for effective config snapshots below the user's runtime or
+/// cache dir, which other users cannot write to unlike the shared temp dir
+fn effective_snapshot_dir(root: &Path) -> Option<PathBuf> {
+    let var = |name: &str| {
+        std::env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+    };
+    let base = var("XDG_RUNTIME_DIR")
+        .or_else(|| var("XDG_CACHE_HOME"))
+        .or_else(|| var("LOCALAPPDATA"))
+        .or_else(|| var("HOME").map(|home| home.join(".cache")))?;
+    let mut hasher = DefaultHasher::new();
+    root.hash(&mut hasher);
+    Some(
+        base.join("turbo-lsp-effective")
+            .join(format!("{:016x}", hasher.finish())),
+    )
+}
 
-    Some(Package {
-        name: package_json
-            .get("name")
-            .and_then(|value| value.as_str())
-            .unwrap_or("root")
-            .to_string(),
-        path: root.to_path_buf(),
-        package_json_path,
-        scripts,
-    })
+/// Replace `path` with `text` and mark it read-only, since edits would be lost
+///
+/// The file is created exclusively, so a symlink left in its place is never followed.
+async fn write_read_only(dir: &Path, path: &Path, text: &str) -> bool {
+    let mut builder = tokio::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    if builder.create(dir).await.is_err() {
+        return false;
+    }
+    // the previous snapshot is read-only, so it is removed rather than overwritten
+    let _ = tokio::fs::remove_file(path).await;
+    let written = match tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await
+    {
+        Ok(mut file) => file.write_all(text.as_bytes()).await.is_ok(),
+        Err(_) => false,
+    };
+    if !written {
+        return false;
+    }
+    let Ok(metadata) = tokio::fs::metadata(path).await else {
+        return false;
+    };
+    let mut permissions = metadata.permissions();
+    permissions.set_readonly(true);
+    tokio::fs::set_permissions(path, permissions).await.is_ok()
+}
+
+/// Run history label of `task` as defined in `package`'s turbo.json, or in the root one
+fn stats_label(package: Option<&str>, task: &str) -> String {
+    match package {
+        Some(package) if !task.contains('#') => format!("{package}#{task}"),
+        _ => task.to_string(),
+    }
+}
+
+fn parse_jsonc_value(text: &str) -> Option<serde_json::Value> {
+    jsonc_parser::parse_to_serde_value(text, &ParseOptions::default()).ok()
 }
 
 fn script_location(content: &str, path: &Path, task_name: &str) -> Option<Location> {
@@ -922,18 +2544,60 @@ fn script_location(content: &str, path: &Path, task_name: &str) -> Option<Locati
     Some(Location::new(uri, range))
 }
 
-async fn build_hover_markdown(repo_root: Option<&Path>, target: &HoverTarget) -> String {
-    let context = load_hover_context(repo_root).await;
-
+async fn build_hover_markdown(
+    stats: Option<&RunStats>,
+    context: Option<&HoverContext>,
+    target: &HoverTarget,
+) -> String {
     match target {
         HoverTarget::TopLevelKey(name) => top_level_hover(name),
-        HoverTarget::TaskName(name) => task_name_hover(name, context.as_ref()),
+        HoverTarget::TaskName(name) => {
+            let label = stats_label(
+                context.and_then(|context| context.package_name.as_deref()),
+                name,
+            );
+            task_name_hover(
+                name,
+                context,
+                stats.and_then(|stats| stats.for_label(&label)),
+            )
+        }
         HoverTarget::TaskField {
             task_name,
             field_name,
-        } => task_field_hover(task_name, field_name, context.as_ref()),
+        } => task_field_hover(task_name, field_name, context),
         HoverTarget::DependsOnEntry { task_name, entry } => {
-            depends_on_hover(task_name, entry, context.as_ref())
+            depends_on_hover(task_name, entry, context)
+        }
+        HoverTarget::GlobEntry {
+            task_name,
+            field_name,
+            entry,
+        } => {
+            let Some(context) = context else {
+                return format!(
+                    "### `{entry}`\n\nMatches unknown until package discovery succeeds."
+                );
+            };
+            let own = context.package_name.as_ref().and_then(|name| {
+                context
+                    .packages
+                    .iter()
+                    .find(|package| &package.name == name && package.path != context.root_path)
+            });
+            let bases = globs::bases(
+                &context.packages,
+                &context.root_path,
+                task_name.as_deref(),
+                own,
+            );
+            let (entry, field_name, root) =
+                (entry.clone(), field_name.clone(), context.root_path.clone());
+            tokio::task::spawn_blocking(move || {
+                globs::hover_markdown(&entry, &field_name, &bases, &root)
+            })
+            .await
+            .unwrap_or_default()
         }
     }
 }
@@ -942,23 +2606,9 @@ async fn build_hover_markdown(repo_root: Option<&Path>, target: &HoverTarget) ->
 struct HoverContext {
     packages: Vec<Package>,
     root_path: PathBuf,
-}
-
-async fn load_hover_context(repo_root: Option<&Path>) -> Option<HoverContext> {
-    let root = repo_root?;
-    let discovery = PackageDiscovery::new(root.to_path_buf());
-    let mut packages = discovery.discover_packages().await.ok()?;
-    if let Some(root_package) = load_root_package(root).await
-        && !packages
-            .iter()
-            .any(|package| package.path == root_package.path)
-    {
-        packages.push(root_package);
-    }
-    Some(HoverContext {
-        packages,
-        root_path: root.to_path_buf(),
-    })
+    root_config: Option<TurboConfig>,
+    /// Set when hovering inside a package-level turbo.json
+    package_name: Option<String>,
 }
 
 fn top_level_hover(name: &str) -> String {
@@ -982,7 +2632,11 @@ fn top_level_hover(name: &str) -> String {
     )
 }
 
-fn task_name_hover(name: &str, context: Option<&HoverContext>) -> String {
+fn task_name_hover(
+    name: &str,
+    context: Option<&HoverContext>,
+    stats: Option<&TaskStats>,
+) -> String {
     let package_line = context
         .map(|ctx| packages_for_task(ctx, name))
         .filter(|packages| !packages.is_empty())
@@ -1003,8 +2657,12 @@ fn task_name_hover(name: &str, context: Option<&HoverContext>) -> String {
             },
         );
 
+    let history = stats
+        .map(|stats| format!("\n\n{}", run_stats::hover_section(stats)))
+        .unwrap_or_default();
+
     format!(
-        "### `{name}`\n\nTurborepo task name.\n\n**Context**\n- Task key inside `tasks` / `pipeline`.\n{package_line}\n- Use `dependsOn` to wire execution order and cache boundaries.\n\n**Example**\n```jsonc\n{{\n  \"tasks\": {{\n    \"{name}\": {{\n      \"dependsOn\": [\"^build\"],\n      \"outputs\": [\"dist/**\"]\n    }}\n  }}\n}}\n```\n\n[Turbo task config docs]({})",
+        "### `{name}`\n\nTurborepo task name.\n\n**Context**\n- Task key inside `tasks` / `pipeline`.\n{package_line}\n- Use `dependsOn` to wire execution order and cache boundaries.{history}\n\n**Example**\n```jsonc\n{{\n  \"tasks\": {{\n    \"{name}\": {{\n      \"dependsOn\": [\"^build\"],\n      \"outputs\": [\"dist/**\"]\n    }}\n  }}\n}}\n```\n\n[Turbo task config docs]({})",
         docs::TASKS
     )
 }
@@ -1035,7 +2693,19 @@ fn task_field_hover(task_name: &str, field_name: &str, context: Option<&HoverCon
         },
     );
 
-    card.replace(
+    let inherited = context
+        .and_then(|ctx| {
+            package_config::inherited_value(
+                ctx.root_config.as_ref()?,
+                ctx.package_name.as_deref()?,
+                task_name,
+                field_name,
+            )
+        })
+        .map(|value| format!("\n\n**Inherited from root**\n```json\n{value}\n```\nThis package config overrides it."))
+        .unwrap_or_default();
+
+    let card = card.replace(
         "Workspace script usage unknown until package discovery succeeds.",
         &context.map_or_else(
             || "Workspace script usage unknown until package discovery succeeds.".to_string(),
@@ -1044,7 +2714,8 @@ fn task_field_hover(task_name: &str, field_name: &str, context: Option<&HoverCon
                 format!("{count} package(s) currently expose `{task_name}` as a script.")
             },
         ),
-    )
+    );
+    format!("{card}{inherited}")
 }
 
 fn depends_on_hover(task_name: &str, entry: &str, context: Option<&HoverContext>) -> String {
@@ -1133,14 +2804,58 @@ fn markdown_card_with_context(
 async fn main() {
     let stdin = io::stdin();
     let stdout = io::stdout();
-    let (service, socket) = LspService::new(TurboBackend::new);
+    let (service, socket) = LspService::build(TurboBackend::new)
+        .custom_method(
+            "turbo/effectiveConfig",
+            TurboBackend::effective_config_request,
+        )
+        .custom_method(
+            "window/workDoneProgress/cancel",
+            TurboBackend::cancel_progress,
+        )
+        .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
 
 #[cfg(test)]
 mod tests {
+    use tower_lsp::{LanguageServer, lsp_types::TextDocumentItem};
+
     use super::*;
 
+    const TURBO_JSON: &str = r#"{"tasks": {"build": {}}}"#;
+
+    /// Backend serving `dir` as its only workspace folder
+    fn backend_for(dir: &Path) -> LspService<TurboBackend> {
+        let (service, _socket) = LspService::new(TurboBackend::new);
+        service
+            .inner()
+            .workspace_folders
+            .lock()
+            .unwrap()
+            .push(dir.to_path_buf());
+        service
+    }
+
+    /// Opens the file at `path` with its on-disk text
+    fn open(backend: &TurboBackend, path: &Path) -> (Url, String) {
+        let uri = Url::from_file_path(path).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        let language = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("json");
+        backend.remember_open_file(&DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                language.to_string(),
+                1,
+                text.clone(),
+            ),
+        });
+        (uri, text)
+    }
+
     #[test]
     fn task_reference_parses_root_tasks() {
         let task_ref = TaskReference::parse("//#install:lsp");
@@ -1184,4 +2899,430 @@ mod tests {
             })
         );
     }
+
+    #[tokio::test]
+    async fn requests_route_to_the_document_repo_root() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-two-roots");
+        for (repo, packages) in [("one", &["web"][..]), ("two", &["api", "worker"][..])] {
+            dir.write(
+                &format!("{repo}/package.json"),
+                r#"{"name": "repo", "workspaces": ["packages/*"]}"#,
+            );
+            dir.write(&format!("{repo}/turbo.json"), TURBO_JSON);
+            for package in packages {
+                dir.write(
+                    &format!("{repo}/packages/{package}/package.json"),
+                    format!(r#"{{"name": "{package}", "scripts": {{"build": "tsc"}}}}"#),
+                );
+            }
+        }
+        let (service, _socket) = LspService::new(TurboBackend::new);
+        let backend = service.inner();
+        let folder = |repo: &str| WorkspaceFolder {
+            uri: Url::from_directory_path(dir.join(repo)).unwrap(),
+            name: repo.to_string(),
+        };
+        backend
+            .initialize(InitializeParams {
+                workspace_folders: Some(vec![folder("one"), folder("two")]),
+                ..InitializeParams::default()
+            })
+            .await
+            .unwrap();
+
+        let packages_lens = |repo: &str| {
+            let (uri, _) = open(backend, &dir.join(repo).join("turbo.json"));
+            async move {
+                let lenses = backend.code_lens(&uri).await.unwrap();
+                let lazy = lenses
+                    .into_iter()
+                    .find(|lens| lens.command.is_none())
+                    .unwrap();
+                backend.resolve_code_lens(lazy).await.command.unwrap().title
+            }
+        };
+        assert_eq!(packages_lens("one").await, "Run in 1 package…");
+        assert_eq!(packages_lens("two").await, "Run in 2 packages…");
+        assert_eq!(
+            backend.root_for(&Url::from_file_path(dir.join("two/packages/api/src/a.ts")).unwrap()),
+            Some(dir.join("two"))
+        );
+    }
+
+    #[tokio::test]
+    async fn rename_updates_turbo_jsonc_files() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-rename-jsonc");
+        dir.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        dir.write(
+            "turbo.jsonc",
+            "{\n  // tasks\n  \"tasks\": {\"check\": {}, \"test\": {\"dependsOn\": [\"check\"],},},\n}",
+        );
+        dir.write(
+            "apps/web/package.json",
+            r#"{"name": "web", "scripts": {"check": "tsc"}}"#,
+        );
+        dir.write(
+            "apps/web/turbo.jsonc",
+            r#"{"extends": ["//"], "tasks": {"check": {}}}"#,
+        );
+        let service = backend_for(&dir);
+        let backend = service.inner();
+        let (uri, text) = open(backend, &dir.join("turbo.jsonc"));
+        let offset = text.find("check").unwrap();
+
+        let edit = backend
+            .rename(&RenameParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    position: byte_offset_to_position(&text, offset),
+                },
+                new_name: "typecheck".to_string(),
+                work_done_progress_params: tower_lsp::lsp_types::WorkDoneProgressParams::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let Some(tower_lsp::lsp_types::DocumentChanges::Edits(edits)) = edit.document_changes
+        else {
+            panic!("expected document edits");
+        };
+        let files: Vec<(String, usize)> = edits
+            .iter()
+            .map(|edit| {
+                let path = edit.text_document.uri.to_file_path().unwrap();
+                let relative = path.strip_prefix(&*dir).unwrap();
+                (relative.display().to_string(), edit.edits.len())
+            })
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("turbo.jsonc".to_string(), 2),
+                ("apps/web/turbo.jsonc".to_string(), 1),
+                ("apps/web/package.json".to_string(), 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn workspace_symbols_include_turbo_jsonc_tasks() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-symbols-jsonc");
+        dir.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        dir.write(
+            "turbo.jsonc",
+            "{\n  // root\n  \"tasks\": {\"build\": {},},\n}",
+        );
+        dir.write(
+            "apps/web/package.json",
+            r#"{"name": "web", "scripts": {"lint": "eslint"}}"#,
+        );
+        dir.write(
+            "apps/web/turbo.jsonc",
+            r#"{"extends": ["//"], "tasks": {"lint": {}}}"#,
+        );
+        let service = backend_for(&dir);
+        let backend = service.inner();
+
+        let symbols: Vec<(String, String)> = backend
+            .workspace_symbols("")
+            .await
+            .into_iter()
+            .map(|symbol| {
+                let path = symbol.location.uri.to_file_path().unwrap();
+                let relative = path.strip_prefix(&*dir).unwrap();
+                (symbol.name, relative.display().to_string())
+            })
+            .collect();
+        assert!(symbols.contains(&("build".to_string(), "turbo.jsonc".to_string())));
+        assert!(symbols.contains(&("web#lint".to_string(), "apps/web/turbo.jsonc".to_string())));
+    }
+
+    #[tokio::test]
+    async fn call_hierarchy_follows_turbo_jsonc_depends_on() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-call-hierarchy-jsonc");
+        dir.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        dir.write(
+            "turbo.jsonc",
+            "{\n  // root\n  \"tasks\": {\"build\": {}, \"test\": {\"dependsOn\": [\"build\"]},},\n}",
+        );
+        let service = backend_for(&dir);
+        let backend = service.inner();
+        let (uri, text) = open(backend, &dir.join("turbo.jsonc"));
+        let offset = text.find("\"test\"").unwrap() + 1;
+
+        let items = backend
+            .prepare_call_hierarchy(&CallHierarchyPrepareParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: byte_offset_to_position(&text, offset),
+                },
+                work_done_progress_params: tower_lsp::lsp_types::WorkDoneProgressParams::default(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(items[0].name, "test");
+        assert_eq!(items[0].uri, uri);
+
+        let calls = backend.outgoing_calls(&items[0]).await.unwrap();
+        let callees: Vec<&str> = calls.iter().map(|call| call.to.name.as_str()).collect();
+        assert_eq!(callees, vec!["build"]);
+        assert_eq!(calls[0].to.uri, uri);
+    }
+
+    #[tokio::test]
+    async fn effective_config_uris_resolve_in_their_own_repo() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-effective-roots");
+        for repo in ["one", "two"] {
+            dir.write(
+                &format!("{repo}/package.json"),
+                r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+            );
+            dir.write(&format!("{repo}/turbo.json"), TURBO_JSON);
+            dir.write(
+                &format!("{repo}/apps/web/package.json"),
+                format!(r#"{{"name": "web-{repo}"}}"#),
+            );
+        }
+        let service = backend_for(&dir.join("one"));
+        let backend = service.inner();
+        let document = Url::from_file_path(dir.join("two/apps/web/package.json")).unwrap();
+
+        let uri = backend
+            .effective_config_uri(&[serde_json::json!(document.as_str())])
+            .unwrap();
+        let text = backend.effective_config(&uri).await.unwrap();
+        assert!(text.contains("`web-two`"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn effective_config_snapshots_are_rewritten_read_only() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-effective-snapshot");
+        let snapshots = dir.join("effective");
+        let path = snapshots.join("apps%2Fweb.jsonc");
+
+        assert!(write_read_only(&snapshots, &path, "{}").await);
+        assert!(write_read_only(&snapshots, &path, "{\"tasks\": {}}").await);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"tasks\": {}}");
+        assert!(std::fs::metadata(&path).unwrap().permissions().readonly());
+        assert!(
+            effective_snapshot_dir(&dir)
+                .is_none_or(|snapshots| !snapshots.starts_with(std::env::temp_dir()))
+        );
+    }
+
+    #[tokio::test]
+    async fn turbo_version_is_detected_again_when_turbo_is_reinstalled() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-version-cache");
+        dir.write("package.json", r#"{"name": "repo"}"#);
+        dir.write("turbo.json", TURBO_JSON);
+        let install = |version: &str| {
+            dir.write(
+                "node_modules/turbo/package.json",
+                format!(r#"{{"version": "{version}"}}"#),
+            );
+        };
+        install("2.1.0");
+        let service = backend_for(&dir);
+        let backend = service.inner();
+
+        let first = backend.workspace_state(&dir).await.unwrap();
+        assert_eq!(
+            first.turbo_version,
+            Some((TurboVersion::new(2, 1, 0), VersionSource::Installed))
+        );
+        assert!(Arc::ptr_eq(
+            &first,
+            &backend.workspace_state(&dir).await.unwrap()
+        ));
+
+        install("2.10.0");
+        let second = backend.workspace_state(&dir).await.unwrap();
+        assert_eq!(
+            second.turbo_version,
+            Some((TurboVersion::new(2, 10, 0), VersionSource::Installed))
+        );
+    }
+
+    #[tokio::test]
+    async fn loading_one_root_does_not_wait_for_another() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-root-locks");
+        for repo in ["a", "b"] {
+            dir.write(&format!("{repo}/package.json"), r#"{"name": "repo"}"#);
+            dir.write(&format!("{repo}/turbo.json"), TURBO_JSON);
+            dir.write(
+                &format!("{repo}/node_modules/turbo/package.json"),
+                r#"{"version": "2.1.0"}"#,
+            );
+        }
+        let service = backend_for(&dir);
+        let backend = service.inner();
+
+        let busy = backend.workspace_slot(&dir.join("a")).unwrap();
+        let _loading = busy.lock().await;
+        let other = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            backend.workspace_state(&dir.join("b")),
+        )
+        .await
+        .expect("root b waited for root a");
+        assert!(other.is_some());
+    }
+
+    #[tokio::test]
+    async fn watched_workspaces_reload_only_reported_files() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-watched-refresh");
+        dir.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        dir.write(
+            "apps/web/package.json",
+            r#"{"name": "web", "scripts": {"build": "next build"}}"#,
+        );
+        dir.write("turbo.json", TURBO_JSON);
+        dir.write("node_modules/turbo/package.json", r#"{"version": "2.1.0"}"#);
+        let service = backend_for(&dir);
+        let backend = service.inner();
+        backend
+            .watch_files_dynamically
+            .store(true, Ordering::Relaxed);
+        let first = backend.workspace_state(&dir).await.unwrap();
+
+        dir.write(
+            "apps/web/package.json",
+            r#"{"name": "web", "scripts": {"lint": "eslint"}}"#,
+        );
+        assert!(Arc::ptr_eq(
+            &first,
+            &backend.workspace_state(&dir).await.unwrap()
+        ));
+
+        backend
+            .invalidate_workspace_files(&[(
+                dir.join("apps/web/package.json"),
+                FileChangeType::CHANGED,
+            )])
+            .await;
+        let second = backend.workspace_state(&dir).await.unwrap();
+        assert!(second.task_packages.contains_key("lint"));
+    }
+
+    #[tokio::test]
+    async fn run_history_is_reread_when_a_summary_is_added() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-run-cache");
+        dir.write("package.json", r#"{"name": "repo"}"#);
+        dir.write("turbo.json", TURBO_JSON);
+        let summary = |id: &str, task: &str| {
+            format!(
+                r#"{{"id": "{id}", "tasks": [{{"taskId": "//#{task}", "task": "{task}",
+                    "execution": {{"startTime": 1000, "endTime": 1200}}}}]}}"#
+            )
+        };
+        dir.write(".turbo/runs/a.json", summary("a", "build"));
+        let service = backend_for(&dir);
+        let backend = service.inner();
+        backend.workspace_state(&dir).await.unwrap();
+
+        let first = backend.run_stats(&dir).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &backend.run_stats(&dir).await.unwrap()));
+        assert!(first.critical_path("build").is_some());
+        assert!(first.last_run("lint").is_none());
+
+        dir.write(".turbo/runs/b.json", summary("b", "lint"));
+        let runs_dir = std::fs::File::open(dir.join(".turbo/runs")).unwrap();
+        runs_dir
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(1))
+            .unwrap();
+        let second = backend.run_stats(&dir).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(second.last_run("lint").is_some());
+    }
+
+    #[tokio::test]
+    async fn package_config_inlay_hints_use_that_package_runs() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-package-stats");
+        dir.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        dir.write("turbo.json", TURBO_JSON);
+        dir.write("apps/web/package.json", r#"{"name": "web"}"#);
+        dir.write(
+            "apps/web/turbo.json",
+            r#"{"extends": ["//"], "tasks": {"build": {}}}"#,
+        );
+        dir.write("apps/api/package.json", r#"{"name": "api"}"#);
+        dir.write(
+            ".turbo/runs/a.json",
+            r#"{"id": "a", "tasks": [
+                {"taskId": "web#build", "task": "build", "execution": {"startTime": 0, "endTime": 1000}},
+                {"taskId": "api#build", "task": "build", "execution": {"startTime": 0, "endTime": 9000}}
+            ]}"#,
+        );
+        let service = backend_for(&dir);
+        let backend = service.inner();
+        let (uri, _) = open(backend, &dir.join("apps/web/turbo.json"));
+
+        let hints = backend.inlay_hints(&uri).await.unwrap();
+        let InlayHintLabel::String(label) = &hints[0].label else {
+            panic!("expected a string label");
+        };
+        assert!(label.starts_with("avg 1.0s"), "{label}");
+    }
+
+    #[tokio::test]
+    async fn env_names_are_cached_until_a_file_below_the_package_changes() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-env-cache");
+        dir.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        dir.write("turbo.json", TURBO_JSON);
+        dir.write("apps/web/package.json", r#"{"name": "web"}"#);
+        dir.write("apps/web/.env", "API_URL=x\n");
+        let service = backend_for(&dir);
+        let backend = service.inner();
+        backend
+            .watch_files_dynamically
+            .store(true, Ordering::Relaxed);
+        let workspace = backend.workspace_state(&dir).await.unwrap();
+        let web = workspace
+            .packages
+            .iter()
+            .find(|package| package.name == "web")
+            .unwrap()
+            .clone();
+
+        let names = || async {
+            let found = backend
+                .env_names(&dir, vec![(web.clone(), true)])
+                .await
+                .unwrap();
+            found[0].keys().cloned().collect::<Vec<_>>()
+        };
+        assert_eq!(names().await, vec!["API_URL"]);
+
+        dir.write("apps/web/.env", "API_URL=x\nSECRET=y\n");
+        assert_eq!(names().await, vec!["API_URL"]);
+
+        backend
+            .invalidate_workspace_files(&[(dir.join("apps/web/.env"), FileChangeType::CHANGED)])
+            .await;
+        assert_eq!(names().await, vec!["API_URL", "SECRET"]);
+    }
+
+    #[test]
+    fn parse_jsonc_value_accepts_comments_and_trailing_commas() {
+        let value = parse_jsonc_value("{\n  // root\n  \"tasks\": {\"build\": {},},\n}").unwrap();
+        assert!(value["tasks"]["build"].is_object());
+    }
 }
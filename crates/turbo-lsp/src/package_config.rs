@@ -0,0 +1,179 @@
+//! Package-level turbo.json (`packages/web/turbo.json`) rules and root inheritance
+
+use std::path::Path;
+
+use jsonc_parser::{ast::Object, common::Ranged};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+use turbo_core::{Package, TurboConfig};
+
+use crate::{ROOT_PACKAGE_NAME, byte_range_to_lsp_range, name_range};
+
+/// Package whose directory holds the turbo.json at `uri`, unless it is the repo root
+pub fn package_for_config<'a>(
+    packages: &'a [Package],
+    root: &Path,
+    uri: &Url,
+) -> Option<&'a Package> {
+    let path = uri.to_file_path().ok()?;
+    let dir = path.parent()?;
+    if dir == root {
+        return None;
+    }
+    packages.iter().find(|package| package.path == dir)
+}
+
+/// Rules turbo applies to package configs: `extends: ["//"]`, plain task keys, package scripts only
+pub fn diagnostics(text: &str, config: &Object<'_>, package: &Package) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_extends(text, config, &mut diagnostics);
+
+    for task_group_name in ["tasks", "pipeline"] {
+        let Some(task_group) = config.get_object(task_group_name) else {
+            continue;
+        };
+        for property in &task_group.properties {
+            let task_name = property.name.as_str();
+            let range = name_range(&property.name);
+
+            let (message, code) = if task_name.contains('#') {
+                (
+                    format!(
+                        "Package configs cannot use `package#task` keys; write `{}` instead.",
+                        task_name.rsplit('#').next().unwrap_or(task_name)
+                    ),
+                    "turbo:package-task-in-package-config",
+                )
+            } else if !package.scripts.contains_key(task_name) {
+                (
+                    format!(
+                        "The task `{task_name}` does not exist in package `{}`.",
+                        package.name
+                    ),
+                    "turbo:no-such-task-in-package",
+                )
+            } else {
+                continue;
+            };
+            diagnostics.push(Diagnostic {
+                message,
+                range: byte_range_to_lsp_range(text, range),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(code.to_string())),
+                ..Diagnostic::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+fn check_extends(text: &str, config: &Object<'_>, diagnostics: &mut Vec<Diagnostic>) {
+    let range = match config.get("extends") {
+        None => config.range.start..config.range.start + 1,
+        Some(extends) => {
+            let is_root = extends.value.as_array().is_some_and(|array| {
+                array.elements.len() == 1
+                    && array.elements[0]
+                        .as_string_lit()
+                        .is_some_and(|entry| entry.value == ROOT_PACKAGE_NAME)
+            });
+            if is_root {
+                return;
+            }
+            extends.value.start()..extends.value.end()
+        }
+    };
+    diagnostics.push(Diagnostic {
+        message: format!("Package configs must set `\"extends\": [\"{ROOT_PACKAGE_NAME}\"]`."),
+        range: byte_range_to_lsp_range(text, range),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String("turbo:invalid-extends".to_string())),
+        ..Diagnostic::default()
+    });
+}
+
+/// Root value of a task field that a package config overrides, as pretty JSON
+///
+/// A root `package#task` definition wins over the plain task, as it does in turbo.
+pub fn inherited_value(
+    root_config: &TurboConfig,
+    package_name: &str,
+    task_name: &str,
+    field_name: &str,
+) -> Option<String> {
+    let task = root_config
+        .get_task(&format!("{package_name}#{task_name}"))
+        .or_else(|| root_config.get_task(task_name))?;
+    let value = serde_json::to_value(task).ok()?.get(field_name)?.clone();
+    serde_json::to_string_pretty(&value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonc_parser::{CollectOptions, ParseOptions, parse_to_ast};
+
+    use super::*;
+
+    fn web() -> Package {
+        let mut package = Package::new("web", "/repo/apps/web");
+        package.scripts =
+            std::iter::once(("build".to_string(), "next build".to_string())).collect();
+        package
+    }
+
+    fn codes(text: &str) -> Vec<String> {
+        let parse =
+            parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()).unwrap();
+        let config = parse
+            .value
+            .as_ref()
+            .and_then(|value| value.as_object())
+            .unwrap();
+        diagnostics(text, config, &web())
+            .into_iter()
+            .filter_map(|diagnostic| match diagnostic.code {
+                Some(NumberOrString::String(code)) => Some(code),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn package_config_rules() {
+        assert_eq!(
+            codes(r#"{"extends": ["//"], "tasks": {"build": {}}}"#),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            codes(r#"{"extends": ["//"], "tasks": {"web#build": {}, "lint": {}}}"#),
+            vec![
+                "turbo:package-task-in-package-config",
+                "turbo:no-such-task-in-package"
+            ]
+        );
+        assert_eq!(
+            codes(r#"{"extends": ["ui"], "tasks": {}}"#),
+            vec!["turbo:invalid-extends"]
+        );
+        assert_eq!(codes(r#"{"tasks": {}}"#), vec!["turbo:invalid-extends"]);
+    }
+
+    #[test]
+    fn inherited_value_prefers_package_task() {
+        let root = TurboConfig::parse(
+            r#"{"tasks": {"build": {"outputs": ["dist/**"]}, "web#build": {"outputs": [".next/**"]}}}"#,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            inherited_value(&root, "web", "build", "outputs").as_deref(),
+            Some("[\n  \".next/**\"\n]")
+        );
+        assert_eq!(
+            inherited_value(&root, "ui", "build", "outputs").as_deref(),
+            Some("[\n  \"dist/**\"\n]")
+        );
+        assert_eq!(inherited_value(&root, "web", "build", "cache"), None);
+    }
+}
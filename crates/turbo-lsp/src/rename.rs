@@ -0,0 +1,188 @@
+//! Renaming a task across turbo.json keys, `dependsOn` / `with` entries and package.json scripts
+
+use std::ops::Range;
+
+use jsonc_parser::{CollectOptions, ParseOptions, ast::Value, parse_to_ast};
+use turbo_core::Package;
+
+use crate::{collapse_string_range, name_range};
+
+/// Task fields whose entries reference other tasks
+const REFERENCE_FIELDS: &[&str] = &["dependsOn", "with"];
+
+/// A task name occurrence: the plain task name and the byte range of that name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub task: String,
+    pub range: Range<usize>,
+}
+
+/// Task key or reference under the cursor
+pub fn task_at(text: &str, offset: usize) -> Option<Occurrence> {
+    occurrences(text, true)
+        .into_iter()
+        .find(|occurrence| occurrence.range.start <= offset && offset <= occurrence.range.end)
+}
+
+/// Ranges in a turbo.json naming `task`, with any `^` and `package#` prefix left intact
+pub fn turbo_json_edits(text: &str, task: &str) -> Vec<Range<usize>> {
+    occurrences(text, true)
+        .into_iter()
+        .filter(|occurrence| occurrence.task == task)
+        .map(|occurrence| occurrence.range)
+        .collect()
+}
+
+/// Range of the `scripts` key `name` in a package.json
+pub fn script_range(package_json: &str, name: &str) -> Option<Range<usize>> {
+    let parse = parse_to_ast(
+        package_json,
+        &CollectOptions::default(),
+        &ParseOptions::default(),
+    )
+    .ok()?;
+    let scripts = parse.value.as_ref()?.as_object()?.get_object("scripts")?;
+    let script = scripts.get(name)?;
+    Some(name_range(&script.name))
+}
+
+/// Why renaming `old` to `new` is not possible, checked before any edit is built
+pub fn conflicts(
+    packages: &[Package],
+    turbo_json_texts: &[&str],
+    old: &str,
+    new: &str,
+) -> Option<String> {
+    if new.is_empty()
+        || new.starts_with('^')
+        || new.contains('#')
+        || new.chars().any(char::is_whitespace)
+    {
+        return Some(format!("`{new}` is not a valid task name."));
+    }
+    if new == old {
+        return None;
+    }
+
+    let clashing: Vec<&str> = packages
+        .iter()
+        .filter(|package| package.scripts.contains_key(old) && package.scripts.contains_key(new))
+        .map(|package| package.name.as_str())
+        .collect();
+    if !clashing.is_empty() {
+        return Some(format!(
+            "`{new}` already exists in {}.",
+            clashing
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    turbo_json_texts
+        .iter()
+        .any(|text| {
+            occurrences(text, false)
+                .iter()
+                .any(|occurrence| occurrence.task == new)
+        })
+        .then(|| format!("A task named `{new}` is already defined in turbo.json."))
+}
+
+/// Task definition keys (`build`, `web#build`, `//#build`) and, with `references`,
+/// `dependsOn` / `with` entries
+fn occurrences(text: &str, references: bool) -> Vec<Occurrence> {
+    let Ok(parse) = parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()) else {
+        return Vec::new();
+    };
+    let Some(config) = parse.value.as_ref().and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut occurrences = Vec::new();
+    let tasks = ["tasks", "pipeline"]
+        .iter()
+        .filter_map(|group| config.get_object(group))
+        .flat_map(|tasks| &tasks.properties);
+    for task in tasks {
+        occurrences.push(task_part(task.name.as_str(), name_range(&task.name)));
+        let Some(fields) = task.value.as_object().filter(|_| references) else {
+            continue;
+        };
+        let entries = REFERENCE_FIELDS
+            .iter()
+            .filter_map(|name| fields.get_array(name))
+            .flat_map(|field| &field.elements)
+            .filter_map(Value::as_string_lit)
+            .filter(|string| !string.value.starts_with('$'));
+        for string in entries {
+            let range = collapse_string_range(string.range);
+            occurrences.push(task_part(string.value.trim_start_matches('^'), range));
+        }
+    }
+    occurrences
+}
+
+/// The task after any `package#` prefix; `range` covers `value` and ends at the same byte
+fn task_part(value: &str, range: Range<usize>) -> Occurrence {
+    let task = value.rsplit('#').next().unwrap_or(value);
+    Occurrence {
+        task: task.to_string(),
+        range: range.end - task.len()..range.end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::*;
+
+    const TURBO_JSON: &str = r#"{
+  "tasks": {
+    "typecheck": { "dependsOn": ["^typecheck"] },
+    "web#typecheck": {},
+    "test": { "dependsOn": ["typecheck", "web#typecheck", "build"], "with": ["//#typecheck"] }
+  }
+}"#;
+
+    #[test]
+    fn finds_every_occurrence_of_a_task() {
+        let ranges = turbo_json_edits(TURBO_JSON, "typecheck");
+        assert_eq!(ranges.len(), 6);
+        assert!(
+            ranges
+                .iter()
+                .all(|range| &TURBO_JSON[range.clone()] == "typecheck")
+        );
+
+        let offset = TURBO_JSON.find("\"web#typecheck\",").unwrap() + 7;
+        assert_eq!(
+            task_at(TURBO_JSON, offset).map(|occurrence| occurrence.task),
+            Some("typecheck".to_string())
+        );
+        assert_eq!(
+            script_range(r#"{"scripts": {"typecheck": "tsc"}}"#, "typecheck"),
+            Some(14..23)
+        );
+    }
+
+    #[test]
+    fn reports_conflicts_before_editing() {
+        let mut package = Package::new("web", "/repo/apps/web");
+        package.scripts = IndexMap::from([
+            ("typecheck".to_string(), "tsc".to_string()),
+            ("check-types".to_string(), "tsc".to_string()),
+        ]);
+        let packages = [package];
+        assert!(
+            conflicts(&packages, &[TURBO_JSON], "typecheck", "check-types")
+                .unwrap()
+                .contains("`web`")
+        );
+        assert!(conflicts(&[], &[TURBO_JSON], "typecheck", "test").is_some());
+        assert!(conflicts(&[], &[TURBO_JSON], "typecheck", "a#b").is_some());
+        assert_eq!(conflicts(&[], &[TURBO_JSON], "typecheck", "lint"), None);
+    }
+}
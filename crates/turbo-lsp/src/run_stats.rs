@@ -0,0 +1,286 @@
+//! Task statistics and critical paths from `.turbo/runs` for hovers, inlay hints and code lenses
+
+use std::{fmt::Write as _, path::Path, sync::Mutex};
+
+use indexmap::IndexMap;
+use turbo_core::{
+    CriticalPathAnalysis, RunSummary, TaskGraph, TaskStats, TaskSummary,
+    critical_path::DEFAULT_CONCURRENCY, runs,
+};
+
+use crate::TaskReference;
+
+/// Task statistics keyed both by task id and by bare task name
+#[derive(Debug, Default)]
+pub struct RunStats {
+    history: Vec<RunSummary>,
+    by_task_id: IndexMap<String, TaskStats>,
+    by_task_name: IndexMap<String, TaskStats>,
+    /// Critical paths computed so far, by task
+    critical_paths: Mutex<IndexMap<String, Option<CriticalPathAnalysis>>>,
+}
+
+/// Cache results and wall time of a task in the latest run that executed it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastRun {
+    pub cache_hits: usize,
+    pub tasks: usize,
+    pub duration_ms: u64,
+}
+
+impl RunStats {
+    pub async fn load(repo_root: &Path) -> Option<Self> {
+        let history = RunSummary::load_all(repo_root).await.ok()?;
+        if history.is_empty() {
+            return None;
+        }
+        Some(Self {
+            by_task_id: runs::task_stats(&history),
+            by_task_name: runs::task_name_stats(&history),
+            history,
+            critical_paths: Mutex::default(),
+        })
+    }
+
+    /// Stats for a task key as written in turbo.json (`build`, `web#build`, `//#lint`)
+    pub fn for_label(&self, label: &str) -> Option<&TaskStats> {
+        // turbo records package tasks as `pkg#task` and root tasks as `//#task`,
+        // which is exactly how they are written as turbo.json keys
+        let task_ref = TaskReference::parse(label);
+        if task_ref.package.is_some() {
+            self.by_task_id.get(label)
+        } else {
+            self.by_task_name.get(task_ref.task)
+        }
+    }
+
+    /// Critical path of `turbo run <task>` using the latest run that executed it
+    ///
+    /// Computed once per task for this history.
+    pub fn critical_path(&self, task: &str) -> Option<CriticalPathAnalysis> {
+        if let Some(analysis) = self
+            .critical_paths
+            .lock()
+            .ok()
+            .and_then(|paths| paths.get(task).cloned())
+        {
+            return analysis;
+        }
+        let analysis = self.analyze_critical_path(task);
+        if let Ok(mut paths) = self.critical_paths.lock() {
+            paths.insert(task.to_string(), analysis.clone());
+        }
+        analysis
+    }
+
+    fn analyze_critical_path(&self, task: &str) -> Option<CriticalPathAnalysis> {
+        let run = self
+            .history
+            .iter()
+            .rev()
+            .find(|run| run.tasks.iter().any(|t| t.task == task))?;
+        let graph = TaskGraph::from_summary(run, &self.by_task_id).for_task(task);
+        graph.analyze(DEFAULT_CONCURRENCY).ok()
+    }
+
+    /// Latest run of a turbo.json task key; a bare task name covers every package
+    pub fn last_run(&self, label: &str) -> Option<LastRun> {
+        let task_ref = TaskReference::parse(label);
+        let matches = |task: &&TaskSummary| {
+            if task_ref.package.is_some() {
+                task.task_id == label
+            } else {
+                task.task == task_ref.task
+            }
+        };
+        let run = self
+            .history
+            .iter()
+            .rev()
+            .find(|run| run.tasks.iter().any(|task| matches(&task)))?;
+        let tasks: Vec<&TaskSummary> = run.tasks.iter().filter(matches).collect();
+        let executions = tasks.iter().filter_map(|task| task.execution.as_ref());
+        let start = executions
+            .clone()
+            .map(|execution| execution.start_time)
+            .min();
+        let end = executions.map(|execution| execution.end_time).max();
+        Some(LastRun {
+            cache_hits: tasks.iter().filter(|task| task.is_cache_hit()).count(),
+            tasks: tasks.len(),
+            duration_ms: start
+                .zip(end)
+                .and_then(|(start, end)| u64::try_from(end - start).ok())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Code lens title for the latest run of a task
+pub fn last_run_title(last: &LastRun) -> String {
+    let cache = match (last.cache_hits, last.tasks) {
+        (0, _) => "cache miss".to_string(),
+        (hits, tasks) if hits == tasks => "cache hit".to_string(),
+        (hits, tasks) => format!("{hits}/{tasks} cached"),
+    };
+    format!("Last run: {cache} · {}", format_duration(last.duration_ms))
+}
+
+/// Code lens title for a critical path
+pub fn critical_path_title(analysis: &CriticalPathAnalysis) -> String {
+    format!(
+        "Critical path {} ({} tasks) · min {} @ {}",
+        format_duration(analysis.critical_path_ms),
+        analysis.critical_path.len(),
+        format_duration(analysis.lower_bound_ms),
+        analysis.concurrency,
+    )
+}
+
+/// Human-readable critical path report for `window/showMessage`
+pub fn critical_path_report(task: &str, analysis: &CriticalPathAnalysis) -> String {
+    let chain = analysis
+        .critical_path
+        .iter()
+        .map(|step| format!("{} ({})", step.task_id, format_duration(step.duration_ms)))
+        .collect::<Vec<_>>()
+        .join(" → ");
+    let mut report = format!(
+        "`{task}` critical path {}: {chain}. Estimated wall time {} at concurrency {}.",
+        format_duration(analysis.critical_path_ms),
+        format_duration(analysis.estimated_wall_time_ms),
+        analysis.concurrency,
+    );
+    if let Some(edge) = analysis.edges.first() {
+        let _ = write!(
+            report,
+            " Loosening `{}` → `{}` saves {}.",
+            edge.task_id,
+            edge.depends_on,
+            format_duration(edge.savings_ms)
+        );
+    }
+    report
+}
+
+/// Short single-line form for inlay hints
+pub fn inlay_label(stats: &TaskStats) -> String {
+    let mut label = format!(
+        "avg {} · p95 {} · {} cached",
+        format_duration(stats.average_ms),
+        format_duration(stats.p95_ms),
+        format_percent(stats.cache_hit_rate),
+    );
+    if let Some(code) = stats.last_exit_code.filter(|code| *code != 0) {
+        let _ = write!(label, " · last exit {code}");
+    }
+    label
+}
+
+/// Markdown section for task-name hovers
+pub fn hover_section(stats: &TaskStats) -> String {
+    let exit = stats
+        .last_exit_code
+        .map_or_else(|| "unknown".to_string(), |code| format!("`{code}`"));
+    format!(
+        "**Run history** ({runs} run(s) in `.turbo/runs`)\n- Average: {avg} · p95: {p95}\n- Cache hit rate: {rate} ({hits}/{runs})\n- Last exit code: {exit}",
+        runs = stats.runs,
+        avg = format_duration(stats.average_ms),
+        p95 = format_duration(stats.p95_ms),
+        rate = format_percent(stats.cache_hit_rate),
+        hits = stats.cache_hits,
+    )
+}
+
+fn format_duration(ms: u64) -> String {
+    match ms {
+        0..1_000 => format!("{ms}ms"),
+        1_000..60_000 => format!("{}.{}s", ms / 1_000, (ms % 1_000) / 100),
+        _ => format!("{}m {}s", ms / 60_000, (ms % 60_000) / 1_000),
+    }
+}
+
+fn format_percent(ratio: f64) -> String {
+    format!("{:.0}%", ratio * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(850), "850ms");
+        assert_eq!(format_duration(2_150), "2.1s");
+        assert_eq!(format_duration(192_000), "3m 12s");
+    }
+
+    #[test]
+    fn last_run_covers_every_package_of_a_task() {
+        let run = RunSummary::parse(
+            r#"{"tasks": [
+                {"taskId": "web#build", "task": "build", "cache": {"status": "HIT"},
+                 "execution": {"startTime": 1000, "endTime": 1200}},
+                {"taskId": "ui#build", "task": "build", "cache": {"status": "MISS"},
+                 "execution": {"startTime": 1100, "endTime": 3400}}
+            ]}"#,
+            None,
+        )
+        .unwrap();
+        let stats = RunStats {
+            history: vec![run],
+            ..RunStats::default()
+        };
+
+        let all = stats.last_run("build").unwrap();
+        assert_eq!(last_run_title(&all), "Last run: 1/2 cached · 2.4s");
+        let web = stats.last_run("web#build").unwrap();
+        assert_eq!(last_run_title(&web), "Last run: cache hit · 200ms");
+        assert_eq!(stats.last_run("lint"), None);
+    }
+
+    #[test]
+    fn critical_paths_are_computed_once_per_task() {
+        let run = RunSummary::parse(
+            r#"{"tasks": [
+                {"taskId": "web#build", "task": "build",
+                 "execution": {"startTime": 1000, "endTime": 1200}}
+            ]}"#,
+            None,
+        )
+        .unwrap();
+        let stats = RunStats {
+            history: vec![run],
+            ..RunStats::default()
+        };
+
+        let first = stats.critical_path("build").unwrap();
+        assert_eq!(first.critical_path_ms, 200);
+        assert!(stats.critical_path("lint").is_none());
+        let cached: Vec<_> = stats
+            .critical_paths
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(cached, vec!["build", "lint"]);
+        assert_eq!(stats.critical_path("build").unwrap().critical_path_ms, 200);
+    }
+
+    #[test]
+    fn inlay_label_mentions_failures() {
+        let stats = TaskStats {
+            runs: 4,
+            average_ms: 12_000,
+            p95_ms: 20_000,
+            last_exit_code: Some(1),
+            ..TaskStats::default()
+        };
+
+        assert_eq!(
+            inlay_label(&stats),
+            "avg 12.0s · p95 20.0s · 0% cached · last exit 1"
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Structural validation of turbo.json against an embedded copy of the schema
+//!
+//! `schema.json` mirrors the structure of `https://turborepo.dev/schema.json`
+//! (types, enums and allowed keys) so validation works offline; the build
+//! fails when its definitions allow other keys than the published schema.
+//! Only the keywords it uses are interpreted: `type`, `enum`, `properties`,
+//! `additionalProperties`, `items` and local `$ref`s.
+
+use std::{fmt::Write as _, ops::Range, sync::LazyLock};
+
+use jsonc_parser::{
+    ast::{Object, ObjectProp, Value as JsonValue},
+    common::Ranged,
+};
+use serde_json::{Value, json};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use crate::{byte_range_to_lsp_range, collapse_string_range, deprecations, name_range};
+
+static SCHEMA: LazyLock<Value> =
+    LazyLock::new(|| serde_json::from_str(include_str!("../schema.json")).unwrap_or_default());
+
+/// Definitions a key can belong to, for misplaced-key hints
+const PLACES: &[(&str, &str)] = &[
+    ("RootSchema", "the root turbo.json"),
+    ("WorkspaceSchema", "package turbo.json files"),
+    ("Pipeline", "task definitions"),
+    ("RemoteCache", "`remoteCache`"),
+];
+
+/// Wrong types, unknown or misplaced keys and invalid enum values in a turbo.json
+///
+/// Fixable diagnostics carry `{"title", "newText"}` in `data`; the new text
+/// replaces the diagnostic range.
+pub fn diagnostics(text: &str, config: &JsonValue<'_>, package_config: bool) -> Vec<Diagnostic> {
+    let name = if package_config {
+        "WorkspaceSchema"
+    } else {
+        "RootSchema"
+    };
+    let Some(schema) = definition(name) else {
+        return Vec::new();
+    };
+    let mut validator = Validator {
+        text,
+        diagnostics: Vec::new(),
+    };
+    validator.value(config, schema, Some(name));
+    validator.diagnostics
+}
+
+/// Keys the schema allows at the top of a root or package turbo.json
+pub fn config_keys(package_config: bool) -> Vec<&'static str> {
+    property_names(if package_config {
+        "WorkspaceSchema"
+    } else {
+        "RootSchema"
+    })
+}
+
+/// Keys the schema allows in a task definition
+pub fn task_fields() -> Vec<&'static str> {
+    property_names("Pipeline")
+}
+
+fn property_names(name: &str) -> Vec<&'static str> {
+    definition(name)
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object)
+        .map(|properties| properties.keys().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn definition(name: &str) -> Option<&'static Value> {
+    SCHEMA.get("definitions")?.get(name)
+}
+
+/// Follow a `$ref`, returning the schema and the definition it names
+fn resolve<'a>(
+    schema: &'static Value,
+    place: Option<&'a str>,
+) -> (&'static Value, Option<&'a str>) {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/definitions/"))
+        .and_then(|name| Some((definition(name)?, Some(name))))
+        .unwrap_or((schema, place))
+}
+
+struct Validator<'t> {
+    text: &'t str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn value(&mut self, value: &JsonValue<'_>, schema: &'static Value, place: Option<&str>) {
+        let (schema, place) = resolve(schema, place);
+
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(kind)) => vec![kind.as_str()],
+            Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|kind| has_type(value, kind)) {
+            self.type_error(value, &types);
+            return;
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && let JsonValue::StringLit(string) = value
+            && !allowed
+                .iter()
+                .any(|allowed| allowed == string.value.as_ref())
+        {
+            let allowed: Vec<&str> = allowed.iter().filter_map(Value::as_str).collect();
+            let expected = allowed
+                .iter()
+                .map(|value| format!("`{value}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let fix = closest(&string.value, allowed.iter().copied())
+                .map(|value| (format!("Replace with `{value}`"), value.to_string()));
+            self.push(
+                collapse_string_range(string.range),
+                "turbo:invalid-enum",
+                format!(
+                    "`{}` is not a valid value. Expected one of {expected}.",
+                    string.value
+                ),
+                fix,
+            );
+            return;
+        }
+
+        match value {
+            JsonValue::Object(object) => self.object(object, schema, place),
+            JsonValue::Array(array) => {
+                if let Some(items) = schema.get("items") {
+                    for element in &array.elements {
+                        self.value(element, items, None);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn object(&mut self, object: &Object<'_>, schema: &'static Value, place: Option<&str>) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for property in &object.properties {
+            if let Some(field) = properties.and_then(|fields| fields.get(property.name.as_str())) {
+                self.value(&property.value, field, None);
+                continue;
+            }
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => self.unknown_key(property, properties, place),
+                Some(additional @ Value::Object(_)) => {
+                    self.value(&property.value, additional, None);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn unknown_key(
+        &mut self,
+        property: &ObjectProp<'_>,
+        properties: Option<&'static serde_json::Map<String, Value>>,
+        place: Option<&str>,
+    ) {
+        let key = property.name.as_str();
+        if deprecations::covers(key) {
+            return;
+        }
+        let range = name_range(&property.name);
+
+        let home = PLACES
+            .iter()
+            .filter(|(name, _)| Some(*name) != place)
+            .find(|(name, _)| {
+                definition(name)
+                    .and_then(|schema| schema.get("properties"))
+                    .is_some_and(|fields| fields.get(key).is_some())
+            });
+        if let Some((_, description)) = home {
+            self.push(
+                range,
+                "turbo:misplaced-key",
+                format!("`{key}` is not allowed here; it belongs in {description}."),
+                None,
+            );
+            return;
+        }
+
+        let suggestion = properties
+            .and_then(|fields| closest(key, fields.keys().map(String::as_str)))
+            .filter(|suggestion| *suggestion != key);
+        let message = suggestion.map_or_else(
+            || format!("Unknown key `{key}`."),
+            |suggestion| format!("Unknown key `{key}`. Did you mean `{suggestion}`?"),
+        );
+        let fix = suggestion.map(|suggestion| {
+            (
+                format!("Replace with `{suggestion}`"),
+                suggestion.to_string(),
+            )
+        });
+        self.push(range, "turbo:unknown-key", message, fix);
+    }
+
+    fn type_error(&mut self, value: &JsonValue<'_>, types: &[&str]) {
+        let range = value.range().start..value.range().end;
+        let source = &self.text[range.clone()];
+        let expected = types.join(" or ");
+        let mut message = format!("Expected {expected}, found {}.", type_name(value));
+
+        // `"false"` for a boolean, `"dist/**"` for an array of globs
+        let unquoted = match value {
+            JsonValue::StringLit(string) => serde_json::from_str::<Value>(&string.value).ok(),
+            _ => None,
+        };
+        let fix = if let Some(unquoted) = unquoted.filter(|unquoted| {
+            types.iter().any(|kind| {
+                json_type(unquoted) == *kind || (*kind == "integer" && unquoted.is_i64())
+            })
+        }) {
+            let new_text = unquoted.to_string();
+            let _ = write!(message, " Remove the quotes: `{new_text}`.");
+            Some((format!("Replace with `{new_text}`"), new_text))
+        } else if types.contains(&"array") && !matches!(value, JsonValue::Object(_)) {
+            let new_text = format!("[{source}]");
+            let _ = write!(message, " Wrap the value in an array: `{new_text}`.");
+            Some(("Wrap in an array".to_string(), new_text))
+        } else {
+            None
+        };
+        self.push(range, "turbo:schema-type", message, fix);
+    }
+
+    fn push(
+        &mut self,
+        range: Range<usize>,
+        code: &str,
+        message: String,
+        fix: Option<(String, String)>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            message,
+            range: byte_range_to_lsp_range(self.text, range),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(code.to_string())),
+            data: fix.map(|(title, new_text)| json!({ "title": title, "newText": new_text })),
+            ..Diagnostic::default()
+        });
+    }
+}
+
+fn has_type(value: &JsonValue<'_>, kind: &str) -> bool {
+    match (value, kind) {
+        (JsonValue::NumberLit(number), "integer") => number.value.parse::<i64>().is_ok(),
+        (JsonValue::NumberLit(_), "number") => true,
+        _ => type_name(value) == kind,
+    }
+}
+
+const fn type_name(value: &JsonValue<'_>) -> &'static str {
+    match value {
+        JsonValue::StringLit(_) => "string",
+        JsonValue::NumberLit(_) => "number",
+        JsonValue::BooleanLit(_) => "boolean",
+        JsonValue::Object(_) => "object",
+        JsonValue::Array(_) => "array",
+        JsonValue::NullKeyword(_) => "null",
+    }
+}
+
+const fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::Null => "null",
+    }
+}
+
+/// Candidate closest to `name` by case-insensitive edit distance, if close enough to be a typo
+fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let threshold = (name.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == *b {
+                diagonal
+            } else {
+                1 + diagonal.min(row[j]).min(above)
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonc_parser::{CollectOptions, ParseOptions, parse_to_ast};
+
+    use super::*;
+
+    fn check(text: &str, package_config: bool) -> Vec<(String, Option<Value>)> {
+        let parse =
+            parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()).unwrap();
+        diagnostics(text, parse.value.as_ref().unwrap(), package_config)
+            .into_iter()
+            .map(|diagnostic| match diagnostic.code {
+                Some(NumberOrString::String(code)) => (code, diagnostic.data),
+                _ => (String::new(), diagnostic.data),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn embedded_schema_covers_documented_keys() {
+        // `extends` only exists in package configs
+        for (definitions, keys) in [
+            (
+                &["RootSchema", "WorkspaceSchema"][..],
+                crate::DOCUMENTED_TOP_LEVEL_KEYS,
+            ),
+            (&["Pipeline"][..], crate::DOCUMENTED_TASK_FIELDS),
+        ] {
+            for key in keys {
+                let properties = definitions
+                    .iter()
+                    .map(|name| &definition(name).unwrap()["properties"])
+                    .find(|properties| properties.get(key).is_some())
+                    .unwrap_or_else(|| {
+                        panic!("doc_links.ts documents `{key}`, but schema.json lacks it")
+                    });
+                // completion offers these values, so validation must accept them
+                if let Some(values) = crate::enum_values(key) {
+                    let allowed = &properties[key]["enum"];
+                    for (value, _) in values {
+                        assert!(
+                            allowed.as_array().unwrap().contains(&Value::from(*value)),
+                            "`{key}` value `{value}` missing from schema.json"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_types_enums_and_unknown_keys() {
+        let text = r#"{
+  "outputs": ["dist/**"],
+  "ui": "tiu",
+  "tasks": {
+    "build": { "cache": "false", "outputs": "dist/**", "dependson": [] }
+  }
+}"#;
+        assert_eq!(
+            check(text, false),
+            vec![
+                ("turbo:misplaced-key".to_string(), None),
+                (
+                    "turbo:invalid-enum".to_string(),
+                    Some(json!({"title": "Replace with `tui`", "newText": "tui"}))
+                ),
+                (
+                    "turbo:schema-type".to_string(),
+                    Some(json!({"title": "Replace with `false`", "newText": "false"}))
+                ),
+                (
+                    "turbo:schema-type".to_string(),
+                    Some(json!({"title": "Wrap in an array", "newText": "[\"dist/**\"]"}))
+                ),
+                (
+                    "turbo:unknown-key".to_string(),
+                    Some(json!({"title": "Replace with `dependsOn`", "newText": "dependsOn"}))
+                ),
+            ]
+        );
+        assert_eq!(
+            check(r#"{"extends": ["//"], "tags": ["ui"]}"#, true),
+            Vec::new()
+        );
+        assert_eq!(
+            check(r#"{"globalEnv": []}"#, true),
+            vec![("turbo:misplaced-key".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("ouputs", "outputs"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(closest("inptus", ["inputs", "outputs"]), Some("inputs"));
+        assert_eq!(closest("zzz", ["inputs"]), None);
+    }
+}
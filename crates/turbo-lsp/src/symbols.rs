@@ -0,0 +1,235 @@
+//! Document symbols (sections → tasks → fields) and workspace symbol search
+
+use std::ops::Range;
+
+use jsonc_parser::{
+    CollectOptions, ParseOptions,
+    ast::{Object, ObjectProp, Value},
+    common::Ranged,
+    parse_to_ast,
+};
+use tower_lsp::lsp_types::{DocumentSymbol, Location, SymbolInformation, SymbolKind};
+
+use crate::{byte_range_to_lsp_range, name_range};
+
+/// Longest field value shown as symbol detail
+const DETAIL_LEN: usize = 40;
+
+/// Outline of a turbo.json: top-level keys, tasks under `tasks` / `pipeline`, and their fields
+pub fn document_symbols(text: &str) -> Vec<DocumentSymbol> {
+    let Ok(parse) = parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()) else {
+        return Vec::new();
+    };
+    let Some(config) = parse.value.as_ref().and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    config
+        .properties
+        .iter()
+        .map(|section| {
+            let name = section.name.as_str();
+            match section.value.as_object() {
+                Some(tasks) if matches!(name, "tasks" | "pipeline") => {
+                    let children = tasks
+                        .properties
+                        .iter()
+                        .map(|task| {
+                            let fields = task
+                                .value
+                                .as_object()
+                                .map(|fields| field_symbols(text, fields));
+                            symbol(
+                                text,
+                                task,
+                                SymbolKind::FUNCTION,
+                                depends_on_detail(task),
+                                fields,
+                            )
+                        })
+                        .collect();
+                    symbol(text, section, SymbolKind::NAMESPACE, None, Some(children))
+                }
+                Some(fields) => symbol(
+                    text,
+                    section,
+                    SymbolKind::PROPERTY,
+                    None,
+                    Some(field_symbols(text, fields)),
+                ),
+                None => symbol(
+                    text,
+                    section,
+                    SymbolKind::PROPERTY,
+                    value_detail(text, &section.value),
+                    None,
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Task definition keys of a turbo.json with their key ranges
+pub fn task_definitions(text: &str) -> Vec<(String, Range<usize>)> {
+    let Ok(parse) = parse_to_ast(text, &CollectOptions::default(), &ParseOptions::default()) else {
+        return Vec::new();
+    };
+    let Some(config) = parse.value.as_ref().and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    ["tasks", "pipeline"]
+        .iter()
+        .filter_map(|group| config.get_object(group))
+        .flat_map(|tasks| &tasks.properties)
+        .map(|task| (task.name.as_str().to_string(), name_range(&task.name)))
+        .collect()
+}
+
+/// `scripts` keys of a package.json with their key ranges
+pub fn scripts(package_json: &str) -> Vec<(String, Range<usize>)> {
+    let Ok(parse) = parse_to_ast(
+        package_json,
+        &CollectOptions::default(),
+        &ParseOptions::default(),
+    ) else {
+        return Vec::new();
+    };
+    parse
+        .value
+        .as_ref()
+        .and_then(Value::as_object)
+        .and_then(|package| package.get_object("scripts"))
+        .map(|scripts| {
+            scripts
+                .properties
+                .iter()
+                .map(|script| (script.name.as_str().to_string(), name_range(&script.name)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Case-insensitive subsequence match, so `wdev` finds `web#dev`
+pub fn matches(query: &str, name: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|wanted| name.any(|c| c == wanted))
+}
+
+fn field_symbols(text: &str, fields: &Object<'_>) -> Vec<DocumentSymbol> {
+    fields
+        .properties
+        .iter()
+        .map(|field| {
+            symbol(
+                text,
+                field,
+                SymbolKind::FIELD,
+                value_detail(text, &field.value),
+                None,
+            )
+        })
+        .collect()
+}
+
+fn depends_on_detail(task: &ObjectProp<'_>) -> Option<String> {
+    let entries: Vec<&str> = task
+        .value
+        .as_object()?
+        .get_array("dependsOn")?
+        .elements
+        .iter()
+        .filter_map(Value::as_string_lit)
+        .map(|string| string.value.as_ref())
+        .collect();
+    (!entries.is_empty()).then(|| format!("→ {}", entries.join(", ")))
+}
+
+/// Single-line source text of a value, shortened
+fn value_detail(text: &str, value: &Value<'_>) -> Option<String> {
+    let source = text.get(value.range().start..value.range().end)?;
+    let detail = source.split_whitespace().collect::<Vec<_>>().join(" ");
+    if detail.chars().count() > DETAIL_LEN {
+        let short: String = detail.chars().take(DETAIL_LEN - 1).collect();
+        Some(format!("{short}…"))
+    } else {
+        Some(detail)
+    }
+}
+
+/// Workspace symbol for a task or script
+#[allow(deprecated)] // `SymbolInformation::deprecated` must still be initialized
+pub const fn information(name: String, location: Location, container: String) -> SymbolInformation {
+    SymbolInformation {
+        name,
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        deprecated: None,
+        location,
+        container_name: Some(container),
+    }
+}
+
+#[allow(deprecated)] // `DocumentSymbol::deprecated` must still be initialized
+fn symbol(
+    text: &str,
+    property: &ObjectProp<'_>,
+    kind: SymbolKind,
+    detail: Option<String>,
+    children: Option<Vec<DocumentSymbol>>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name: property.name.as_str().to_string(),
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: byte_range_to_lsp_range(text, property.range.start..property.range.end),
+        selection_range: byte_range_to_lsp_range(text, name_range(&property.name)),
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_symbols_nest_sections_tasks_and_fields() {
+        let text = r#"{
+  "$schema": "https://turborepo.dev/schema.json",
+  "tasks": {
+    "build": { "dependsOn": ["^build"], "outputs": ["dist/**"] }
+  },
+  "remoteCache": { "enabled": true }
+}"#;
+        let symbols = document_symbols(text);
+        let names: Vec<&str> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["$schema", "tasks", "remoteCache"]);
+
+        let build = &symbols[1].children.as_ref().unwrap()[0];
+        assert_eq!(build.kind, SymbolKind::FUNCTION);
+        assert_eq!(build.detail.as_deref(), Some("→ ^build"));
+        let fields = build.children.as_ref().unwrap();
+        assert_eq!(fields[1].name, "outputs");
+        assert_eq!(fields[1].detail.as_deref(), Some(r#"["dist/**"]"#));
+        assert_eq!(symbols[2].children.as_ref().unwrap()[0].name, "enabled");
+    }
+
+    #[test]
+    fn workspace_symbol_sources_and_matching() {
+        assert_eq!(
+            task_definitions(r#"{"tasks": {"web#dev": {}}}"#),
+            vec![("web#dev".to_string(), 12..19)]
+        );
+        assert_eq!(
+            scripts(r#"{"scripts": {"dev": "next dev"}}"#),
+            vec![("dev".to_string(), 14..17)]
+        );
+        assert!(matches("wdev", "web#dev"));
+        assert!(matches("WEB#", "web#dev"));
+        assert!(!matches("dew", "web#dev"));
+    }
+}
@@ -0,0 +1,538 @@
+//! `turbo.run`: spawning `turbo run` and streaming its output to the client
+
+use std::{
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use indexmap::IndexMap;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::Notify,
+    time::Instant,
+};
+use tower_lsp::{
+    Client,
+    lsp_types::{
+        InitializeParams, MessageType, NumberOrString, ProgressParams, ProgressParamsValue,
+        ShowDocumentParams, Url, WorkDoneProgress, WorkDoneProgressBegin,
+        WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+        notification::Progress, request::WorkDoneProgressCreate,
+    },
+};
+
+/// Cancellation handles of running tasks, keyed by progress token
+pub type Runs = Arc<Mutex<IndexMap<String, Arc<Notify>>>>;
+
+static NEXT_RUN: AtomicU64 = AtomicU64::new(1);
+
+/// Minimum gap between progress reports; every line still goes to the log
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a cancelled run gets to exit after each stop signal before the next
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// Arguments of `turbo.run`: a task label, then optional `{uri, filter, dryRun, force}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunRequest {
+    pub task: String,
+    /// Document the command came from, used to pick the repo root
+    pub uri: Option<Url>,
+    pub filter: Option<String>,
+    pub dry_run: bool,
+    pub force: bool,
+    /// Write the task graph here instead of running, then open it
+    pub graph: Option<PathBuf>,
+}
+
+impl RunRequest {
+    pub fn from_arguments(arguments: &[serde_json::Value]) -> Option<Self> {
+        let task = arguments.first()?.as_str()?.to_string();
+        let options = arguments.get(1);
+        let option = |name: &str| options.and_then(|options| options.get(name));
+        let flag = |name: &str| {
+            option(name)
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false)
+        };
+        Some(Self {
+            task,
+            uri: option("uri")
+                .and_then(serde_json::Value::as_str)
+                .and_then(|uri| Url::parse(uri).ok()),
+            filter: option("filter")
+                .and_then(serde_json::Value::as_str)
+                .map(ToString::to_string),
+            dry_run: flag("dryRun"),
+            force: flag("force"),
+            graph: None,
+        })
+    }
+
+    /// Arguments after `turbo`
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["run".to_string(), self.task.clone()];
+        if let Some(filter) = &self.filter {
+            args.extend(["--filter".to_string(), filter.clone()]);
+        }
+        if self.dry_run {
+            args.push("--dry-run".to_string());
+        }
+        if self.force {
+            args.push("--force".to_string());
+        }
+        if let Some(graph) = &self.graph {
+            args.push(format!("--graph={}", graph.display()));
+        }
+        args
+    }
+
+    /// Command line shown as the progress title and in the final message
+    pub fn title(&self) -> String {
+        format!("turbo {}", self.args().join(" "))
+    }
+}
+
+/// `Tasks:`, `Cached:` and `Time:` lines turbo prints after a run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    tasks: Option<String>,
+    cached: Option<String>,
+    time: Option<String>,
+}
+
+impl Summary {
+    pub fn observe(&mut self, line: &str) {
+        let Some((label, value)) = line.trim().split_once(':') else {
+            return;
+        };
+        let slot = match label {
+            "Tasks" => &mut self.tasks,
+            "Cached" => &mut self.cached,
+            "Time" => &mut self.time,
+            _ => return,
+        };
+        *slot = Some(value.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+
+    fn parts(&self) -> Vec<&str> {
+        [&self.tasks, &self.cached, &self.time]
+            .into_iter()
+            .filter_map(Option::as_deref)
+            .collect()
+    }
+}
+
+/// How a run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Exit code, `None` when killed by a signal
+    Exited(Option<i32>),
+    Cancelled,
+}
+
+/// Final message for a run, with the cache summary when turbo printed one
+pub fn finish_message(title: &str, outcome: Outcome, summary: &Summary) -> String {
+    let status = match outcome {
+        Outcome::Exited(Some(0)) => "succeeded".to_string(),
+        Outcome::Exited(Some(code)) => format!("failed with exit code {code}"),
+        Outcome::Exited(None) => "was terminated".to_string(),
+        Outcome::Cancelled => "was cancelled".to_string(),
+    };
+    let parts = summary.parts();
+    if parts.is_empty() {
+        format!("`{title}` {status}.")
+    } else {
+        format!("`{title}` {status}: {}.", parts.join(" · "))
+    }
+}
+
+/// Whether the client accepts server-initiated `window/workDoneProgress/create`
+pub fn supports_work_done_progress(params: &InitializeParams) -> bool {
+    params
+        .capabilities
+        .window
+        .as_ref()
+        .and_then(|window| window.work_done_progress)
+        .unwrap_or(false)
+}
+
+/// Remove terminal color and cursor escape sequences from a line of output
+pub fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            plain.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            // parameters end at the first byte in `@`..=`~`
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    plain
+}
+
+/// Run `turbo` in `root`, logging every line and ending with a message to the user
+///
+/// With `progress`, output is also reported as cancellable work-done progress;
+/// cancelling interrupts turbo and the tasks it started, killing them if they
+/// do not exit.
+pub async fn run(client: Client, runs: Runs, root: PathBuf, request: RunRequest, progress: bool) {
+    let title = request.title();
+    let token = format!("turbo-run-{}", NEXT_RUN.fetch_add(1, Ordering::Relaxed));
+    let progress = progress
+        && client
+            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: NumberOrString::String(token.clone()),
+            })
+            .await
+            .is_ok();
+    let cancel = Arc::new(Notify::new());
+    if let Ok(mut runs) = runs.lock() {
+        runs.insert(token.clone(), Arc::clone(&cancel));
+    }
+
+    report(
+        &client,
+        progress,
+        &token,
+        WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.clone(),
+            cancellable: Some(true),
+            message: None,
+            percentage: None,
+        }),
+    )
+    .await;
+    client
+        .log_message(MessageType::INFO, format!("$ {title}"))
+        .await;
+
+    let result = stream(
+        &client,
+        &root,
+        &request,
+        &cancel,
+        progress.then_some(&token),
+    )
+    .await;
+    if let Ok(mut runs) = runs.lock() {
+        runs.shift_remove(&token);
+    }
+
+    let graph = request
+        .graph
+        .as_ref()
+        .filter(|_| matches!(result, Ok((Outcome::Exited(Some(0)), _))))
+        .and_then(|graph| Url::from_file_path(graph).ok());
+    let (message, failed) = match result {
+        Ok((outcome, summary)) => (
+            finish_message(&title, outcome, &summary),
+            !matches!(outcome, Outcome::Exited(Some(0)) | Outcome::Cancelled),
+        ),
+        Err(e) => (format!("Failed to start `{title}`: {e}"), true),
+    };
+    report(
+        &client,
+        progress,
+        &token,
+        WorkDoneProgress::End(WorkDoneProgressEnd {
+            message: Some(message.clone()),
+        }),
+    )
+    .await;
+    let level = if failed {
+        MessageType::ERROR
+    } else {
+        MessageType::INFO
+    };
+    client.show_message(level, message).await;
+    if let Some(uri) = graph {
+        let _ = client
+            .show_document(ShowDocumentParams {
+                uri,
+                external: Some(true),
+                take_focus: None,
+                selection: None,
+            })
+            .await;
+    }
+}
+
+/// Signal the run behind a progress token, if still running, to stop
+pub fn cancel(runs: &Runs, token: &NumberOrString) {
+    let NumberOrString::String(token) = token else {
+        return;
+    };
+    if let Some(cancel) = runs.lock().ok().and_then(|runs| runs.get(token).cloned()) {
+        cancel.notify_one();
+    }
+}
+
+async fn stream(
+    client: &Client,
+    root: &std::path::Path,
+    request: &RunRequest,
+    cancel: &Notify,
+    token: Option<&String>,
+) -> std::io::Result<(Outcome, Summary)> {
+    let mut command = tokio::process::Command::new("turbo");
+    command
+        .args(request.args())
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // own group, so cancelling reaches the task processes turbo spawned too
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command.spawn()?;
+    let mut group = RunGroup(child.id());
+    let mut stdout = child.stdout.take().map(|out| BufReader::new(out).lines());
+    let mut stderr = child.stderr.take().map(|err| BufReader::new(err).lines());
+    let mut summary = Summary::default();
+    let mut last_report: Option<Instant> = None;
+    // once cancelled: when to send the next stop signal, and how many were sent
+    let mut stopping: Option<(Instant, usize)> = None;
+
+    loop {
+        let line = tokio::select! {
+            () = cancel.notified(), if stopping.is_none() => {
+                stopping = Some((Instant::now(), 0));
+                continue;
+            }
+            () = tokio::time::sleep_until(stopping.map_or_else(Instant::now, |(at, _)| at)),
+                if stopping.is_some() =>
+            {
+                let sent = stopping.map_or(0, |(_, sent)| sent);
+                if !group.interrupt(sent) {
+                    group.kill();
+                    child.kill().await?;
+                    return Ok((Outcome::Cancelled, summary));
+                }
+                stopping = Some((Instant::now() + STOP_GRACE, sent + 1));
+                continue;
+            }
+            line = next_line(stdout.as_mut()), if stdout.is_some() => {
+                line.or_else(|| { stdout = None; None })
+            }
+            line = next_line(stderr.as_mut()), if stderr.is_some() => {
+                line.or_else(|| { stderr = None; None })
+            }
+            status = child.wait(), if stdout.is_none() && stderr.is_none() => {
+                group.forget();
+                let outcome = match stopping {
+                    Some(_) => Outcome::Cancelled,
+                    None => Outcome::Exited(status?.code()),
+                };
+                return Ok((outcome, summary));
+            }
+        };
+        let Some(line) = line.map(|line| strip_ansi(&line)) else {
+            continue;
+        };
+        summary.observe(&line);
+        if let Some(token) = token.filter(|_| !line.trim().is_empty())
+            && last_report.is_none_or(|at| at.elapsed() >= REPORT_INTERVAL)
+        {
+            last_report = Some(Instant::now());
+            report(
+                client,
+                true,
+                token,
+                WorkDoneProgress::Report(WorkDoneProgressReport {
+                    cancellable: Some(true),
+                    message: Some(line.trim().to_string()),
+                    percentage: None,
+                }),
+            )
+            .await;
+        }
+        client.log_message(MessageType::LOG, line).await;
+    }
+}
+
+/// Process group of a run, led by turbo, killed when dropped before turbo was reaped
+///
+/// `kill_on_drop` alone only reaches turbo, not the tasks it started.
+struct RunGroup(Option<u32>);
+
+impl RunGroup {
+    /// Send the `sent`th stop signal to the group, `false` once none are left
+    fn interrupt(&self, sent: usize) -> bool {
+        #[cfg(unix)]
+        {
+            use rustix::process::Signal;
+
+            [Signal::INT, Signal::TERM]
+                .get(sent)
+                .is_some_and(|signal| self.signal(*signal))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = sent;
+            false
+        }
+    }
+
+    /// Kill every process left in the group, including ones ignoring stop signals
+    fn kill(&mut self) {
+        #[cfg(unix)]
+        self.signal(rustix::process::Signal::KILL);
+        self.0 = None;
+    }
+
+    /// Turbo was reaped, so its id may be reused by an unrelated group
+    const fn forget(&mut self) {
+        self.0 = None;
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: rustix::process::Signal) -> bool {
+        use rustix::process::{Pid, kill_process_group};
+
+        self.0
+            .and_then(|id| i32::try_from(id).ok())
+            .and_then(Pid::from_raw)
+            .is_some_and(|group| kill_process_group(group, signal).is_ok())
+    }
+}
+
+impl Drop for RunGroup {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Next line of a stream, `None` once it ends or fails
+async fn next_line<R: tokio::io::AsyncBufRead + Unpin>(
+    lines: Option<&mut tokio::io::Lines<R>>,
+) -> Option<String> {
+    lines?.next_line().await.ok().flatten()
+}
+
+async fn report(client: &Client, progress: bool, token: &str, value: WorkDoneProgress) {
+    if progress {
+        client
+            .send_notification::<Progress>(ProgressParams {
+                token: NumberOrString::String(token.to_string()),
+                value: ProgressParamsValue::WorkDone(value),
+            })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn builds_turbo_arguments() {
+        let request = RunRequest::from_arguments(&[
+            json!("build"),
+            json!({ "filter": "web", "dryRun": true, "force": true, "uri": "file:///repo/turbo.json" }),
+        ])
+        .unwrap();
+        assert_eq!(
+            request.title(),
+            "turbo run build --filter web --dry-run --force"
+        );
+        assert_eq!(request.uri.unwrap().path(), "/repo/turbo.json");
+
+        let plain = RunRequest::from_arguments(&[json!("web#lint")]).unwrap();
+        assert_eq!(plain.args(), vec!["run", "web#lint"]);
+        assert_eq!(RunRequest::from_arguments(&[]), None);
+    }
+
+    #[test]
+    fn summarizes_turbo_output() {
+        let mut summary = Summary::default();
+        for line in [
+            "web:build: cache hit, replaying logs",
+            " Tasks:    2 successful, 2 total",
+            "\u{1b}[1mCached:    1 cached, 2 total\u{1b}[0m",
+            "  Time:    1.2s",
+        ] {
+            summary.observe(&strip_ansi(line));
+        }
+        assert_eq!(
+            finish_message("turbo run build", Outcome::Exited(Some(0)), &summary),
+            "`turbo run build` succeeded: 2 successful, 2 total · 1 cached, 2 total · 1.2s."
+        );
+        assert_eq!(
+            finish_message(
+                "turbo run build",
+                Outcome::Exited(Some(1)),
+                &Summary::default()
+            ),
+            "`turbo run build` failed with exit code 1."
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn interrupt_signals_the_run_process_group() {
+        use std::os::unix::process::ExitStatusExt;
+
+        /// Signal that ended the child, `None` while it is still running
+        async fn wait(child: &mut tokio::process::Child, timeout: u64) -> Option<Option<i32>> {
+            tokio::time::timeout(Duration::from_millis(timeout), child.wait())
+                .await
+                .ok()
+                .map(|status| status.unwrap().signal())
+        }
+
+        /// Shell running `script` in its own group, once it printed its first line
+        async fn spawn(script: &str) -> tokio::process::Child {
+            let mut child = tokio::process::Command::new("sh")
+                .args(["-c", script])
+                .stdout(Stdio::piped())
+                .process_group(0)
+                .kill_on_drop(true)
+                .spawn()
+                .unwrap();
+            let stdout = child.stdout.as_mut().unwrap();
+            BufReader::new(stdout).lines().next_line().await.unwrap();
+            child
+        }
+
+        let mut child = spawn("sleep 30 & echo ready; wait").await;
+        let mut group = RunGroup(child.id());
+        assert!(group.interrupt(1));
+        assert_eq!(wait(&mut child, 5000).await, Some(Some(15)));
+        group.forget();
+        assert!(!group.interrupt(0));
+
+        // the shell and its `sleep` both ignore INT and TERM
+        let mut child = spawn("trap '' INT TERM; sleep 30 & echo ready; wait").await;
+        let mut group = RunGroup(child.id());
+        let leader = rustix::process::Pid::from_raw(child.id().unwrap().cast_signed()).unwrap();
+        assert!(group.interrupt(0) && group.interrupt(1));
+        assert!(!group.interrupt(2));
+        assert_eq!(wait(&mut child, 200).await, None);
+        group.kill();
+        assert_eq!(wait(&mut child, 5000).await, Some(Some(9)));
+        // `sleep` is reparented and reaped once killed, so its group disappears
+        for _ in 0..50 {
+            if rustix::process::test_kill_process_group(leader).is_err() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("`sleep` outlived the killed group");
+    }
+}
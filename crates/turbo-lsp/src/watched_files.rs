@@ -0,0 +1,238 @@
+//! `workspace/didChangeWatchedFiles` registration for files that change the workspace
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use tower_lsp::lsp_types::{
+    DidChangeWatchedFilesRegistrationOptions, FileSystemWatcher, GlobPattern, InitializeParams,
+    OneOf, Registration, RelativePattern, Unregistration, Url, WatchKind,
+};
+use turbo_core::{config::CONFIG_FILES, env::SOURCE_EXTENSIONS, workspace::WORKSPACE_FILES};
+
+/// Registration id, needed to unregister later
+const REGISTRATION_ID: &str = "turbo-workspace-files";
+
+/// Registration id of the create/delete watchers for glob directories
+const GLOB_DIRS_REGISTRATION_ID: &str = "turbo-glob-dirs";
+
+const WATCHED_FILES_METHOD: &str = "workspace/didChangeWatchedFiles";
+
+/// Whether the client accepts `client/registerCapability` for file watchers
+pub fn supports_dynamic_registration(params: &InitializeParams) -> bool {
+    params
+        .capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.did_change_watched_files)
+        .and_then(|watched_files| watched_files.dynamic_registration)
+        .unwrap_or(false)
+}
+
+/// Whether the client accepts watchers relative to a base directory
+pub fn supports_relative_patterns(params: &InitializeParams) -> bool {
+    params
+        .capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.did_change_watched_files)
+        .and_then(|watched_files| watched_files.relative_pattern_support)
+        .unwrap_or(false)
+}
+
+/// Watchers for every turbo.json, package.json, workspace config and lockfile, and
+/// the `.env*` and source files variable names are collected from
+pub fn registration() -> Registration {
+    let watchers = CONFIG_FILES
+        .iter()
+        .chain(WORKSPACE_FILES)
+        .map(|name| format!("**/{name}"))
+        .chain([
+            "**/.env*".to_string(),
+            format!("**/*.{{{}}}", SOURCE_EXTENSIONS.join(",")),
+        ])
+        .map(|glob| FileSystemWatcher {
+            glob_pattern: GlobPattern::String(glob),
+            kind: None,
+        })
+        .collect();
+    watched_files_registration(REGISTRATION_ID, watchers)
+}
+
+/// Create/delete watchers for the directories glob diagnostics were evaluated in
+///
+/// Unlike one `**/*` watcher, this keeps the client from sending every file an
+/// install or build writes. Directories inside another one are covered by it.
+pub fn glob_dirs_registration(dirs: &BTreeSet<PathBuf>, relative: bool) -> Registration {
+    let mut covered: Vec<&Path> = Vec::new();
+    for dir in dirs {
+        if !covered.iter().any(|parent| dir.starts_with(parent)) {
+            covered.push(dir);
+        }
+    }
+    let watchers = covered
+        .into_iter()
+        .filter_map(|dir| glob_dir_watcher(dir, relative))
+        .collect();
+    watched_files_registration(GLOB_DIRS_REGISTRATION_ID, watchers)
+}
+
+/// Removes the watchers of [`glob_dirs_registration`]
+pub fn glob_dirs_unregistration() -> Unregistration {
+    Unregistration {
+        id: GLOB_DIRS_REGISTRATION_ID.to_string(),
+        method: WATCHED_FILES_METHOD.to_string(),
+    }
+}
+
+/// Watcher for files created or deleted in `dir`
+///
+/// Patterns start at the nearest existing ancestor, since a directory such as
+/// `dist` may only appear once the task runs.
+fn glob_dir_watcher(dir: &Path, relative: bool) -> Option<FileSystemWatcher> {
+    let base = dir.ancestors().find(|ancestor| ancestor.is_dir())?;
+    let missing = dir
+        .strip_prefix(base)
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?
+        .join("/");
+    let pattern = if missing.is_empty() {
+        "**/*".to_string()
+    } else {
+        format!("{{{missing},{missing}/**}}")
+    };
+    let glob_pattern = if relative {
+        GlobPattern::Relative(RelativePattern {
+            base_uri: OneOf::Right(Url::from_directory_path(base).ok()?),
+            pattern,
+        })
+    } else {
+        let base = base.to_str()?.replace('\\', "/");
+        GlobPattern::String(format!("{}/{pattern}", base.trim_end_matches('/')))
+    };
+    Some(FileSystemWatcher {
+        glob_pattern,
+        kind: Some(WatchKind::Create | WatchKind::Delete),
+    })
+}
+
+fn watched_files_registration(id: &str, watchers: Vec<FileSystemWatcher>) -> Registration {
+    Registration {
+        id: id.to_string(),
+        method: WATCHED_FILES_METHOD.to_string(),
+        register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+            watchers,
+        })
+        .ok(),
+    }
+}
+
+/// Whether a watched-file event can change the workspace
+///
+/// `**/package.json` also matches installed dependencies, and watcher globs
+/// cannot exclude them, so `node_modules` and `.git` are filtered here.
+pub fn is_workspace_file(uri: &Url) -> bool {
+    uri.path_segments().is_some_and(|mut segments| {
+        !segments.any(|segment| segment == "node_modules" || segment == ".git")
+    })
+}
+
+/// Whether a changed file can add, remove or reconfigure packages
+pub fn is_workspace_config(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| CONFIG_FILES.contains(&name) || WORKSPACE_FILES.contains(&name))
+}
+
+/// Whether a document is a turbo.json whose diagnostics depend on other files
+pub fn is_turbo_json(uri: &Url) -> bool {
+    uri.path_segments()
+        .and_then(Iterator::last)
+        .is_some_and(|name| CONFIG_FILES.contains(&name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registration_watches_package_and_turbo_json() {
+        let registration = registration();
+        let options = registration.register_options.unwrap();
+        let globs: Vec<_> = options["watchers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|watcher| watcher["globPattern"].as_str())
+            .collect();
+
+        assert!(globs.contains(&"**/turbo.json"));
+        assert!(globs.contains(&"**/package.json"));
+        assert!(globs.contains(&"**/pnpm-lock.yaml"));
+        assert!(globs.contains(&"**/.env*"));
+        assert!(!globs.contains(&"**/*"));
+        assert!(is_workspace_config(Path::new(
+            "/repo/apps/web/package.json"
+        )));
+        assert!(!is_workspace_config(Path::new(
+            "/repo/apps/web/src/index.ts"
+        )));
+        assert!(is_turbo_json(
+            &Url::parse("file:///repo/apps/web/turbo.json").unwrap()
+        ));
+        assert!(!is_turbo_json(
+            &Url::parse("file:///repo/package.json").unwrap()
+        ));
+    }
+
+    #[test]
+    fn glob_dirs_are_watched_from_their_nearest_existing_ancestor() {
+        let dir = turbo_core::test_util::TempDir::new("lsp-glob-dirs");
+        dir.write("apps/web/src/index.ts", "");
+        let dirs = BTreeSet::from([
+            dir.join("apps/web/src"),
+            dir.join("apps/web/src/components"),
+            dir.join("apps/web/dist/assets"),
+        ]);
+
+        let registration = glob_dirs_registration(&dirs, false);
+        let options = registration.register_options.unwrap();
+        let globs: Vec<_> = options["watchers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|watcher| watcher["globPattern"].as_str())
+            .collect();
+        let web = dir.join("apps/web").display().to_string();
+        assert_eq!(
+            globs,
+            vec![
+                format!("{web}/{{dist/assets,dist/assets/**}}"),
+                format!("{web}/src/**/*"),
+            ]
+        );
+
+        let registration = glob_dirs_registration(&dirs, true);
+        let options = registration.register_options.unwrap();
+        assert_eq!(
+            options["watchers"][1]["globPattern"]["baseUri"],
+            Url::from_directory_path(dir.join("apps/web/src"))
+                .unwrap()
+                .as_str()
+        );
+        assert_eq!(options["watchers"][1]["globPattern"]["pattern"], "**/*");
+    }
+
+    #[test]
+    fn dependency_files_are_not_workspace_files() {
+        let uri = |path: &str| Url::parse(&format!("file:///repo/{path}")).unwrap();
+        assert!(is_workspace_file(&uri("apps/web/package.json")));
+        assert!(!is_workspace_file(&uri("node_modules/react/package.json")));
+        assert!(!is_workspace_file(&uri(
+            "apps/web/node_modules/.pnpm/x/package.json"
+        )));
+    }
+}
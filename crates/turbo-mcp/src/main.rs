@@ -6,32 +6,44 @@
 //! - `turbo://config` - Full turbo.json configuration
 //! - `turbo://tasks` - List of defined tasks
 //! - `turbo://packages` - Workspace packages
+//! - `turbo://runs` - Run history (`.turbo/runs`) with per-task statistics
+//! - `turbo://runs/{id}` - A single parsed run summary
+//! - `turbo://cache` - Cache configuration, daemon status and local artifacts
+//! - `turbo://cache/{hash}` - Files stored in a local cache artifact
 //!
 //! ## Tools (actions)
 //! - `workdir` - Manage working directory (get/set)
 //! - `daemon` - Control turbo daemon (status/start/stop)
 //! - `run` - Execute turbo tasks
 //! - `graph` - Show task dependency graph
+//! - `explain_cache_miss` - Diff two run summaries for a task
+//! - `critical_path` - Critical path and parallelism analysis of a task
 
 use std::{path::PathBuf, process::Stdio, sync::Arc};
 
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use indexmap::IndexSet;
 use rmcp::{
-    ErrorData as McpError, ServiceExt,
+    ErrorData as McpError, Peer, ServiceExt,
     handler::server::{tool::ToolRouter, wrapper::Parameters},
     model::{
         Annotated, CallToolResult, Content, Icon, Implementation, InitializeResult,
-        ListResourcesResult, PaginatedRequestParams, RawResource, ReadResourceRequestParams,
-        ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo,
+        ListResourceTemplatesResult, ListResourcesResult, PaginatedRequestParams, RawResource,
+        RawResourceTemplate, ReadResourceRequestParams, ReadResourceResult, ResourceContents,
+        ResourceUpdatedNotificationParam, ServerCapabilities, ServerInfo, SubscribeRequestParams,
+        UnsubscribeRequestParams,
     },
     schemars::JsonSchema,
-    service::{RequestContext, RoleServer},
+    service::{NotificationContext, RequestContext, RoleServer},
     tool, tool_handler, tool_router,
     transport::stdio,
 };
 use serde::Deserialize;
 use tokio::sync::Mutex;
-use turbo_core::{PackageDiscovery, TurboConfig};
+use turbo_core::{
+    GcPolicy, LocalCache, PackageDiscovery, RunSummary, TaskGraph, TurboConfig, WorkspaceEvent,
+    WorkspaceWatcher, critical_path, runs, watcher,
+};
 
 /// Turbo icon SVG embedded at compile time
 const ICON_SVG: &str = include_str!("../../../resources/icon.svg");
@@ -63,7 +75,26 @@ const RESOURCE_DEFS: &[ResourceDef] = &[
     ResourceDef {
         uri: "turbo://cache",
         name: "Cache Status",
-        description: "Cache configuration and status",
+        description: "Cache configuration, status and local artifacts",
+    },
+    ResourceDef {
+        uri: "turbo://runs",
+        name: "Run History",
+        description: "Run summaries and per-task statistics",
+    },
+];
+
+/// Resource templates - parameterized variants of [`RESOURCE_DEFS`]
+const RESOURCE_TEMPLATE_DEFS: &[ResourceDef] = &[
+    ResourceDef {
+        uri: "turbo://runs/{id}",
+        name: "Run Summary",
+        description: "Parsed `turbo run --summarize` output",
+    },
+    ResourceDef {
+        uri: "turbo://cache/{hash}",
+        name: "Cache Artifact",
+        description: "Files stored in a local cache artifact",
     },
 ];
 
@@ -72,6 +103,34 @@ pub struct TurboServer {
     cwd: Arc<Mutex<PathBuf>>,
     tool_router: ToolRouter<Self>,
     instructions: String,
+    /// Resource URIs the client subscribed to
+    subscriptions: Arc<Mutex<IndexSet<String>>>,
+    /// Client handle for resource-updated notifications, set once initialized
+    peer: Arc<Mutex<Option<Peer<RoleServer>>>>,
+    /// Workspace watcher of the current workdir
+    watch_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+/// Resources whose content changes with the given workspace events
+fn affected_resources(events: &[WorkspaceEvent]) -> IndexSet<&'static str> {
+    let mut uris = IndexSet::new();
+    for event in events {
+        match event {
+            WorkspaceEvent::ConfigChanged { .. } => {
+                uris.extend(["turbo://config", "turbo://tasks", "turbo://cache"]);
+            }
+            WorkspaceEvent::PackageAdded { .. }
+            | WorkspaceEvent::PackageRemoved { .. }
+            | WorkspaceEvent::ScriptsChanged { .. }
+            | WorkspaceEvent::WorkspaceConfigChanged { .. } => {
+                uris.insert("turbo://packages");
+            }
+            WorkspaceEvent::RunsChanged { .. } => {
+                uris.insert("turbo://runs");
+            }
+        }
+    }
+    uris
 }
 
 impl Default for TurboServer {
@@ -167,6 +226,64 @@ pub struct InfoParams {
     /// Package name (empty = workspace info)
     #[serde(default)]
     pub package: Option<String>,
+    /// Also compare native package discovery with `turbo ls`
+    #[serde(default)]
+    pub cross_check: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExplainCacheMissParams {
+    /// Task id (e.g., `web#build`); empty = every task that missed
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Baseline run id (default: second most recent run)
+    #[serde(default)]
+    pub previous: Option<String>,
+    /// Run id to explain (default: most recent run)
+    #[serde(default)]
+    pub current: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CriticalPathParams {
+    /// Task to analyze (default: build)
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Concurrency to estimate wall time for (default: 10)
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// Run id to take the task graph from (default: `turbo run --dry=json`, then latest run)
+    #[serde(default)]
+    pub run: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheAction {
+    /// List local artifacts with size, age and producing task
+    Inspect,
+    /// Evict artifacts by size, age or run references
+    Gc,
+    /// Evict every artifact
+    Clear,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CacheParams {
+    /// Action: "inspect", "gc", or "clear"
+    pub action: CacheAction,
+    /// gc: evict the oldest artifacts until the cache fits in this many MiB
+    #[serde(default)]
+    pub max_size_mb: Option<u64>,
+    /// gc: evict artifacts older than this many days
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+    /// gc: evict artifacts not referenced by the last N runs in `.turbo/runs`
+    #[serde(default)]
+    pub keep_runs: Option<usize>,
+    /// Actually delete files; without it gc and clear only report what would be freed
+    #[serde(default)]
+    pub confirm: bool,
 }
 
 #[tool_router]
@@ -177,7 +294,11 @@ impl TurboServer {
         let tool_router = Self::tool_router();
 
         // Build instructions dynamically from registered tools and resources
-        let resource_uris: Vec<_> = RESOURCE_DEFS.iter().map(|r| r.uri).collect();
+        let resource_uris: Vec<_> = RESOURCE_DEFS
+            .iter()
+            .chain(RESOURCE_TEMPLATE_DEFS)
+            .map(|r| r.uri)
+            .collect();
         let tools = tool_router.list_all();
         let tool_names: Vec<_> = tools.iter().map(|t| t.name.as_ref()).collect();
 
@@ -192,6 +313,9 @@ impl TurboServer {
             cwd: Arc::new(Mutex::new(cwd)),
             tool_router,
             instructions,
+            subscriptions: Arc::new(Mutex::new(IndexSet::new())),
+            peer: Arc::new(Mutex::new(None)),
+            watch_task: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -209,6 +333,167 @@ impl TurboServer {
         PackageDiscovery::new(cwd)
     }
 
+    /// Repo root: the monorepo root above cwd, skipping package turbo.json files,
+    /// falling back to cwd
+    ///
+    /// Uses the same lookup as turbo-lsp, so both servers agree on the root.
+    async fn repo_root(&self) -> PathBuf {
+        let cwd = self.cwd.lock().await.clone();
+        TurboConfig::find_repo_root(&cwd).unwrap_or(cwd)
+    }
+
+    /// Resolve the (previous, current) run pair, defaulting to the last two runs
+    async fn run_pair(
+        &self,
+        previous: Option<&str>,
+        current: Option<&str>,
+    ) -> Result<(RunSummary, RunSummary), McpError> {
+        let root = self.repo_root().await;
+        let to_mcp = |e: turbo_core::Error| McpError::resource_not_found(e.to_string(), None);
+
+        let mut history = RunSummary::load_all(&root).await.map_err(to_mcp)?;
+        let current = match current {
+            Some(id) => RunSummary::load_by_id(&root, id).await.map_err(to_mcp)?,
+            None => history.pop().ok_or_else(|| {
+                McpError::resource_not_found("No run summaries; use `turbo run --summarize`", None)
+            })?,
+        };
+        let previous = match previous {
+            Some(id) => RunSummary::load_by_id(&root, id).await.map_err(to_mcp)?,
+            None => history
+                .into_iter()
+                .rev()
+                .find(|run| run.id != current.id && run.start_time() <= current.start_time())
+                .ok_or_else(|| {
+                    McpError::resource_not_found("Need at least two run summaries", None)
+                })?,
+        };
+        Ok((previous, current))
+    }
+
+    /// (Re)start watching the current repo and notify subscribers of changed resources
+    async fn watch_workspace(&self) {
+        let Some(peer) = self.peer.lock().await.clone() else {
+            return;
+        };
+        let root = self.repo_root().await;
+        let subscriptions = Arc::clone(&self.subscriptions);
+
+        let task = tokio::spawn(async move {
+            let mut watcher = match WorkspaceWatcher::start(&root, watcher::DEFAULT_DEBOUNCE).await
+            {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    tracing::warn!("Not watching {}: {e}", root.display());
+                    return;
+                }
+            };
+            while let Some(events) = watcher.next().await {
+                let subscribed = subscriptions.lock().await.clone();
+                for uri in affected_resources(&events) {
+                    if subscribed.contains(uri)
+                        && peer
+                            .notify_resource_updated(ResourceUpdatedNotificationParam::new(uri))
+                            .await
+                            .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        });
+
+        let previous = self.watch_task.lock().await.replace(task);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+
+    /// Local cache directories of the current repo
+    async fn local_caches(&self) -> Vec<LocalCache> {
+        let root = self.repo_root().await;
+        let config = self.load_config().await.ok();
+        LocalCache::discover(&root, config.as_ref())
+    }
+
+    /// Daemon status, remote cache settings and local cache inventories
+    async fn cache_status(&self) -> Result<serde_json::Value, McpError> {
+        let config = self.load_config().await.ok();
+        let cwd = self.cwd.lock().await.clone();
+
+        // Check daemon status for cache info
+        let daemon_output = tokio::process::Command::new("turbo")
+            .args(["daemon", "status"])
+            .current_dir(&cwd)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .ok();
+
+        let daemon_status = daemon_output
+            .as_ref()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string());
+
+        let history = RunSummary::load_all(&self.repo_root().await)
+            .await
+            .unwrap_or_default();
+        let caches = self.local_caches().await;
+        let local = tokio::task::spawn_blocking(move || {
+            caches
+                .iter()
+                .filter_map(|cache| cache.inspect(&history).ok())
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(serde_json::json!({
+            "cacheDir": config.as_ref().map(turbo_core::TurboConfig::cache_dir),
+            "remoteCache": config.as_ref().and_then(|c| c.remote_cache.as_ref()),
+            "daemonStatus": daemon_status,
+            "local": local
+        }))
+    }
+
+    /// Recorded runs (newest first) and per-task statistics
+    async fn run_history(&self) -> serde_json::Value {
+        let root = self.repo_root().await;
+        let history = RunSummary::load_all(&root).await.unwrap_or_default();
+
+        let runs: Vec<_> = history
+            .iter()
+            .rev()
+            .map(|run| {
+                serde_json::json!({
+                    "id": run.id,
+                    "uri": format!("turbo://runs/{}", run.id),
+                    "command": run.execution.as_ref().and_then(|e| e.command.as_ref()),
+                    "startTime": run.start_time(),
+                    "endTime": run.execution.as_ref().map(|e| e.end_time),
+                    "exitCode": run.execution.as_ref().and_then(|e| e.exit_code),
+                    "tasks": run.tasks.len(),
+                    "cached": run.tasks.iter().filter(|t| t.is_cache_hit()).count()
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "runs": runs,
+            "taskStats": runs::task_stats(&history)
+        })
+    }
+
+    /// Task graph for `task` from `turbo run --dry=json`
+    async fn dry_run_graph(&self, task: &str) -> Option<RunSummary> {
+        let output = self
+            .run_turbo(&["run", task, "--dry=json"])
+            .await
+            .ok()
+            .filter(|output| output.status.success())?;
+        RunSummary::parse(&String::from_utf8_lossy(&output.stdout), None).ok()
+    }
+
     async fn run_turbo(&self, args: &[&str]) -> Result<std::process::Output, McpError> {
         let cwd = self.cwd.lock().await.clone();
         tokio::process::Command::new("turbo")
@@ -257,6 +542,7 @@ impl TurboServer {
                     .map_err(|e| McpError::internal_error(format!("Path error: {e}"), None))?;
 
                 *self.cwd.lock().await = canonical.clone();
+                self.watch_workspace().await;
                 Ok(CallToolResult::success(vec![Content::text(format!(
                     "Set to: {}",
                     canonical.display()
@@ -343,6 +629,150 @@ impl TurboServer {
         )]))
     }
 
+    #[tool(description = "Explain cache misses by diffing two run summaries (default: last two)")]
+    async fn explain_cache_miss(
+        &self,
+        Parameters(p): Parameters<ExplainCacheMissParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let (previous, current) = self
+            .run_pair(p.previous.as_deref(), p.current.as_deref())
+            .await?;
+
+        let task_ids: Vec<&str> = p.task.as_deref().map_or_else(
+            || {
+                current
+                    .tasks
+                    .iter()
+                    .filter(|task| !task.is_cache_hit() && previous.task(&task.task_id).is_some())
+                    .map(|task| task.task_id.as_str())
+                    .collect()
+            },
+            |task| vec![task],
+        );
+
+        let explanations = task_ids
+            .into_iter()
+            .map(|task_id| runs::explain_cache_miss(&previous, &current, task_id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+
+        let response = serde_json::json!({
+            "previousRun": previous.id,
+            "currentRun": current.id,
+            "explanations": explanations
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap(),
+        )]))
+    }
+
+    #[tool(
+        description = "Critical path, minimum wall time and most valuable dependsOn cuts for a task"
+    )]
+    async fn critical_path(
+        &self,
+        Parameters(p): Parameters<CriticalPathParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let task = p.task.as_deref().unwrap_or("build");
+        let root = self.repo_root().await;
+        let history = RunSummary::load_all(&root).await.unwrap_or_default();
+
+        let (source, summary) = if let Some(id) = &p.run {
+            let summary = RunSummary::load_by_id(&root, id)
+                .await
+                .map_err(|e| McpError::resource_not_found(e.to_string(), None))?;
+            (format!("run {id}"), summary)
+        } else if let Some(summary) = self.dry_run_graph(task).await {
+            ("turbo run --dry=json".to_string(), summary)
+        } else {
+            let summary = history
+                .iter()
+                .rev()
+                .find(|run| {
+                    run.tasks
+                        .iter()
+                        .any(|t| t.task == task || t.task_id == task)
+                })
+                .cloned()
+                .ok_or_else(|| {
+                    McpError::resource_not_found(format!("No task graph found for `{task}`"), None)
+                })?;
+            (format!("run {}", summary.id), summary)
+        };
+
+        let graph = TaskGraph::from_summary(&summary, &runs::task_stats(&history)).for_task(task);
+        let analysis = graph
+            .analyze(p.concurrency.unwrap_or(critical_path::DEFAULT_CONCURRENCY))
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        let response = serde_json::json!({
+            "task": task,
+            "graphSource": source,
+            "runsUsedForDurations": history.len(),
+            "analysis": analysis
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap(),
+        )]))
+    }
+
+    #[tool(
+        description = "Inspect, garbage-collect or clear the local cache (gc/clear are dry runs unless confirm=true)"
+    )]
+    async fn cache(
+        &self,
+        Parameters(p): Parameters<CacheParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let policy = GcPolicy {
+            max_total_bytes: p.max_size_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+            max_age_ms: p.max_age_days.map(|days| days.saturating_mul(86_400_000)),
+            keep_runs: p.keep_runs,
+        };
+        if matches!(p.action, CacheAction::Gc) && policy.is_empty() {
+            return Err(McpError::invalid_params(
+                "gc needs max_size_mb, max_age_days or keep_runs",
+                None,
+            ));
+        }
+
+        let caches = self.local_caches().await;
+        if caches.is_empty() {
+            return Err(McpError::resource_not_found(
+                "No local cache directory found",
+                None,
+            ));
+        }
+        let history = RunSummary::load_all(&self.repo_root().await)
+            .await
+            .unwrap_or_default();
+
+        let action = p.action;
+        let dry_run = !p.confirm;
+        let reports = tokio::task::spawn_blocking(move || {
+            caches
+                .iter()
+                .map(|cache| match action {
+                    CacheAction::Inspect => cache.inspect(&history).map(|i| serde_json::json!(i)),
+                    CacheAction::Gc => cache
+                        .gc(&policy, &history, dry_run)
+                        .map(|r| serde_json::json!(r)),
+                    CacheAction::Clear => {
+                        cache.clear(&history, dry_run).map(|r| serde_json::json!(r))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&reports).unwrap(),
+        )]))
+    }
+
     #[tool(description = "Prune workspace to minimal subset for a package")]
     async fn prune(
         &self,
@@ -451,10 +881,16 @@ impl TurboServer {
         // Get turbo config
         let turbo_config = self.load_config().await.ok();
 
-        let response = serde_json::json!({
+        let mut response = serde_json::json!({
             "package": pkg_info,
             "turbo_config": turbo_config
         });
+        if p.cross_check {
+            response["discovery"] = match discovery.cross_check(&packages).await {
+                Ok(diff) => serde_json::json!({ "matches": diff.is_empty(), "diff": diff }),
+                Err(e) => serde_json::json!({ "error": e.to_string() }),
+            };
+        }
 
         Ok(CallToolResult::success(vec![Content::text(
             serde_json::to_string_pretty(&response).unwrap(),
@@ -463,11 +899,13 @@ impl TurboServer {
 }
 
 #[tool_handler(router = self.tool_router)]
+#[allow(clippy::unused_async_trait_impl)] // trait signatures are async; some handlers never await
 impl rmcp::ServerHandler for TurboServer {
     fn get_info(&self) -> ServerInfo {
         let capabilities = ServerCapabilities::builder()
             .enable_tools()
             .enable_resources()
+            .enable_resources_subscribe()
             .build();
         let icon = Icon::new(format!(
             "data:image/svg+xml;base64,{}",
@@ -484,6 +922,35 @@ impl rmcp::ServerHandler for TurboServer {
             .with_instructions(self.instructions.clone())
     }
 
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        *self.peer.lock().await = Some(context.peer);
+        self.watch_workspace().await;
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        if !RESOURCE_DEFS.iter().any(|def| def.uri == request.uri) {
+            return Err(McpError::resource_not_found(
+                format!("Unknown: {}", request.uri),
+                None,
+            ));
+        }
+        self.subscriptions.lock().await.insert(request.uri);
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.subscriptions.lock().await.shift_remove(&request.uri);
+        Ok(())
+    }
+
     async fn list_resources(
         &self,
         _request: Option<PaginatedRequestParams>,
@@ -515,6 +982,31 @@ impl rmcp::ServerHandler for TurboServer {
         })
     }
 
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        let templates = RESOURCE_TEMPLATE_DEFS
+            .iter()
+            .map(|def| {
+                Annotated::new(
+                    RawResourceTemplate {
+                        uri_template: def.uri.into(),
+                        name: def.name.into(),
+                        title: None,
+                        description: Some(def.description.into()),
+                        mime_type: Some("application/json".into()),
+                        icons: None,
+                    },
+                    None,
+                )
+            })
+            .collect();
+
+        Ok(ListResourceTemplatesResult::with_all_items(templates))
+    }
+
     async fn read_resource(
         &self,
         request: ReadResourceRequestParams,
@@ -550,31 +1042,47 @@ impl rmcp::ServerHandler for TurboServer {
                 )]))
             }
             "turbo://cache" => {
-                let config = self.load_config().await.ok();
-                let cwd = self.cwd.lock().await.clone();
-
-                // Check daemon status for cache info
-                let daemon_output = tokio::process::Command::new("turbo")
-                    .args(["daemon", "status"])
-                    .current_dir(&cwd)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .output()
-                    .await
-                    .ok();
-
-                let daemon_status = daemon_output
-                    .as_ref()
-                    .map(|o| String::from_utf8_lossy(&o.stdout).to_string());
+                let response = self.cache_status().await?;
+                Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    serde_json::to_string_pretty(&response).unwrap(),
+                    request.uri,
+                )]))
+            }
+            "turbo://runs" => {
+                let response = self.run_history().await;
+                Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    serde_json::to_string_pretty(&response).unwrap(),
+                    request.uri,
+                )]))
+            }
+            uri if uri.starts_with("turbo://cache/") => {
+                let hash = uri["turbo://cache/".len()..].to_string();
+                let caches = self.local_caches().await;
+                let files = tokio::task::spawn_blocking(move || {
+                    caches
+                        .iter()
+                        .find_map(|cache| cache.archive_files(&hash).ok())
+                })
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?
+                .ok_or_else(|| {
+                    McpError::resource_not_found(format!("No cache artifact: {uri}"), None)
+                })?;
 
-                let response = serde_json::json!({
-                    "cacheDir": config.as_ref().map(turbo_core::TurboConfig::cache_dir),
-                    "remoteCache": config.as_ref().and_then(|c| c.remote_cache.as_ref()),
-                    "daemonStatus": daemon_status
-                });
+                Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    serde_json::to_string_pretty(&files).unwrap(),
+                    request.uri,
+                )]))
+            }
+            uri if uri.starts_with("turbo://runs/") => {
+                let id = &uri["turbo://runs/".len()..];
+                let root = self.repo_root().await;
+                let summary = RunSummary::load_by_id(&root, id)
+                    .await
+                    .map_err(|e| McpError::resource_not_found(e.to_string(), None))?;
 
                 Ok(ReadResourceResult::new(vec![ResourceContents::text(
-                    serde_json::to_string_pretty(&response).unwrap(),
+                    serde_json::to_string_pretty(&summary).unwrap(),
                     request.uri,
                 )]))
             }
@@ -612,7 +1120,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 #[cfg(test)]
 mod tests {
-    use turbo_core::config::strip_json_comments;
+    use turbo_core::{config::strip_json_comments, test_util::TempDir};
+
+    use super::*;
 
     #[test]
     fn test_strip_json_comments() {
@@ -625,4 +1135,55 @@ mod tests {
         let input = r#"{"url": "https://example.com"}"#;
         assert_eq!(strip_json_comments(input), input);
     }
+
+    #[tokio::test]
+    async fn test_info_cross_check_is_opt_in() {
+        let root = TempDir::new("mcp-info");
+        root.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["apps/*"]}"#,
+        );
+        root.write("apps/web/package.json", r#"{"name": "web"}"#);
+        let server = TurboServer::new();
+        *server.cwd.lock().await = root.to_path_buf();
+
+        let info = |cross_check: bool| {
+            let server = server.clone();
+            async move {
+                let result = server
+                    .info(Parameters(InfoParams {
+                        package: None,
+                        cross_check,
+                    }))
+                    .await
+                    .unwrap();
+                let text = result.content[0].as_text().unwrap().text.clone();
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+
+        let plain = info(false).await;
+        assert_eq!(plain["package"]["name"], "web");
+        assert!(plain.get("discovery").is_none());
+        // either a diff or the `turbo ls` failure, depending on whether turbo is installed
+        let checked = info(true).await;
+        let discovery = checked["discovery"].as_object().unwrap();
+        assert!(discovery.contains_key("diff") || discovery.contains_key("error"));
+    }
+
+    #[tokio::test]
+    async fn test_repo_root_skips_package_turbo_json() {
+        let root = TempDir::new("mcp-repo-root");
+        root.write(
+            "package.json",
+            r#"{"name": "repo", "workspaces": ["packages/*"]}"#,
+        );
+        root.write("turbo.json", r#"{"tasks": {}}"#);
+        root.write("packages/web/package.json", r#"{"name": "web"}"#);
+        root.write("packages/web/turbo.json", r#"{"extends": ["//"]}"#);
+        let server = TurboServer::new();
+        *server.cwd.lock().await = root.join("packages/web");
+
+        assert_eq!(server.repo_root().await, root.path());
+    }
 }
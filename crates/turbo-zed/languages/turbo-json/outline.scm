@@ -1,3 +1,12 @@
-; Outline for turbo.json - show tasks in the outline view
-(pair
-  key: (string (string_content) @name)) @item
+; Outline for turbo.json - top-level sections and the tasks inside them
+(document
+  (object
+    (pair
+      key: (string (string_content) @name)) @item))
+
+(document
+  (object
+    (pair
+      value: (object
+        (pair
+          key: (string (string_content) @name)) @item))))